@@ -0,0 +1,163 @@
+use super::table::{AsnRange, AsnTable, AsnTableBuilder};
+use super::{AsnAnnouncement, AsnProvider, AsnRecord};
+use crate::error::Error;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr};
+use std::path::{Path, PathBuf};
+
+/// Add the ranges of an iptoasn.com style TSV file (`ip2asn-v4.tsv`,
+/// `ip2asn-v6.tsv`, `ip2asn-combined.tsv` or `ip2asn-v4-u32.tsv`) to
+/// `builder` and return the number of routed ranges added.
+///
+/// Each line holds `range_start`, `range_end`, `AS_number`, `country_code`
+/// and `AS_description`, separated by tabs. Ranges with AS 0 ("Not routed")
+/// are skipped.
+pub fn load_iptoasn(
+    content: &str,
+    source: &str,
+    builder: &mut AsnTableBuilder,
+) -> Result<usize, Error> {
+    let mut count = 0;
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+        let parse_error = |message: String| Error::data_parse(source, Some(line_number), message);
+        if fields.len() < 3 {
+            return Err(parse_error(format!(
+                "expected at least 3 tab-separated fields, found {}",
+                fields.len()
+            )));
+        }
+
+        let address = |text: &str| -> Result<IpAddr, Error> {
+            // The u32 variant of the IPv4 file uses integers
+            match text.parse::<u32>() {
+                Ok(value) => Ok(IpAddr::V4(Ipv4Addr::from(value))),
+                Err(_) => text
+                    .parse()
+                    .map_err(|_| parse_error(format!("invalid IP address '{}'", text))),
+            }
+        };
+        let start = address(fields[0])?;
+        let end = address(fields[1])?;
+        let asn: u32 = fields[2]
+            .parse()
+            .map_err(|_| parse_error(format!("invalid AS number '{}'", fields[2])))?;
+        if asn == 0 {
+            continue;
+        }
+
+        let country = fields
+            .get(3)
+            .filter(|country| !country.is_empty() && **country != "None");
+        let name = fields
+            .get(4)
+            .filter(|name| !name.is_empty() && **name != "Not routed");
+        builder.push(start, end, asn, source, line_number)?;
+        builder.set_as_info(asn, name.copied(), country.copied());
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// An iptoasn.com TSV file loaded into memory
+pub struct IpToAsnFile {
+    path: PathBuf,
+    table: AsnTable,
+}
+
+impl IpToAsnFile {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|e| Error::data_parse(&source, None, e))?;
+        let mut builder = AsnTableBuilder::new();
+        load_iptoasn(&content, &source, &mut builder)?;
+        Ok(IpToAsnFile {
+            path: path.to_path_buf(),
+            table: builder.build()?,
+        })
+    }
+
+    pub fn table(&self) -> &AsnTable {
+        &self.table
+    }
+}
+
+impl AsnProvider for IpToAsnFile {
+    fn source(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<AsnRecord>, Error> {
+        Ok(self.table.lookup(ip))
+    }
+
+    fn announced(&self, asn: u32) -> Result<AsnAnnouncement, Error> {
+        Ok(self.table.announced(asn))
+    }
+
+    fn routes(&self, ipv4: bool, start: u128, end: u128) -> Result<Vec<AsnRange>, Error> {
+        Ok(self.table.routes(ipv4, start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "\
+1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET
+1.0.1.0\t1.0.3.255\t0\tNone\tNot routed
+1.0.4.0\t1.0.7.255\t38803\tAU\tGTELECOM-AUSTRALIA Gtelecom Pty Ltd
+1.1.1.0\t1.1.1.255\t13335\tUS\tCLOUDFLARENET
+2606:4700::\t2606:4700:ffff:ffff:ffff:ffff:ffff:ffff\t13335\tUS\tCLOUDFLARENET
+";
+
+    fn table(content: &str) -> Result<AsnTable, Error> {
+        let mut builder = AsnTableBuilder::new();
+        load_iptoasn(content, "ip2asn-combined.tsv", &mut builder)?;
+        builder.build()
+    }
+
+    #[test]
+    fn test_load_iptoasn() {
+        let table = table(SAMPLE).unwrap();
+        assert_eq!(table.len(), (3, 1));
+
+        let record = table.lookup("1.0.5.1".parse().unwrap()).unwrap();
+        assert_eq!(record.asn, 38803);
+        assert_eq!(record.country.as_deref(), Some("AU"));
+        assert_eq!(
+            record.name.as_deref(),
+            Some("GTELECOM-AUSTRALIA Gtelecom Pty Ltd")
+        );
+        assert_eq!(record.network, "1.0.4.0/22");
+        assert!(table.lookup("1.0.2.1".parse().unwrap()).is_none());
+
+        let announced = table.announced(13335);
+        assert_eq!(announced.ipv4.len(), 2);
+        assert_eq!(announced.ipv6.len(), 1);
+    }
+
+    #[test]
+    fn test_load_u32_variant() {
+        let table = table("16777216\t16777471\t13335\tUS\tCLOUDFLARENET\n").unwrap();
+        assert_eq!(table.lookup("1.0.0.1".parse().unwrap()).unwrap().asn, 13335);
+    }
+
+    #[test]
+    fn test_invalid_line_is_reported() {
+        let err = table("1.0.0.0\t1.0.0.255\tAS13335\tUS\tCLOUDFLARENET\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse ip2asn-combined.tsv at line 1: invalid AS number 'AS13335'"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use super::{AsInfo, AsnAnnouncement, AsnProvider, AsnRange, AsnRecord};
+use crate::error::Error;
+use crate::geo::MmdbDatabase;
+use crate::ip_utils::{merge_ranges, network_range, value_address};
+use maxminddb::{MaxMindDBError, geoip2};
+use std::net::IpAddr;
+use std::path::Path;
+
+/// A MaxMind-format ASN database (GeoLite2-ASN or compatible)
+pub struct AsnMmdb {
+    db: MmdbDatabase,
+}
+
+impl AsnMmdb {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        MmdbDatabase::open(path).map(|db| AsnMmdb { db })
+    }
+
+    fn data_error(&self, e: MaxMindDBError) -> Error {
+        Error::data_parse(self.db.path().display().to_string(), None, e)
+    }
+}
+
+impl AsnProvider for AsnMmdb {
+    fn source(&self) -> String {
+        self.db.path().display().to_string()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<AsnRecord>, Error> {
+        let (record, prefix_len): (geoip2::Asn, usize) = match self.db.reader().lookup_prefix(ip) {
+            Ok(found) => found,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => return Ok(None),
+            Err(e) => return Err(self.data_error(e)),
+        };
+        let Some(asn) = record.autonomous_system_number else {
+            return Ok(None);
+        };
+
+        let (start, _) = network_range(ip, prefix_len as u8);
+        Ok(Some(AsnRecord {
+            asn,
+            name: record.autonomous_system_organization.map(str::to_string),
+            country: None,
+            network: format!("{}/{}", value_address(start, ip.is_ipv4()), prefix_len),
+        }))
+    }
+
+    fn announced(&self, asn: u32) -> Result<AsnAnnouncement, Error> {
+        let mut announcement = AsnAnnouncement::default();
+        for ipv4 in [true, false] {
+            let mut ranges = Vec::new();
+            self.db
+                .for_each_network(ipv4, |start, end, record: geoip2::Asn| {
+                    if record.autonomous_system_number == Some(asn) {
+                        ranges.push((start, end));
+                        if announcement.info.is_none() {
+                            announcement.info = Some(AsInfo {
+                                name: record.autonomous_system_organization.map(str::to_string),
+                                country: None,
+                            });
+                        }
+                    }
+                })?;
+            if ipv4 {
+                announcement.ipv4 = merge_ranges(ranges);
+            } else {
+                announcement.ipv6 = merge_ranges(ranges);
+            }
+        }
+        Ok(announcement)
+    }
+
+    fn routes(&self, ipv4: bool, start: u128, end: u128) -> Result<Vec<AsnRange>, Error> {
+        let mut routes = Vec::new();
+        self.db
+            .for_each_network(ipv4, |network_start, network_end, record: geoip2::Asn| {
+                if network_start <= end
+                    && network_end >= start
+                    && let Some(asn) = record.autonomous_system_number
+                {
+                    routes.push(AsnRange {
+                        start: network_start,
+                        end: network_end,
+                        asn,
+                    });
+                }
+            })?;
+        Ok(routes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/GeoLite2-ASN-Test.mmdb"
+    );
+
+    #[test]
+    fn test_lookup() {
+        let db = AsnMmdb::open(FIXTURE).unwrap();
+        let record = db.lookup("1.1.1.1".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(record.asn, 13335);
+        assert_eq!(record.name.as_deref(), Some("CLOUDFLARENET"));
+        assert_eq!(record.network, "1.1.1.0/24");
+
+        let record = db.lookup("2001:218::1".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(record.asn, 2914);
+        assert_eq!(record.network, "2001:218::/32");
+
+        assert_eq!(db.lookup("192.0.2.1".parse().unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_announced() {
+        let db = AsnMmdb::open(FIXTURE).unwrap();
+        let announced = db.announced(2519).unwrap();
+        // 1.0.16.0/24 and 1.0.17.0/24 merge into one range
+        assert_eq!(announced.ipv4, [(0x01001000, 0x010011ff)]);
+        assert!(announced.ipv6.is_empty());
+
+        let routes = db.routes(true, 0x01000000, 0x0100ffff).unwrap();
+        assert_eq!(routes.len(), 2);
+        assert_eq!(
+            announced.info.unwrap().name.as_deref(),
+            Some("ARTERIA Networks Corporation")
+        );
+    }
+}
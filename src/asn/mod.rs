@@ -0,0 +1,85 @@
+use crate::error::Error;
+use serde::Serialize;
+use std::net::IpAddr;
+
+mod iptoasn;
+mod mmdb;
+mod mrt;
+mod table;
+
+pub use iptoasn::{IpToAsnFile, load_iptoasn};
+pub use mmdb::AsnMmdb;
+pub use mrt::{MrtFile, load_mrt};
+pub use table::{AsnRange, AsnTable, AsnTableBuilder, describe_range};
+
+/// Origin AS of an address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AsnRecord {
+    pub asn: u32,
+    /// AS name or organization, when the data source provides one
+    pub name: Option<String>,
+    /// Country the AS is registered in, when the data source provides one
+    pub country: Option<String>,
+    /// Matched network (CIDR), or "start - end" for ranges that are not one block
+    pub network: String,
+}
+
+/// Name and registration country of an AS
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct AsInfo {
+    pub name: Option<String>,
+    pub country: Option<String>,
+}
+
+/// Address space originated by one AS, as merged inclusive integer ranges
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AsnAnnouncement {
+    pub info: Option<AsInfo>,
+    pub ipv4: Vec<(u128, u128)>,
+    pub ipv6: Vec<(u128, u128)>,
+}
+
+/// A source of IP-to-ASN data
+pub trait AsnProvider: Send + Sync {
+    /// File the data was loaded from, for reports
+    fn source(&self) -> String;
+
+    /// Look up the origin AS of an address. Returns `None` when it is not routed.
+    fn lookup(&self, ip: IpAddr) -> Result<Option<AsnRecord>, Error>;
+
+    /// Every address range originated by `asn`
+    fn announced(&self, asn: u32) -> Result<AsnAnnouncement, Error>;
+
+    /// Ranges overlapping the inclusive range `start..=end` of one family:
+    /// those covering it as well as more specific ones, in table order
+    fn routes(&self, ipv4: bool, start: u128, end: u128) -> Result<Vec<AsnRange>, Error>;
+}
+
+/// Parse an AS number written as "13335", "AS13335" or "as13335"
+pub fn parse_asn(text: &str) -> Option<u32> {
+    let digits = text
+        .strip_prefix("AS")
+        .or_else(|| text.strip_prefix("as"))
+        .or_else(|| text.strip_prefix("As"))
+        .unwrap_or(text);
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    digits.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_asn() {
+        assert_eq!(parse_asn("13335"), Some(13335));
+        assert_eq!(parse_asn("AS13335"), Some(13335));
+        assert_eq!(parse_asn("as4200000000"), Some(4_200_000_000));
+        assert_eq!(parse_asn("AS"), None);
+        assert_eq!(parse_asn("AS-13335"), None);
+        assert_eq!(parse_asn("+1"), None);
+        assert_eq!(parse_asn("4294967296"), None);
+    }
+}
@@ -0,0 +1,350 @@
+use super::table::{AsnRange, AsnTable, AsnTableBuilder};
+use super::{AsnAnnouncement, AsnProvider, AsnRecord};
+use crate::error::Error;
+use crate::ip_utils::{network_range, value_address};
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+const TABLE_DUMP_V2: u16 = 13;
+
+const RIB_IPV4_UNICAST: u16 = 2;
+const RIB_IPV6_UNICAST: u16 = 4;
+const RIB_IPV4_UNICAST_ADDPATH: u16 = 8;
+const RIB_IPV6_UNICAST_ADDPATH: u16 = 10;
+
+const ATTR_AS_PATH: u8 = 2;
+const ATTR_FLAG_EXTENDED_LENGTH: u8 = 0x10;
+
+const AS_SET: u8 = 1;
+const AS_SEQUENCE: u8 = 2;
+
+/// Bounds-checked reader over one MRT record
+struct Cursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Cursor { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| {
+                format!(
+                    "truncated: needs {} bytes at offset {}, {} left",
+                    len,
+                    self.position,
+                    self.data.len() - self.position
+                )
+            })?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        let bytes = self.take(2)?;
+        Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+}
+
+/// Origin AS of an AS_PATH attribute value: the last AS of the path, or the
+/// only member of a trailing AS_SET. Paths ending in a larger AS_SET (from
+/// aggregation) or carrying no AS have no single origin.
+///
+/// TABLE_DUMP_V2 always encodes AS numbers in 4 bytes (RFC 6396, 4.3.4).
+fn path_origin(value: &[u8]) -> Result<Option<u32>, String> {
+    let mut cursor = Cursor::new(value);
+    let mut origin = None;
+    while !cursor.is_empty() {
+        let segment_type = cursor.u8()?;
+        let count = cursor.u8()? as usize;
+        let mut last = None;
+        for _ in 0..count {
+            last = Some(cursor.u32()?);
+        }
+        origin = match segment_type {
+            AS_SEQUENCE => last.or(origin),
+            AS_SET if count == 1 => last,
+            AS_SET => None,
+            // Confederation segments are internal to the neighbouring AS
+            _ => origin,
+        };
+    }
+    Ok(origin)
+}
+
+/// Origin AS carried in a path attribute block, if any
+fn attributes_origin(attributes: &[u8]) -> Result<Option<u32>, String> {
+    let mut cursor = Cursor::new(attributes);
+    while !cursor.is_empty() {
+        let flags = cursor.u8()?;
+        let attribute_type = cursor.u8()?;
+        let len = if flags & ATTR_FLAG_EXTENDED_LENGTH != 0 {
+            cursor.u16()? as usize
+        } else {
+            cursor.u8()? as usize
+        };
+        let value = cursor.take(len)?;
+        if attribute_type == ATTR_AS_PATH {
+            return path_origin(value);
+        }
+    }
+    Ok(None)
+}
+
+/// Prefix and origin AS of a RIB_IPV4_UNICAST or RIB_IPV6_UNICAST record
+/// (or their ADD-PATH variants, RFC 8050). When peers disagree, the origin
+/// seen by most peers wins, ties going to the first one listed.
+fn parse_rib(body: &[u8], ipv4: bool, addpath: bool) -> Result<Option<(IpAddr, u8, u32)>, String> {
+    let mut cursor = Cursor::new(body);
+    let _sequence = cursor.u32()?;
+    let prefix_len = cursor.u8()?;
+    let max_len = if ipv4 { 32 } else { 128 };
+    if prefix_len > max_len {
+        return Err(format!("invalid prefix length /{}", prefix_len));
+    }
+
+    let mut octets = [0u8; 16];
+    let prefix_bytes = cursor.take(prefix_len.div_ceil(8) as usize)?;
+    octets[..prefix_bytes.len()].copy_from_slice(prefix_bytes);
+    let value = if ipv4 {
+        u32::from_be_bytes([octets[0], octets[1], octets[2], octets[3]]) as u128
+    } else {
+        u128::from_be_bytes(octets)
+    };
+    let network = value_address(value, ipv4);
+
+    let entry_count = cursor.u16()?;
+    let mut origins: Vec<(u32, usize)> = Vec::new();
+    for _ in 0..entry_count {
+        let _peer_index = cursor.u16()?;
+        let _originated = cursor.u32()?;
+        if addpath {
+            let _path_id = cursor.u32()?;
+        }
+        let attributes_len = cursor.u16()? as usize;
+        let attributes = cursor.take(attributes_len)?;
+        if let Some(asn) = attributes_origin(attributes)? {
+            match origins.iter_mut().find(|(origin, _)| *origin == asn) {
+                Some((_, votes)) => *votes += 1,
+                None => origins.push((asn, 1)),
+            }
+        }
+    }
+
+    // max_by_key returns the last maximum; iterate in reverse to prefer the first
+    let origin = origins
+        .iter()
+        .rev()
+        .max_by_key(|(_, votes)| *votes)
+        .map(|(asn, _)| *asn);
+    Ok(origin.map(|asn| (network, prefix_len, asn)))
+}
+
+/// Add the prefixes of an uncompressed MRT TABLE_DUMP_V2 RIB dump
+/// (RouteViews `rib.*`, RIPE RIS `bview.*`) to `builder` and return the
+/// number of prefixes with an origin AS.
+///
+/// Records of other MRT types and RIB subtypes (multicast, RIB_GENERIC) are
+/// skipped. Errors name the 1-based record number in place of a line number.
+pub fn load_mrt(data: &[u8], source: &str, builder: &mut AsnTableBuilder) -> Result<usize, Error> {
+    if data.starts_with(&[0x1f, 0x8b]) || data.starts_with(b"BZh") {
+        return Err(Error::data_parse(
+            source,
+            None,
+            "file is compressed; decompress it first (e.g. with gunzip or bunzip2)",
+        ));
+    }
+
+    let mut cursor = Cursor::new(data);
+    let mut record_number = 0;
+    let mut count = 0;
+
+    while !cursor.is_empty() {
+        record_number += 1;
+        let parse_error = |message: String| {
+            Error::data_parse(
+                source,
+                None,
+                format!("record {}: {}", record_number, message),
+            )
+        };
+        let header = cursor.take(12).map_err(parse_error)?;
+        let mrt_type = u16::from_be_bytes([header[4], header[5]]);
+        let subtype = u16::from_be_bytes([header[6], header[7]]);
+        let len = u32::from_be_bytes([header[8], header[9], header[10], header[11]]) as usize;
+        let body = cursor.take(len).map_err(parse_error)?;
+        if mrt_type != TABLE_DUMP_V2 {
+            continue;
+        }
+
+        let (ipv4, addpath) = match subtype {
+            RIB_IPV4_UNICAST => (true, false),
+            RIB_IPV6_UNICAST => (false, false),
+            RIB_IPV4_UNICAST_ADDPATH => (true, true),
+            RIB_IPV6_UNICAST_ADDPATH => (false, true),
+            _ => continue,
+        };
+        let Some((network, prefix_len, asn)) =
+            parse_rib(body, ipv4, addpath).map_err(parse_error)?
+        else {
+            continue;
+        };
+
+        // network_range also clears any stray host bits
+        let (start, end) = network_range(network, prefix_len);
+        builder.push(
+            value_address(start, ipv4),
+            value_address(end, ipv4),
+            asn,
+            source,
+            record_number,
+        )?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// One or more MRT RIB dumps loaded into memory
+pub struct MrtFile {
+    paths: Vec<PathBuf>,
+    table: AsnTable,
+}
+
+impl MrtFile {
+    /// Load the given dumps, e.g. separate IPv4 and IPv6 RIBs
+    pub fn open(paths: &[PathBuf]) -> Result<Self, Error> {
+        let mut builder = AsnTableBuilder::new();
+        for path in paths {
+            let source = path.display().to_string();
+            let data = fs::read(path).map_err(|e| Error::data_parse(&source, None, e))?;
+            load_mrt(&data, &source, &mut builder)?;
+        }
+        Ok(MrtFile {
+            paths: paths.to_vec(),
+            table: builder.build()?,
+        })
+    }
+
+    pub fn table(&self) -> &AsnTable {
+        &self.table
+    }
+}
+
+impl AsnProvider for MrtFile {
+    fn source(&self) -> String {
+        self.paths
+            .iter()
+            .map(|path| path.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<AsnRecord>, Error> {
+        Ok(self.table.lookup(ip))
+    }
+
+    fn announced(&self, asn: u32) -> Result<AsnAnnouncement, Error> {
+        Ok(self.table.announced(asn))
+    }
+
+    fn routes(&self, ipv4: bool, start: u128, end: u128) -> Result<Vec<AsnRange>, Error> {
+        Ok(self.table.routes(ipv4, start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/testdata/rib-test.mrt");
+
+    fn origin(table: &AsnTable, ip: &str) -> Option<u32> {
+        table.find(ip.parse().unwrap()).map(|range| range.asn)
+    }
+
+    #[test]
+    fn test_load_fixture() {
+        let mrt = MrtFile::open(&[PathBuf::from(FIXTURE)]).unwrap();
+        let table = mrt.table();
+        assert_eq!(table.len(), (6, 1));
+
+        assert_eq!(origin(table, "1.0.0.1"), Some(13335));
+        // Longest match between the /22 and the more specific /24
+        assert_eq!(origin(table, "1.0.4.1"), Some(4608));
+        assert_eq!(origin(table, "1.0.5.1"), Some(38803));
+        // AS_SET origin: skipped
+        assert_eq!(origin(table, "10.0.0.1"), None);
+        // Two of three peers see AS64497
+        assert_eq!(origin(table, "192.0.2.1"), Some(64497));
+        assert_eq!(origin(table, "198.51.100.1"), Some(64498));
+        // ADD-PATH record
+        assert_eq!(origin(table, "203.0.113.1"), Some(64499));
+        assert_eq!(origin(table, "2001:db8::1"), Some(64511));
+
+        let record = mrt.lookup("1.0.5.1".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(record.network, "1.0.4.0/22");
+        assert_eq!(mrt.source(), FIXTURE);
+    }
+
+    #[test]
+    fn test_path_origin() {
+        let path = |segments: &[(u8, &[u32])]| {
+            let mut value = Vec::new();
+            for (segment_type, asns) in segments {
+                value.push(*segment_type);
+                value.push(asns.len() as u8);
+                for asn in *asns {
+                    value.extend_from_slice(&asn.to_be_bytes());
+                }
+            }
+            path_origin(&value).unwrap()
+        };
+        assert_eq!(path(&[(AS_SEQUENCE, &[3356, 13335])]), Some(13335));
+        assert_eq!(path(&[(AS_SEQUENCE, &[3356]), (AS_SET, &[1, 2])]), None);
+        assert_eq!(
+            path(&[(AS_SEQUENCE, &[3356]), (AS_SET, &[64498])]),
+            Some(64498)
+        );
+        // Confederation segments do not change the origin
+        assert_eq!(path(&[(3, &[65001]), (AS_SEQUENCE, &[13335])]), Some(13335));
+        assert_eq!(path(&[]), None);
+    }
+
+    #[test]
+    fn test_errors() {
+        let data = fs::read(FIXTURE).unwrap();
+        let mut builder = AsnTableBuilder::new();
+        let err = load_mrt(&data[..40], "rib", &mut builder).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Failed to parse rib: record 1: truncated"),
+            "{}",
+            err
+        );
+
+        let err = load_mrt(b"BZh91AY&SY", "rib.bz2", &mut builder).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_DATA_PARSE);
+    }
+}
@@ -0,0 +1,409 @@
+use super::{AsInfo, AsnAnnouncement, AsnRecord};
+use crate::error::Error;
+use crate::ip_utils::{address_value, merge_ranges, range_to_cidrs, value_address};
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+/// An inclusive address range originated by one AS
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AsnRange {
+    pub start: u128,
+    pub end: u128,
+    pub asn: u32,
+}
+
+/// Ranges of one address family. `ranges` holds every range as loaded,
+/// sorted by start address with enclosing ranges first; `segments` splits
+/// the address space so that each piece points at the most specific range
+/// covering it.
+#[derive(Debug, Clone, Default)]
+struct FamilyTable {
+    ranges: Vec<AsnRange>,
+    segments: Vec<(u128, u128, usize)>,
+}
+
+/// Prefix-to-origin table with longest-match lookup. Ranges may nest (a
+/// more specific route inside a covering one) but must not partially overlap.
+#[derive(Debug, Clone, Default)]
+pub struct AsnTable {
+    ipv4: FamilyTable,
+    ipv6: FamilyTable,
+    info: BTreeMap<u32, AsInfo>,
+}
+
+/// Where a pending range came from, for error messages
+#[derive(Debug, Clone)]
+struct Origin {
+    source: String,
+    line: usize,
+}
+
+/// Collects ranges from one or more data sources and builds an `AsnTable`
+#[derive(Debug, Default)]
+pub struct AsnTableBuilder {
+    ipv4: Vec<(AsnRange, Origin)>,
+    ipv6: Vec<(AsnRange, Origin)>,
+    info: BTreeMap<u32, AsInfo>,
+}
+
+impl AsnTableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the inclusive range `start..=end` originated by `asn`. `source`
+    /// and `line` identify the record in error messages.
+    pub fn push(
+        &mut self,
+        start: IpAddr,
+        end: IpAddr,
+        asn: u32,
+        source: &str,
+        line: usize,
+    ) -> Result<(), Error> {
+        let ((start_ipv4, start_value), (end_ipv4, end_value)) =
+            (address_value(start), address_value(end));
+        let parse_error = |message: String| Error::data_parse(source, Some(line), message);
+        if start_ipv4 != end_ipv4 {
+            return Err(parse_error(format!(
+                "range {} - {} mixes IPv4 and IPv6",
+                start, end
+            )));
+        }
+        if start_value > end_value {
+            return Err(parse_error(format!(
+                "range start {} is after range end {}",
+                start, end
+            )));
+        }
+
+        let ranges = if start_ipv4 {
+            &mut self.ipv4
+        } else {
+            &mut self.ipv6
+        };
+        ranges.push((
+            AsnRange {
+                start: start_value,
+                end: end_value,
+                asn,
+            },
+            Origin {
+                source: source.to_string(),
+                line,
+            },
+        ));
+        Ok(())
+    }
+
+    /// Record the name and registration country of an AS; the first
+    /// non-empty value seen is kept
+    pub fn set_as_info(&mut self, asn: u32, name: Option<&str>, country: Option<&str>) {
+        let info = self.info.entry(asn).or_default();
+        if info.name.is_none() {
+            info.name = name.map(str::to_string);
+        }
+        if info.country.is_none() {
+            info.country = country.map(str::to_string);
+        }
+    }
+
+    /// Sort the ranges and index them for longest-match lookup. Ranges that
+    /// partially overlap are reported; of identical ranges with different
+    /// origins (MOAS) the first one loaded answers lookups.
+    pub fn build(self) -> Result<AsnTable, Error> {
+        Ok(AsnTable {
+            ipv4: Self::finish(self.ipv4, true)?,
+            ipv6: Self::finish(self.ipv6, false)?,
+            info: self.info,
+        })
+    }
+
+    fn finish(mut ranges: Vec<(AsnRange, Origin)>, ipv4: bool) -> Result<FamilyTable, Error> {
+        // Stable sort keeps identical ranges in load order
+        ranges.sort_by_key(|(range, _)| (range.start, Reverse(range.end)));
+
+        let mut segments = Vec::with_capacity(ranges.len());
+        // Open ranges enclosing the current position, outermost first
+        let mut stack: Vec<usize> = Vec::new();
+        // First address not yet assigned to a segment; `None` past the end of the space
+        let mut cursor: Option<u128> = None;
+
+        let close =
+            |index: usize, cursor: &mut Option<u128>, segments: &mut Vec<(u128, u128, usize)>| {
+                let end = ranges[index].0.end;
+                if let Some(start) = *cursor
+                    && start <= end
+                {
+                    segments.push((start, end, index));
+                    *cursor = end.checked_add(1);
+                }
+            };
+
+        for index in 0..ranges.len() {
+            let (range, origin) = &ranges[index];
+            while let Some(&top) = stack.last()
+                && ranges[top].0.end < range.start
+            {
+                close(top, &mut cursor, &mut segments);
+                stack.pop();
+            }
+
+            if let Some(&top) = stack.last() {
+                let (enclosing, enclosing_origin) = &ranges[top];
+                if range.end > enclosing.end {
+                    return Err(Error::data_parse(
+                        origin.source.clone(),
+                        Some(origin.line),
+                        format!(
+                            "range {} - {} partially overlaps {} - {} ({} line {})",
+                            value_address(range.start, ipv4),
+                            value_address(range.end, ipv4),
+                            value_address(enclosing.start, ipv4),
+                            value_address(enclosing.end, ipv4),
+                            enclosing_origin.source,
+                            enclosing_origin.line
+                        ),
+                    ));
+                }
+                if range.start == enclosing.start && range.end == enclosing.end {
+                    continue;
+                }
+                if let Some(start) = cursor
+                    && start < range.start
+                {
+                    segments.push((start, range.start - 1, top));
+                }
+            }
+
+            stack.push(index);
+            cursor = Some(range.start);
+        }
+        while let Some(top) = stack.pop() {
+            close(top, &mut cursor, &mut segments);
+        }
+
+        Ok(FamilyTable {
+            ranges: ranges.into_iter().map(|(range, _)| range).collect(),
+            segments,
+        })
+    }
+}
+
+/// Describe a range as CIDR notation when it is one block, else as "start - end"
+pub fn describe_range(start: u128, end: u128, ipv4: bool) -> String {
+    match range_to_cidrs(start, end, ipv4).as_slice() {
+        [(ip, prefix_len)] => format!("{}/{}", ip, prefix_len),
+        _ => format!(
+            "{} - {}",
+            value_address(start, ipv4),
+            value_address(end, ipv4)
+        ),
+    }
+}
+
+impl AsnTable {
+    /// Number of IPv4 and IPv6 ranges
+    pub fn len(&self) -> (usize, usize) {
+        (self.ipv4.ranges.len(), self.ipv6.ranges.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ipv4.ranges.is_empty() && self.ipv6.ranges.is_empty()
+    }
+
+    /// All ranges of one address family, sorted by start address with
+    /// enclosing ranges before the ranges they contain
+    pub fn ranges(&self, ipv4: bool) -> &[AsnRange] {
+        if ipv4 {
+            &self.ipv4.ranges
+        } else {
+            &self.ipv6.ranges
+        }
+    }
+
+    /// The most specific range containing an address
+    pub fn find(&self, ip: IpAddr) -> Option<&AsnRange> {
+        let (ipv4, value) = address_value(ip);
+        let family = if ipv4 { &self.ipv4 } else { &self.ipv6 };
+        let index = family
+            .segments
+            .partition_point(|segment| segment.0 <= value);
+        let (_, end, range) = family.segments.get(index.checked_sub(1)?)?;
+        (value <= *end).then(|| &family.ranges[*range])
+    }
+
+    /// Name and country recorded for an AS
+    pub fn as_info(&self, asn: u32) -> Option<&AsInfo> {
+        self.info.get(&asn)
+    }
+
+    /// Look up the origin AS of an address. Returns `None` when no range contains it.
+    pub fn lookup(&self, ip: IpAddr) -> Option<AsnRecord> {
+        let ipv4 = ip.is_ipv4();
+        self.find(ip).map(|range| {
+            let info = self.as_info(range.asn).cloned().unwrap_or_default();
+            AsnRecord {
+                asn: range.asn,
+                name: info.name,
+                country: info.country,
+                network: describe_range(range.start, range.end, ipv4),
+            }
+        })
+    }
+
+    /// Ranges overlapping `start..=end`, covering and more specific alike
+    pub fn routes(&self, ipv4: bool, start: u128, end: u128) -> Vec<AsnRange> {
+        let ranges = self.ranges(ipv4);
+        // Ranges are sorted by start, so none past this point can overlap
+        let candidates = ranges.partition_point(|range| range.start <= end);
+        ranges[..candidates]
+            .iter()
+            .filter(|range| range.end >= start)
+            .copied()
+            .collect()
+    }
+
+    /// Every address range originated by `asn`, merged
+    pub fn announced(&self, asn: u32) -> AsnAnnouncement {
+        let select = |ranges: &[AsnRange]| {
+            merge_ranges(
+                ranges
+                    .iter()
+                    .filter(|range| range.asn == asn)
+                    .map(|range| (range.start, range.end))
+                    .collect(),
+            )
+        };
+        AsnAnnouncement {
+            info: self.as_info(asn).cloned(),
+            ipv4: select(&self.ipv4.ranges),
+            ipv6: select(&self.ipv6.ranges),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn push(builder: &mut AsnTableBuilder, cidr: &str, asn: u32, line: usize) -> Result<(), Error> {
+        let (ip, prefix_len) = crate::ip_utils::parse_cidr(cidr).unwrap();
+        let (start, end) = crate::ip_utils::network_range(ip, prefix_len);
+        let ipv4 = ip.is_ipv4();
+        builder.push(
+            value_address(start, ipv4),
+            value_address(end, ipv4),
+            asn,
+            "rib",
+            line,
+        )
+    }
+
+    fn sample() -> AsnTable {
+        let mut builder = AsnTableBuilder::new();
+        push(&mut builder, "10.0.0.0/8", 64500, 1).unwrap();
+        push(&mut builder, "10.1.0.0/16", 64501, 2).unwrap();
+        push(&mut builder, "10.1.2.0/24", 64502, 3).unwrap();
+        push(&mut builder, "10.3.0.0/16", 64500, 4).unwrap();
+        push(&mut builder, "10.3.0.0/16", 64503, 5).unwrap();
+        push(&mut builder, "::/0", 64510, 6).unwrap();
+        push(&mut builder, "2001:db8::/32", 64511, 7).unwrap();
+        builder.set_as_info(64501, Some("EXAMPLE-NET"), Some("JP"));
+        builder.build().unwrap()
+    }
+
+    fn origin(table: &AsnTable, ip: &str) -> Option<u32> {
+        table.find(ip.parse().unwrap()).map(|range| range.asn)
+    }
+
+    #[test]
+    fn test_longest_match() {
+        let table = sample();
+        assert_eq!(origin(&table, "10.0.0.1"), Some(64500));
+        assert_eq!(origin(&table, "10.1.0.1"), Some(64501));
+        assert_eq!(origin(&table, "10.1.2.3"), Some(64502));
+        assert_eq!(origin(&table, "10.1.3.0"), Some(64501));
+        assert_eq!(origin(&table, "10.2.0.0"), Some(64500));
+        assert_eq!(origin(&table, "10.255.255.255"), Some(64500));
+        assert_eq!(origin(&table, "11.0.0.0"), None);
+        // MOAS: the first origin loaded answers
+        assert_eq!(origin(&table, "10.3.0.1"), Some(64500));
+        assert_eq!(origin(&table, "2001:db8::1"), Some(64511));
+        assert_eq!(origin(&table, "2001:db9::1"), Some(64510));
+        assert_eq!(
+            origin(&table, "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+            Some(64510)
+        );
+
+        let record = table.lookup("10.1.9.9".parse().unwrap()).unwrap();
+        assert_eq!(record.network, "10.1.0.0/16");
+        assert_eq!(record.name.as_deref(), Some("EXAMPLE-NET"));
+    }
+
+    #[test]
+    fn test_announced() {
+        let table = sample();
+        let announced = table.announced(64500);
+        assert_eq!(announced.ipv4.len(), 1);
+        assert_eq!(table.announced(64503).ipv4.len(), 1);
+        assert!(table.announced(1).ipv4.is_empty());
+        assert_eq!(
+            table.announced(64501).info.and_then(|info| info.name),
+            Some("EXAMPLE-NET".to_string())
+        );
+    }
+
+    #[test]
+    fn test_routes() {
+        let table = sample();
+        let asns = |start: u128, end: u128| -> Vec<u32> {
+            table
+                .routes(true, start, end)
+                .iter()
+                .map(|range| range.asn)
+                .collect()
+        };
+        // 10.1.0.0/16: the covering /8, itself and the /24 inside it
+        assert_eq!(asns(0x0a010000, 0x0a01ffff), [64500, 64501, 64502]);
+        assert_eq!(asns(0x0b000000, 0x0b0000ff), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn test_partial_overlap_is_reported() {
+        let mut builder = AsnTableBuilder::new();
+        builder
+            .push(
+                "10.0.0.0".parse().unwrap(),
+                "10.0.0.255".parse().unwrap(),
+                1,
+                "a.tsv",
+                1,
+            )
+            .unwrap();
+        builder
+            .push(
+                "10.0.0.128".parse().unwrap(),
+                "10.0.1.255".parse().unwrap(),
+                2,
+                "a.tsv",
+                2,
+            )
+            .unwrap();
+        let err = builder.build().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse a.tsv at line 2: range 10.0.0.128 - 10.0.1.255 partially overlaps 10.0.0.0 - 10.0.0.255 (a.tsv line 1)"
+        );
+    }
+
+    #[test]
+    fn test_describe_range() {
+        assert_eq!(describe_range(0x0a000000, 0x0a0000ff, true), "10.0.0.0/24");
+        assert_eq!(
+            describe_range(0x0a000000, 0x0a0002ff, true),
+            "10.0.0.0 - 10.0.2.255"
+        );
+    }
+}
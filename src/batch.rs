@@ -0,0 +1,559 @@
+use crate::error::{EXIT_NOT_FOUND, Error};
+use crate::ip_utils::{ListEntry, parse_ip_address, split_endpoint};
+use crate::output::{self, OutputFormat, Report};
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read};
+use std::path::PathBuf;
+use std::thread;
+
+/// Where batch inputs are read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputSource {
+    Stdin,
+    File(PathBuf),
+}
+
+impl InputSource {
+    /// Resolve the batch input from `--input` and the command's first positional
+    /// argument, where `-` means stdin. Returns `None` for a single-item run.
+    pub fn from_args(input: Option<&PathBuf>, positional: Option<&str>) -> Option<Self> {
+        match (input, positional) {
+            (Some(path), _) if path.as_os_str() == "-" => Some(InputSource::Stdin),
+            (Some(path), _) => Some(InputSource::File(path.clone())),
+            (None, Some("-")) => Some(InputSource::Stdin),
+            (None, _) => None,
+        }
+    }
+
+    fn name(&self) -> String {
+        match self {
+            InputSource::Stdin => "stdin".to_string(),
+            InputSource::File(path) => path.display().to_string(),
+        }
+    }
+}
+
+/// A single input line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InputLine {
+    /// 1-based line number in the input
+    pub line: usize,
+    pub text: String,
+}
+
+/// Read one item per line, skipping blank lines and `#` comments
+pub fn read_lines(source: &InputSource) -> Result<Vec<InputLine>, Error> {
+    let content = match source {
+        InputSource::Stdin => {
+            let mut content = String::new();
+            io::stdin()
+                .read_to_string(&mut content)
+                .map_err(|e| Error::invalid_input(source.name(), e.to_string()))?;
+            content
+        }
+        InputSource::File(path) => fs::read_to_string(path)
+            .map_err(|e| Error::invalid_input(source.name(), e.to_string()))?,
+    };
+
+    Ok(parse_lines(&content))
+}
+
+/// Read whole input texts as `(source, content)` pairs: each file in turn,
+/// or stdin when there are none (or for "-")
+pub fn read_texts(files: &[PathBuf]) -> Result<Vec<(String, String)>, Error> {
+    let read_stdin = || {
+        let mut content = String::new();
+        io::stdin()
+            .read_to_string(&mut content)
+            .map_err(|e| Error::invalid_input("stdin", e.to_string()))?;
+        Ok(("stdin".to_string(), content))
+    };
+
+    if files.is_empty() {
+        return Ok(vec![read_stdin()?]);
+    }
+    files
+        .iter()
+        .map(|path| {
+            if path.as_os_str() == "-" {
+                return read_stdin();
+            }
+            let source = path.display().to_string();
+            fs::read_to_string(path)
+                .map(|content| (source.clone(), content))
+                .map_err(|e| Error::invalid_input(source, e.to_string()))
+        })
+        .collect()
+}
+
+/// Split text into trimmed lines, skipping blank lines and `#` comments
+pub fn parse_lines(content: &str) -> Vec<InputLine> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, text)| InputLine {
+            line: index + 1,
+            text: text.trim().to_string(),
+        })
+        .filter(|line| !line.text.is_empty() && !line.text.starts_with('#'))
+        .collect()
+}
+
+/// Why a list line is not an address or CIDR. An address with a port or
+/// zone ID (203.0.113.9:51234, [fe80::1%eth0]:443) is named as such rather
+/// than silently losing those parts.
+fn list_line_error(source: &str, line: &InputLine) -> Error {
+    let endpoint = split_endpoint(&line.text).is_ok_and(|parts| {
+        (parts.port.is_some() || parts.zone.is_some()) && parse_ip_address(parts.host).is_ok()
+    });
+    let reason = if endpoint {
+        "ports and zone IDs are not supported in address lists"
+    } else {
+        "invalid IP address or CIDR"
+    };
+    Error::invalid_input(
+        &line.text,
+        format!("{} line {}: {}", source, line.line, reason),
+    )
+}
+
+/// Parse every line of each `(source, content)` text as an address or CIDR
+pub fn parse_list(texts: &[(String, String)]) -> Result<Vec<ListEntry>, Error> {
+    let mut entries = Vec::new();
+    for (source, content) in texts {
+        for line in parse_lines(content) {
+            let entry = ListEntry::parse(&line.text).map_err(|_| list_line_error(source, &line))?;
+            entries.push(entry);
+        }
+    }
+    Ok(entries)
+}
+
+/// Apply `check` to every line in parallel, returning results in input order
+pub fn process<T, F>(lines: &[InputLine], check: F) -> Vec<Result<T, Error>>
+where
+    T: Send,
+    F: Fn(&str) -> Result<T, Error> + Sync,
+{
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let workers = thread::available_parallelism()
+        .map(|n| n.get())
+        .unwrap_or(1)
+        .min(lines.len());
+    let chunk_size = lines.len().div_ceil(workers);
+    let check = &check;
+
+    // Each worker handles one contiguous chunk, so joining the workers in
+    // spawn order yields the results in input order
+    thread::scope(|scope| {
+        let handles: Vec<_> = lines
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|line| check(&line.text))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect()
+    })
+}
+
+/// Counts of the per-line outcomes of a batch run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct BatchSummary {
+    pub total: usize,
+    pub ok: usize,
+    pub negative: usize,
+    pub errors: usize,
+    /// Exit code of the first failing line
+    #[serde(skip)]
+    first_error_code: Option<i32>,
+}
+
+impl BatchSummary {
+    /// Exit code for the whole run: the first error's code, 1 when some
+    /// results were negative, 0 otherwise
+    pub fn exit_code(&self) -> i32 {
+        match self.first_error_code {
+            Some(code) => code,
+            None if self.negative > 0 => EXIT_NOT_FOUND,
+            None => 0,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct BatchError {
+    kind: &'static str,
+    message: String,
+}
+
+#[derive(Serialize)]
+struct BatchRecord<'a, R> {
+    line: usize,
+    input: &'a str,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<&'a R>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<BatchError>,
+}
+
+#[derive(Serialize)]
+struct BatchDocument<'a, R> {
+    results: Vec<BatchRecord<'a, R>>,
+    summary: &'a BatchSummary,
+}
+
+#[derive(Serialize)]
+struct SummaryLine<'a> {
+    summary: &'a BatchSummary,
+}
+
+fn summarize<R: Report>(results: &[Result<R, Error>]) -> BatchSummary {
+    let mut summary = BatchSummary {
+        total: results.len(),
+        ..BatchSummary::default()
+    };
+    for result in results {
+        match result {
+            Ok(report) if report.is_negative() => summary.negative += 1,
+            Ok(_) => summary.ok += 1,
+            Err(e) => {
+                summary.errors += 1;
+                summary.first_error_code.get_or_insert(e.exit_code());
+            }
+        }
+    }
+    summary
+}
+
+fn records<'a, R: Report>(
+    lines: &'a [InputLine],
+    results: &'a [Result<R, Error>],
+) -> Vec<BatchRecord<'a, R>> {
+    lines
+        .iter()
+        .zip(results)
+        .map(|(line, result)| match result {
+            Ok(report) => BatchRecord {
+                line: line.line,
+                input: &line.text,
+                status: if report.is_negative() {
+                    "negative"
+                } else {
+                    "ok"
+                },
+                result: Some(report),
+                error: None,
+            },
+            Err(e) => BatchRecord {
+                line: line.line,
+                input: &line.text,
+                status: "error",
+                result: None,
+                error: Some(BatchError {
+                    kind: e.kind(),
+                    message: e.to_string(),
+                }),
+            },
+        })
+        .collect()
+}
+
+/// Render the per-line results and summary of a batch run
+pub fn render_batch<R: Report>(
+    lines: &[InputLine],
+    results: &[Result<R, Error>],
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<String, Error> {
+    let summary = summarize(results);
+    let internal = |e: &dyn std::fmt::Display| Error::Internal(e.to_string());
+
+    match format {
+        OutputFormat::Text => {
+            let mut text = String::new();
+            for (line, result) in lines.iter().zip(results) {
+                match result {
+                    Ok(report) => text.push_str(&output::render_text(report, verbose)),
+                    Err(e) => text.push_str(&format!("✗ Line {}: {}\n", line.line, e)),
+                }
+            }
+            text.push_str(&format!(
+                "Summary: {} processed, {} ok, {} negative, {} errors\n",
+                summary.total, summary.ok, summary.negative, summary.errors
+            ));
+            Ok(text)
+        }
+        OutputFormat::Json | OutputFormat::Yaml => {
+            let document = BatchDocument {
+                results: records(lines, results),
+                summary: &summary,
+            };
+            if format == OutputFormat::Json {
+                serde_json::to_string_pretty(&document)
+                    .map(|json| json + "\n")
+                    .map_err(|e| internal(&e))
+            } else {
+                serde_yaml::to_string(&document).map_err(|e| internal(&e))
+            }
+        }
+        OutputFormat::Ndjson => {
+            let mut text = String::new();
+            for record in records(lines, results) {
+                text.push_str(&serde_json::to_string(&record).map_err(|e| internal(&e))?);
+                text.push('\n');
+            }
+            let summary_line = SummaryLine { summary: &summary };
+            text.push_str(&serde_json::to_string(&summary_line).map_err(|e| internal(&e))?);
+            text.push('\n');
+            Ok(text)
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            let mut header = vec!["line", "input", "status", "error"];
+            header.extend_from_slice(R::csv_header());
+            writer.write_record(&header).map_err(|e| internal(&e))?;
+
+            let empty_row = || vec![String::new(); R::csv_header().len()];
+            for (line, result) in lines.iter().zip(results) {
+                // One row per report row; a report without rows still gets one
+                let (status, error, rows) = match result {
+                    Ok(report) => {
+                        let status = if report.is_negative() {
+                            "negative"
+                        } else {
+                            "ok"
+                        };
+                        let mut rows = report.csv_records();
+                        if rows.is_empty() {
+                            rows.push(empty_row());
+                        }
+                        (status, String::new(), rows)
+                    }
+                    Err(e) => ("error", e.to_string(), vec![empty_row()]),
+                };
+                for row in rows {
+                    let mut record = vec![
+                        line.line.to_string(),
+                        line.text.clone(),
+                        status.to_string(),
+                        error.clone(),
+                    ];
+                    record.extend(row);
+                    writer.write_record(&record).map_err(|e| internal(&e))?;
+                }
+            }
+
+            let bytes = writer.into_inner().map_err(|e| internal(&e))?;
+            String::from_utf8(bytes).map_err(|e| internal(&e))
+        }
+    }
+}
+
+/// Read the batch input, check every line and print the results.
+///
+/// Failing lines are reported in place and do not abort the run.
+pub fn run_batch<R, F>(
+    source: &InputSource,
+    format: OutputFormat,
+    verbose: bool,
+    check: F,
+) -> Result<BatchSummary, Error>
+where
+    R: Report + Send,
+    F: Fn(&str) -> Result<R, Error> + Sync,
+{
+    let lines = read_lines(source)?;
+    let results = process(&lines, check);
+    print!("{}", render_batch(&lines, &results, format, verbose)?);
+    Ok(summarize(&results))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::csv_optional;
+
+    #[derive(Debug, Serialize)]
+    struct Parity {
+        value: u32,
+        even: bool,
+    }
+
+    impl Report for Parity {
+        fn render_text(&self, _verbose: bool) -> String {
+            format!("{} even={}\n", self.value, self.even)
+        }
+
+        fn csv_header() -> &'static [&'static str] {
+            &["value", "even"]
+        }
+
+        fn csv_record(&self) -> Vec<String> {
+            vec![self.value.to_string(), csv_optional(&Some(self.even))]
+        }
+
+        fn is_negative(&self) -> bool {
+            !self.even
+        }
+    }
+
+    fn check(text: &str) -> Result<Parity, Error> {
+        let value: u32 = text
+            .parse()
+            .map_err(|_| Error::invalid_input(text, "Not a number"))?;
+        Ok(Parity {
+            value,
+            even: value.is_multiple_of(2),
+        })
+    }
+
+    #[test]
+    fn test_from_args() {
+        assert_eq!(InputSource::from_args(None, Some("8.8.8.8")), None);
+        assert_eq!(
+            InputSource::from_args(None, Some("-")),
+            Some(InputSource::Stdin)
+        );
+        let path = PathBuf::from("ips.txt");
+        assert_eq!(
+            InputSource::from_args(Some(&path), None),
+            Some(InputSource::File(path.clone()))
+        );
+    }
+
+    #[test]
+    fn test_parse_lines_skips_blank_and_comments() {
+        let lines = parse_lines("1\n\n# comment\n  2  \n");
+        assert_eq!(
+            lines,
+            vec![
+                InputLine {
+                    line: 1,
+                    text: "1".to_string()
+                },
+                InputLine {
+                    line: 4,
+                    text: "2".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_preserves_order() {
+        let content: String = (0..1000).map(|n| format!("{}\n", n)).collect();
+        let lines = parse_lines(&content);
+        let results = process(&lines, check);
+        let values: Vec<u32> = results.into_iter().map(|r| r.unwrap().value).collect();
+        assert_eq!(values, (0..1000).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_errors_do_not_abort() {
+        let lines = parse_lines("2\nx\n3\n");
+        let results = process(&lines, check);
+        let summary = summarize(&results);
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.ok, 1);
+        assert_eq!(summary.negative, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(summary.exit_code(), crate::error::EXIT_BAD_INPUT);
+
+        let text = render_batch(&lines, &results, OutputFormat::Text, false).unwrap();
+        assert_eq!(
+            text,
+            "2 even=true\n\
+             ✗ Line 2: Not a number: x\n\
+             3 even=false\n\
+             Summary: 3 processed, 1 ok, 1 negative, 1 errors\n"
+        );
+    }
+
+    #[test]
+    fn test_render_batch_machine_formats() {
+        let lines = parse_lines("2\nx\n");
+        let results = process(&lines, check);
+
+        let ndjson = render_batch(&lines, &results, OutputFormat::Ndjson, false).unwrap();
+        let rows: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(
+            rows[0],
+            r#"{"line":1,"input":"2","status":"ok","result":{"value":2,"even":true}}"#
+        );
+        assert!(rows[1].contains(r#""kind":"invalid_input""#));
+        assert!(rows[2].starts_with(r#"{"summary":{"total":2"#));
+
+        let csv = render_batch(&lines, &results, OutputFormat::Csv, false).unwrap();
+        assert_eq!(
+            csv,
+            "line,input,status,error,value,even\n1,2,ok,,2,true\n2,x,error,Not a number: x,,\n"
+        );
+    }
+
+    /// A report with one CSV row per digit of the input
+    #[derive(Debug, Serialize)]
+    struct Digits {
+        digits: Vec<char>,
+    }
+
+    impl Report for Digits {
+        fn render_text(&self, _verbose: bool) -> String {
+            format!("{:?}\n", self.digits)
+        }
+
+        fn csv_header() -> &'static [&'static str] {
+            &["digit", "odd"]
+        }
+
+        fn csv_record(&self) -> Vec<String> {
+            self.csv_records().into_iter().next().unwrap_or_default()
+        }
+
+        fn csv_records(&self) -> Vec<Vec<String>> {
+            self.digits
+                .iter()
+                .map(|digit| {
+                    let odd = digit.to_digit(10).unwrap() % 2 == 1;
+                    vec![digit.to_string(), odd.to_string()]
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_render_batch_csv_rows() {
+        let lines = parse_lines(
+            "12
+ab
+",
+        );
+        let results = process(&lines, |text| {
+            Ok::<_, Error>(Digits {
+                digits: text.chars().filter(char::is_ascii_digit).collect(),
+            })
+        });
+        let csv = render_batch(&lines, &results, OutputFormat::Csv, false).unwrap();
+        assert_eq!(
+            csv,
+            "line,input,status,error,digit,odd\n1,12,ok,,1,true\n1,12,ok,,2,false\n2,ab,ok,,,\n"
+        );
+    }
+}
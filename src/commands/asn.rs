@@ -0,0 +1,376 @@
+use crate::asn::{AsnMmdb, AsnProvider, IpToAsnFile, MrtFile, parse_asn};
+use crate::config::{CONFIG_FILE, load_config};
+use crate::error::Error;
+use crate::export::{ExportEntry, ExportFormat, render_networks};
+use crate::ip_utils::{IpFamily, IpInfo, format_ip_details, get_endpoint_info, parse_endpoint};
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use serde::Serialize;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// Result of an origin AS lookup
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AsnReport {
+    pub ip: IpAddr,
+    pub info: IpInfo,
+    /// Origin AS; `None` when the address is not routed in the data
+    pub asn: Option<u32>,
+    pub as_name: Option<String>,
+    /// Country the AS is registered in (iptoasn data only)
+    pub as_country: Option<String>,
+    /// Matched network or range
+    pub network: Option<String>,
+    /// File the data was loaded from
+    pub source: String,
+}
+
+/// Format an AS as "AS13335 CLOUDFLARENET (US)"
+fn describe_as(asn: u32, name: &Option<String>, country: &Option<String>) -> String {
+    let mut text = format!("AS{}", asn);
+    if let Some(name) = name {
+        text.push_str(&format!(" {}", name));
+    }
+    if let Some(country) = country {
+        text.push_str(&format!(" ({})", country));
+    }
+    text
+}
+
+impl Report for AsnReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Checking origin AS for {}...\n", self.info.endpoint());
+
+        if verbose {
+            text.push_str(&format_ip_details(&self.info));
+        }
+
+        match self.asn {
+            Some(asn) => {
+                text.push_str(&format!(
+                    "✓ {}\n",
+                    describe_as(asn, &self.as_name, &self.as_country)
+                ));
+                if verbose && let Some(network) = &self.network {
+                    text.push_str(&format!("  Network: {}\n", network));
+                }
+            }
+            None => text.push_str(&format!("✓ No AS found for {}\n", self.ip)),
+        }
+
+        if verbose {
+            text.push_str(&format!("  Source: {}\n", self.source));
+        }
+
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["ip", "category", "asn", "as_name", "as_country", "network"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.ip.to_string(),
+            self.info.category.as_str().to_string(),
+            csv_optional(&self.asn),
+            csv_optional(&self.as_name),
+            csv_optional(&self.as_country),
+            csv_optional(&self.network),
+        ]
+    }
+
+    fn is_negative(&self) -> bool {
+        self.asn.is_none()
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.info.warnings.clone()
+    }
+}
+
+/// ASN data files given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct AsnSources {
+    /// MaxMind-format ASN database
+    pub db: Option<PathBuf>,
+    /// iptoasn.com TSV file
+    pub tsv: Option<PathBuf>,
+    /// MRT RIB dumps
+    pub mrt: Vec<PathBuf>,
+}
+
+impl AsnSources {
+    fn is_empty(&self) -> bool {
+        self.db.is_none() && self.tsv.is_none() && self.mrt.is_empty()
+    }
+}
+
+/// Open the ASN data given with `--db` (MMDB), `--tsv` (iptoasn) or `--mrt`
+/// (RIB dumps), or else the file(s) set as `asn.mmdb`, `asn.tsv` or
+/// `asn.mrt` in the configuration file, in that order of preference
+pub fn open_asn_source(sources: &AsnSources) -> Result<Box<dyn AsnProvider>, Error> {
+    let sources = if sources.is_empty() {
+        let config = load_config()?.asn;
+        AsnSources {
+            db: config.mmdb,
+            tsv: config.tsv,
+            mrt: config.mrt,
+        }
+    } else {
+        sources.clone()
+    };
+
+    if let Some(path) = &sources.db {
+        Ok(Box::new(AsnMmdb::open(path)?))
+    } else if let Some(path) = &sources.tsv {
+        Ok(Box::new(IpToAsnFile::open(path)?))
+    } else if !sources.mrt.is_empty() {
+        Ok(Box::new(MrtFile::open(&sources.mrt)?))
+    } else {
+        Err(Error::config(
+            CONFIG_FILE,
+            "no ASN data configured (pass --db, --tsv or --mrt, or set asn.mmdb, asn.tsv or asn.mrt)",
+        ))
+    }
+}
+
+/// Look up the origin AS of an IP address and return the outcome
+pub fn asn_report(ip_address: &str, source: &dyn AsnProvider) -> Result<AsnReport, Error> {
+    let endpoint = parse_endpoint(ip_address)?;
+    let ip = endpoint.ip;
+    let record = source.lookup(ip)?;
+
+    Ok(AsnReport {
+        ip,
+        info: get_endpoint_info(&endpoint),
+        asn: record.as_ref().map(|record| record.asn),
+        as_name: record.as_ref().and_then(|record| record.name.clone()),
+        as_country: record.as_ref().and_then(|record| record.country.clone()),
+        network: record.map(|record| record.network),
+        source: source.source(),
+    })
+}
+
+pub fn check_asn(
+    ip_address: &str,
+    source: &dyn AsnProvider,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = asn_report(ip_address, source)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!("No AS found for {}", report.ip)));
+    }
+
+    Ok(())
+}
+
+/// Address space originated by one AS (`asn prefixes`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AsnPrefixesReport {
+    pub asn: u32,
+    pub as_name: Option<String>,
+    pub as_country: Option<String>,
+    pub family: IpFamily,
+    /// Text rendering of the networks
+    #[serde(skip)]
+    pub format: ExportFormat,
+    /// nginx variable or nftables/ipset set name
+    #[serde(skip)]
+    pub name: String,
+    /// Aggregated CIDR blocks in address order, IPv4 first
+    pub networks: Vec<ExportEntry>,
+}
+
+impl Report for AsnPrefixesReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = String::new();
+        if verbose && self.format == ExportFormat::Plain {
+            text.push_str(&format!(
+                "# {}: {} networks\n",
+                describe_as(self.asn, &self.as_name, &self.as_country),
+                self.networks.len()
+            ));
+        }
+        text.push_str(&render_networks(&self.networks, self.format, &self.name));
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["network", "asn"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.networks
+            .iter()
+            .map(|entry| vec![entry.network.clone(), self.asn.to_string()])
+            .collect()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.networks.is_empty()
+    }
+}
+
+/// List the aggregated CIDR blocks originated by an AS ("13335" or "AS13335")
+pub fn asn_prefixes_report(
+    asn: &str,
+    family: IpFamily,
+    format: ExportFormat,
+    name: &str,
+    source: &dyn AsnProvider,
+) -> Result<AsnPrefixesReport, Error> {
+    let number = parse_asn(asn).ok_or_else(|| Error::invalid_input(asn, "Invalid AS number"))?;
+    let announced = source.announced(number)?;
+    let label = format!("AS{}", number);
+
+    let mut networks = Vec::new();
+    for (ipv4, ranges) in [(true, &announced.ipv4), (false, &announced.ipv6)] {
+        if !family.includes(ipv4) {
+            continue;
+        }
+        for &(start, end) in ranges {
+            networks.extend(ExportEntry::from_range(start, end, ipv4, Some(&label)));
+        }
+    }
+
+    let info = announced.info.unwrap_or_default();
+    Ok(AsnPrefixesReport {
+        asn: number,
+        as_name: info.name,
+        as_country: info.country,
+        family,
+        format,
+        name: name.to_string(),
+        networks,
+    })
+}
+
+pub fn check_asn_prefixes(
+    asn: &str,
+    family: IpFamily,
+    export_format: ExportFormat,
+    name: &str,
+    source: &dyn AsnProvider,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = asn_prefixes_report(asn, family, export_format, name, source)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "No networks found for AS{}",
+            report.asn
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn fixture() -> Box<dyn AsnProvider> {
+        open_asn_source(&AsnSources {
+            db: Some(PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/GeoLite2-ASN-Test.mmdb"
+            ))),
+            ..AsnSources::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_asn_report() {
+        let source = fixture();
+        let report = asn_report("1.1.1.1", source.as_ref()).unwrap();
+        assert_eq!(report.asn, Some(13335));
+        assert_eq!(
+            report.render_text(false),
+            "Checking origin AS for 1.1.1.1...\n✓ AS13335 CLOUDFLARENET\n"
+        );
+        assert!(report.render_text(true).contains("  Network: 1.1.1.0/24\n"));
+
+        let report = asn_report("192.0.2.1", source.as_ref()).unwrap();
+        assert!(report.is_negative());
+        assert_eq!(report.network, None);
+    }
+
+    #[test]
+    fn test_asn_prefixes_report() {
+        let source = fixture();
+        let report = asn_prefixes_report(
+            "AS2519",
+            IpFamily::Both,
+            ExportFormat::Plain,
+            "asn",
+            source.as_ref(),
+        )
+        .unwrap();
+        assert_eq!(report.render_text(false), "1.0.16.0/23\n");
+        assert_eq!(
+            report.render_text(true),
+            "# AS2519 ARTERIA Networks Corporation: 1 networks\n1.0.16.0/23\n"
+        );
+
+        let report = asn_prefixes_report(
+            "2914",
+            IpFamily::V4,
+            ExportFormat::Plain,
+            "asn",
+            source.as_ref(),
+        )
+        .unwrap();
+        assert!(report.is_negative());
+
+        let err = asn_prefixes_report(
+            "ASX",
+            IpFamily::Both,
+            ExportFormat::Plain,
+            "asn",
+            source.as_ref(),
+        )
+        .unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+    }
+
+    #[test]
+    fn test_tsv_source() {
+        let path = std::env::temp_dir().join("ipchecker-test-ip2asn.tsv");
+        fs::write(&path, "1.0.0.0\t1.0.0.255\t13335\tUS\tCLOUDFLARENET\n").unwrap();
+        let source = open_asn_source(&AsnSources {
+            tsv: Some(path.clone()),
+            ..AsnSources::default()
+        })
+        .unwrap();
+        let report = asn_report("1.0.0.9", source.as_ref()).unwrap();
+        assert_eq!(report.as_country.as_deref(), Some("US"));
+        assert_eq!(report.network.as_deref(), Some("1.0.0.0/24"));
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_mrt_source() {
+        let source = open_asn_source(&AsnSources {
+            mrt: vec![PathBuf::from(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/testdata/rib-test.mrt"
+            ))],
+            ..AsnSources::default()
+        })
+        .unwrap();
+        let report = asn_report("1.0.4.9", source.as_ref()).unwrap();
+        assert_eq!(report.asn, Some(4608));
+        assert_eq!(report.network.as_deref(), Some("1.0.4.0/24"));
+    }
+}
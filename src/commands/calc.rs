@@ -0,0 +1,401 @@
+use crate::error::Error;
+use crate::ip_utils::{
+    Endpoint, address_distance, common_prefix_len, host_range, network_range, next_address,
+    nth_host, offset_address, parse_cidr, parse_endpoint, previous_address, subnet_index,
+    value_address,
+};
+use crate::output::{self, OutputFormat, Report};
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// An address calculation, with operands as given on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Calculation {
+    Add { address: String, offset: String },
+    Subtract { address: String, offset: String },
+    Next { address: String },
+    Previous { address: String },
+    Host { network: String, n: String },
+    Distance { from: String, to: String },
+    CommonPrefix { a: String, b: String },
+    SubnetIndex { subnet: String, parent: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CalcOperation {
+    Add,
+    Subtract,
+    Next,
+    Previous,
+    Host,
+    Distance,
+    CommonPrefix,
+    SubnetIndex,
+}
+
+impl CalcOperation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CalcOperation::Add => "add",
+            CalcOperation::Subtract => "subtract",
+            CalcOperation::Next => "next",
+            CalcOperation::Previous => "previous",
+            CalcOperation::Host => "host",
+            CalcOperation::Distance => "distance",
+            CalcOperation::CommonPrefix => "common_prefix",
+            CalcOperation::SubnetIndex => "subnet_index",
+        }
+    }
+}
+
+/// Result of an address calculation (`calc`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CalcReport {
+    pub operation: CalcOperation,
+    /// The calculation in words, e.g. "192.0.2.1 + 5" or "host 3 of 192.0.2.0/24"
+    pub expression: String,
+    /// An address, or a number as a string so that 128-bit values survive
+    /// JSON parsers
+    pub result: String,
+}
+
+impl Report for CalcReport {
+    fn render_text(&self, _verbose: bool) -> String {
+        format!("Calculating {}...\n✓ {}\n", self.expression, self.result)
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["operation", "expression", "result"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.operation.as_str().to_string(),
+            self.expression.clone(),
+            self.result.clone(),
+        ]
+    }
+}
+
+fn family_name(ip: IpAddr) -> &'static str {
+    if ip.is_ipv4() { "IPv4" } else { "IPv6" }
+}
+
+/// Parse a signed decimal count such as "5", "+5" or "-5"
+fn parse_count(text: &str) -> Result<i128, Error> {
+    text.trim()
+        .parse()
+        .map_err(|_| Error::invalid_input(text, "Invalid number"))
+}
+
+/// The endpoint moved to `ip`, keeping its zone ID and port
+fn with_ip(endpoint: &Endpoint, ip: IpAddr) -> Endpoint {
+    Endpoint {
+        ip,
+        ..endpoint.clone()
+    }
+}
+
+/// Parse both operands, which must be of the same family
+fn parse_pair(a: &str, b: &str) -> Result<(Endpoint, Endpoint), Error> {
+    let (a_endpoint, b_endpoint) = (parse_endpoint(a)?, parse_endpoint(b)?);
+    if a_endpoint.ip.is_ipv4() != b_endpoint.ip.is_ipv4() {
+        return Err(Error::invalid_input(
+            format!("{} and {}", a, b),
+            "Addresses are of different families",
+        ));
+    }
+    Ok((a_endpoint, b_endpoint))
+}
+
+fn offset_report(
+    operation: CalcOperation,
+    address: &str,
+    offset: &str,
+) -> Result<CalcReport, Error> {
+    let endpoint = parse_endpoint(address)?;
+    let ip = endpoint.ip;
+    let count = parse_count(offset)?;
+    let delta = match operation {
+        CalcOperation::Subtract => count.checked_neg(),
+        _ => Some(count),
+    };
+    let expression = match delta {
+        Some(delta) if delta < 0 => format!("{} - {}", endpoint, delta.unsigned_abs()),
+        Some(delta) => format!("{} + {}", endpoint, delta),
+        None => format!("{} - {}", endpoint, count),
+    };
+    let result = delta
+        .and_then(|delta| offset_address(ip, delta))
+        .ok_or_else(|| {
+            Error::invalid_input(
+                &expression,
+                format!("Result is outside the {} address space", family_name(ip)),
+            )
+        })?;
+
+    Ok(CalcReport {
+        operation,
+        expression,
+        result: with_ip(&endpoint, result).to_string(),
+    })
+}
+
+/// Evaluate one calculation. Results past the first or last address of a
+/// family and hosts outside a network are input errors.
+pub fn calc_report(calculation: &Calculation) -> Result<CalcReport, Error> {
+    match calculation {
+        Calculation::Add { address, offset } => offset_report(CalcOperation::Add, address, offset),
+        Calculation::Subtract { address, offset } => {
+            offset_report(CalcOperation::Subtract, address, offset)
+        }
+        Calculation::Next { address } | Calculation::Previous { address } => {
+            let endpoint = parse_endpoint(address)?;
+            let ip = endpoint.ip;
+            let (operation, expression, result) = match calculation {
+                Calculation::Next { .. } => (
+                    CalcOperation::Next,
+                    format!("the address after {}", endpoint),
+                    next_address(ip),
+                ),
+                _ => (
+                    CalcOperation::Previous,
+                    format!("the address before {}", endpoint),
+                    previous_address(ip),
+                ),
+            };
+            let result = result.ok_or_else(|| {
+                Error::invalid_input(
+                    &expression,
+                    format!("Result is outside the {} address space", family_name(ip)),
+                )
+            })?;
+            Ok(CalcReport {
+                operation,
+                expression,
+                result: with_ip(&endpoint, result).to_string(),
+            })
+        }
+        Calculation::Host { network, n } => {
+            let (ip, prefix_len) = parse_cidr(network)?;
+            let count = parse_count(n)?;
+            let (first, last) = host_range(ip, prefix_len);
+            let hosts = last - first + 1;
+            let network = format!(
+                "{}/{}",
+                value_address(network_range(ip, prefix_len).0, ip.is_ipv4()),
+                prefix_len
+            );
+            let expression = format!("host {} of {}", count, network);
+            let result = nth_host(ip, prefix_len, count).ok_or_else(|| {
+                Error::invalid_input(
+                    &expression,
+                    format!(
+                        "Host number must be between 1 and {} (or -1 and -{} from the end)",
+                        hosts, hosts
+                    ),
+                )
+            })?;
+            Ok(CalcReport {
+                operation: CalcOperation::Host,
+                expression,
+                result: result.to_string(),
+            })
+        }
+        Calculation::Distance { from, to } => {
+            let (from, to) = parse_pair(from, to)?;
+            let distance = address_distance(from.ip, to.ip).unwrap_or_default();
+            Ok(CalcReport {
+                operation: CalcOperation::Distance,
+                expression: format!("the distance from {} to {}", from, to),
+                result: distance.to_string(),
+            })
+        }
+        Calculation::CommonPrefix { a, b } => {
+            let (a, b) = parse_pair(a, b)?;
+            let prefix_len = common_prefix_len(a.ip, b.ip).unwrap_or_default();
+            Ok(CalcReport {
+                operation: CalcOperation::CommonPrefix,
+                expression: format!("the common prefix length of {} and {}", a, b),
+                result: prefix_len.to_string(),
+            })
+        }
+        Calculation::SubnetIndex { subnet, parent } => {
+            let (ip, prefix_len) = parse_cidr(subnet)?;
+            let (parent_ip, parent_len) = parse_cidr(parent)?;
+            let expression = format!("the index of {} in {}", subnet, parent);
+            let index = subnet_index(ip, prefix_len, parent_ip, parent_len)
+                .ok_or_else(|| Error::invalid_input(&expression, "Subnet is not inside parent"))?;
+            Ok(CalcReport {
+                operation: CalcOperation::SubnetIndex,
+                expression,
+                result: index.to_string(),
+            })
+        }
+    }
+}
+
+pub fn check_calc(
+    calculation: &Calculation,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = calc_report(calculation)?;
+    output::print_report(&report, format, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(calculation: Calculation) -> String {
+        calc_report(&calculation).unwrap().result
+    }
+
+    fn error(calculation: Calculation) -> String {
+        calc_report(&calculation).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_calc_report() {
+        let report = calc_report(&Calculation::Add {
+            address: "192.0.2.250".to_string(),
+            offset: "10".to_string(),
+        })
+        .unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "Calculating 192.0.2.250 + 10...\n✓ 192.0.3.4\n"
+        );
+        assert_eq!(
+            report.csv_record(),
+            ["add", "192.0.2.250 + 10", "192.0.3.4"]
+        );
+
+        assert_eq!(
+            result(Calculation::Subtract {
+                address: "2001:db8::".to_string(),
+                offset: "1".to_string(),
+            }),
+            "2001:db7:ffff:ffff:ffff:ffff:ffff:ffff"
+        );
+        assert_eq!(
+            result(Calculation::Previous {
+                address: "10.0.1.0".to_string(),
+            }),
+            "10.0.0.255"
+        );
+        assert_eq!(
+            result(Calculation::Host {
+                network: "192.0.2.0/24".to_string(),
+                n: "-1".to_string(),
+            }),
+            "192.0.2.254"
+        );
+        assert_eq!(
+            result(Calculation::Distance {
+                from: "::".to_string(),
+                to: "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff".to_string(),
+            }),
+            u128::MAX.to_string()
+        );
+        assert_eq!(
+            result(Calculation::CommonPrefix {
+                a: "192.0.2.1".to_string(),
+                b: "192.0.2.200".to_string(),
+            }),
+            "24"
+        );
+        assert_eq!(
+            result(Calculation::SubnetIndex {
+                subnet: "192.0.2.64/26".to_string(),
+                parent: "192.0.2.0/24".to_string(),
+            }),
+            "1"
+        );
+    }
+
+    #[test]
+    fn test_calc_report_endpoints() {
+        let report = calc_report(&Calculation::Next {
+            address: "203.0.113.9:51234".to_string(),
+        })
+        .unwrap();
+        assert_eq!(report.expression, "the address after 203.0.113.9:51234");
+        assert_eq!(report.result, "203.0.113.10:51234");
+
+        assert_eq!(
+            result(Calculation::Add {
+                address: "[fe80::1%eth0]:8443".to_string(),
+                offset: "-1".to_string(),
+            }),
+            "[fe80::%eth0]:8443"
+        );
+        assert_eq!(
+            result(Calculation::Previous {
+                address: "fe80::1%eth0".to_string(),
+            }),
+            "fe80::%eth0"
+        );
+        assert_eq!(
+            result(Calculation::Distance {
+                from: "192.0.2.1:80".to_string(),
+                to: "192.0.2.11".to_string(),
+            }),
+            "10"
+        );
+        assert_eq!(
+            result(Calculation::CommonPrefix {
+                a: "fe80::1%eth0".to_string(),
+                b: "[fe80::2]:22".to_string(),
+            }),
+            "126"
+        );
+    }
+
+    #[test]
+    fn test_calc_report_errors() {
+        assert_eq!(
+            error(Calculation::Next {
+                address: "255.255.255.255".to_string(),
+            }),
+            "Result is outside the IPv4 address space: the address after 255.255.255.255"
+        );
+        assert_eq!(
+            error(Calculation::Subtract {
+                address: "0.0.0.1".to_string(),
+                offset: "2".to_string(),
+            }),
+            "Result is outside the IPv4 address space: 0.0.0.1 - 2"
+        );
+        assert_eq!(
+            error(Calculation::Host {
+                network: "192.0.2.77/30".to_string(),
+                n: "3".to_string(),
+            }),
+            "Host number must be between 1 and 2 (or -1 and -2 from the end): host 3 of 192.0.2.76/30"
+        );
+        assert_eq!(
+            error(Calculation::Distance {
+                from: "10.0.0.1".to_string(),
+                to: "::1".to_string(),
+            }),
+            "Addresses are of different families: 10.0.0.1 and ::1"
+        );
+        assert_eq!(
+            error(Calculation::SubnetIndex {
+                subnet: "192.0.3.0/26".to_string(),
+                parent: "192.0.2.0/24".to_string(),
+            }),
+            "Subnet is not inside parent: the index of 192.0.3.0/26 in 192.0.2.0/24"
+        );
+        assert_eq!(
+            error(Calculation::Add {
+                address: "192.0.2.1".to_string(),
+                offset: "five".to_string(),
+            }),
+            "Invalid number: five"
+        );
+    }
+}
@@ -1,25 +1,788 @@
-use crate::ip_utils::{parse_ip_address, print_ip_details};
+use crate::config::{CONFIG_FILE, load_config};
+use crate::error::Error;
+use crate::export::{ExportEntry, ExportFormat, render_networks};
+use crate::geo::{
+    CsvTable, GeoChain, GeoProvider, GeoRecord, GeofeedIssue, GeofeedTable, IssueSeverity,
+    MmdbDatabase, ProviderKind, RangeTableBuilder, Registry, RirTable, load_delegated,
+    parse_country_code, parse_geofeed,
+};
+use crate::ip_utils::{IpFamily, IpInfo, format_ip_details, get_endpoint_info, parse_endpoint};
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use serde::Serialize;
+use std::fs;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+
+/// Result of a country code lookup
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CountryCodeReport {
+    pub ip: IpAddr,
+    pub info: IpInfo,
+    /// Geolocation fields; all `None` when no provider has an entry
+    #[serde(flatten)]
+    pub geo: GeoRecord,
+    /// Provider that answered, `None` when none did
+    pub provider: Option<ProviderKind>,
+    /// File(s) the answering provider was loaded from
+    pub provider_source: Option<String>,
+}
+
+/// Format a code and its name as "JP (Japan)"
+fn code_and_name(code: &Option<String>, name: &Option<String>) -> Option<String> {
+    match (code, name) {
+        (Some(code), Some(name)) => Some(format!("{} ({})", code, name)),
+        (Some(code), None) => Some(code.clone()),
+        (None, Some(name)) => Some(name.clone()),
+        (None, None) => None,
+    }
+}
+
+impl Report for CountryCodeReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Checking country code for {}...\n", self.info.endpoint());
+
+        if verbose {
+            text.push_str(&format_ip_details(&self.info));
+            text.push_str("Verbose mode enabled for country code check\n");
+        }
+
+        match code_and_name(&self.geo.country_code, &self.geo.country_name) {
+            Some(country) => text.push_str(&format!("✓ {}\n", country)),
+            None => text.push_str(&format!("✓ No country found for {}\n", self.ip)),
+        }
+
+        if verbose {
+            let geo = &self.geo;
+            if let Some(continent) = code_and_name(&geo.continent_code, &geo.continent_name) {
+                text.push_str(&format!("  Continent: {}\n", continent));
+            }
+            if let Some(registered) =
+                code_and_name(&geo.registered_country_code, &geo.registered_country_name)
+            {
+                text.push_str(&format!("  Registered country: {}\n", registered));
+            }
+            if let Some(subdivision) = &geo.subdivision {
+                text.push_str(&format!("  Subdivision: {}\n", subdivision));
+            }
+            if let Some(city) = &geo.city {
+                text.push_str(&format!("  City: {}\n", city));
+            }
+            if let (Some(latitude), Some(longitude)) = (geo.latitude, geo.longitude) {
+                text.push_str(&format!("  Coordinates: {}, {}\n", latitude, longitude));
+            }
+            if let Some(registry) = &geo.registry {
+                text.push_str(&format!("  Registry: {}\n", registry));
+            }
+            if let (Some(provider), Some(source)) = (self.provider, &self.provider_source) {
+                text.push_str(&format!("  Provider: {} ({})\n", provider, source));
+            }
+        }
+
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "ip",
+            "category",
+            "country_code",
+            "country_name",
+            "continent_code",
+            "registered_country_code",
+            "subdivision",
+            "city",
+            "latitude",
+            "longitude",
+            "registry",
+            "provider",
+        ]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.ip.to_string(),
+            self.info.category.as_str().to_string(),
+            csv_optional(&self.geo.country_code),
+            csv_optional(&self.geo.country_name),
+            csv_optional(&self.geo.continent_code),
+            csv_optional(&self.geo.registered_country_code),
+            csv_optional(&self.geo.subdivision),
+            csv_optional(&self.geo.city),
+            csv_optional(&self.geo.latitude),
+            csv_optional(&self.geo.longitude),
+            csv_optional(&self.geo.registry),
+            csv_optional(&self.provider),
+        ]
+    }
+
+    fn is_negative(&self) -> bool {
+        self.geo.country_code.is_none()
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.info.warnings.clone()
+    }
+}
+
+/// Geolocation sources given on the command line
+#[derive(Debug, Clone, Default)]
+pub struct GeoSources {
+    /// MaxMind-format database (`--db`)
+    pub db: Option<PathBuf>,
+    /// Range table built with `cc build-db` (`--table`)
+    pub table: Option<PathBuf>,
+    /// CSV exports (`--csv`)
+    pub csv: Vec<PathBuf>,
+    /// RFC 8805 geofeeds (`--geofeed`)
+    pub geofeed: Vec<PathBuf>,
+    /// Chain order (`--providers`); empty for the configured or default order
+    pub providers: Vec<ProviderKind>,
+}
+
+impl GeoSources {
+    fn has_files(&self) -> bool {
+        self.db.is_some()
+            || self.table.is_some()
+            || !self.csv.is_empty()
+            || !self.geofeed.is_empty()
+    }
+}
+
+/// Build the provider chain. Files given on the command line replace those
+/// in the configuration file (`geo.mmdb`, `geo.table`, `geo.csv`,
+/// `geo.geofeed`). Providers are asked in the `--providers` order, else the
+/// `geo.providers` order, else geofeed, mmdb, rir, csv; a kind without a
+/// file is skipped unless it was listed explicitly.
+pub fn open_geo_chain(sources: &GeoSources) -> Result<GeoChain, Error> {
+    let (files, configured_order) = if sources.has_files() {
+        (sources.clone(), Vec::new())
+    } else {
+        let geo = load_config()?.geo;
+        let files = GeoSources {
+            db: geo.mmdb,
+            table: geo.table,
+            csv: geo.csv,
+            geofeed: geo.geofeed,
+            providers: Vec::new(),
+        };
+        (files, geo.providers)
+    };
+
+    let (order, explicit) = if !sources.providers.is_empty() {
+        (sources.providers.clone(), true)
+    } else if !configured_order.is_empty() {
+        (configured_order, true)
+    } else {
+        (ProviderKind::DEFAULT_ORDER.to_vec(), false)
+    };
+
+    let mut chain = GeoChain::new();
+    let mut added = Vec::new();
+    for kind in order {
+        if added.contains(&kind) {
+            continue;
+        }
+        let provider: Box<dyn GeoProvider> = match kind {
+            ProviderKind::Mmdb => match &files.db {
+                Some(path) => Box::new(MmdbDatabase::open(path)?),
+                None if explicit => return Err(missing_provider(kind, "--db", "geo.mmdb")),
+                None => continue,
+            },
+            ProviderKind::Rir => match &files.table {
+                Some(path) => Box::new(RirTable::open(path)?),
+                None if explicit => return Err(missing_provider(kind, "--table", "geo.table")),
+                None => continue,
+            },
+            ProviderKind::Csv if !files.csv.is_empty() => Box::new(CsvTable::open(&files.csv)?),
+            ProviderKind::Csv if explicit => {
+                return Err(missing_provider(kind, "--csv", "geo.csv"));
+            }
+            ProviderKind::Csv => continue,
+            ProviderKind::Geofeed if !files.geofeed.is_empty() => {
+                Box::new(GeofeedTable::open(&files.geofeed)?)
+            }
+            ProviderKind::Geofeed if explicit => {
+                return Err(missing_provider(kind, "--geofeed", "geo.geofeed"));
+            }
+            ProviderKind::Geofeed => continue,
+        };
+        chain.push(provider);
+        added.push(kind);
+    }
+
+    if chain.is_empty() {
+        return Err(Error::config(
+            CONFIG_FILE,
+            "no geolocation database configured (pass --db, --table, --csv or --geofeed, or set geo.mmdb, geo.table, geo.csv or geo.geofeed)",
+        ));
+    }
+    Ok(chain)
+}
+
+fn missing_provider(kind: ProviderKind, flag: &str, key: &str) -> Error {
+    Error::config(
+        CONFIG_FILE,
+        format!(
+            "provider '{}' is listed but has no data file (pass {} or set {})",
+            kind, flag, key
+        ),
+    )
+}
+
+/// Look up the country for an IP address and return the outcome
+pub fn country_code_report(ip_address: &str, chain: &GeoChain) -> Result<CountryCodeReport, Error> {
+    let endpoint = parse_endpoint(ip_address)?;
+    let ip = endpoint.ip;
+    let answer = chain.lookup(ip)?;
+
+    Ok(CountryCodeReport {
+        ip,
+        info: get_endpoint_info(&endpoint),
+        provider: answer.as_ref().map(|answer| answer.provider),
+        provider_source: answer.as_ref().map(|answer| answer.source.clone()),
+        geo: answer.map(|answer| answer.record).unwrap_or_default(),
+    })
+}
 
 pub fn check_country_code(
     ip_address: &str,
+    chain: &GeoChain,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = country_code_report(ip_address, chain)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "No country found for {}",
+            report.ip
+        )));
+    }
+
+    Ok(())
+}
+
+/// Aggregated networks attributed to a set of countries (`cc ranges`)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CountryRangesReport {
+    pub countries: Vec<String>,
+    pub family: IpFamily,
+    /// Text rendering of the networks
+    #[serde(skip)]
+    pub format: ExportFormat,
+    /// nginx variable or nftables/ipset set name
+    #[serde(skip)]
+    pub name: String,
+    /// CIDR blocks in address order, IPv4 first, labelled with their country
+    pub networks: Vec<ExportEntry>,
+}
+
+impl Report for CountryRangesReport {
+    fn render_text(&self, _verbose: bool) -> String {
+        render_networks(&self.networks, self.format, &self.name)
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["network", "country"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.networks
+            .iter()
+            .map(|entry| vec![entry.network.clone(), csv_optional(&entry.label)])
+            .collect()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.networks.is_empty()
+    }
+}
+
+/// List every aggregated CIDR block that `chain` attributes to one of
+/// `countries`, following the chain's precedence
+pub fn country_ranges_report(
+    countries: &[String],
+    family: IpFamily,
+    format: ExportFormat,
+    name: &str,
+    chain: &GeoChain,
+) -> Result<CountryRangesReport, Error> {
+    let mut codes = Vec::with_capacity(countries.len());
+    for country in countries {
+        let code = parse_country_code(country)
+            .ok_or_else(|| Error::invalid_input(country.as_str(), "Invalid country code"))?;
+        if !codes.contains(&code) {
+            codes.push(code);
+        }
+    }
+
+    let mut networks = Vec::new();
+    for ipv4 in [true, false] {
+        if !family.includes(ipv4) {
+            continue;
+        }
+        for entry in chain.country_ranges(&codes, ipv4)? {
+            networks.extend(ExportEntry::from_range(
+                entry.start,
+                entry.end,
+                ipv4,
+                Some(&entry.country_code()),
+            ));
+        }
+    }
+
+    Ok(CountryRangesReport {
+        countries: codes
+            .iter()
+            .map(|code| String::from_utf8_lossy(code).into_owned())
+            .collect(),
+        family,
+        format,
+        name: name.to_string(),
+        networks,
+    })
+}
+
+pub fn check_country_ranges(
+    countries: &[String],
+    family: IpFamily,
+    export_format: ExportFormat,
+    name: &str,
+    chain: &GeoChain,
     verbose: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let ip = parse_ip_address(ip_address)?;
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = country_ranges_report(countries, family, export_format, name, chain)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "No networks found for {}",
+            report.countries.join(", ")
+        )));
+    }
+
+    Ok(())
+}
 
-    println!("Checking country code for {}...", ip);
+/// Statistics of one delegated file ingested by `cc build-db`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DelegatedFileSummary {
+    pub file: String,
+    pub registry: Option<Registry>,
+    pub records: usize,
+}
+
+/// Result of building a range table from RIR delegated statistics
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BuildTableReport {
+    pub table: String,
+    pub files: Vec<DelegatedFileSummary>,
+    /// Ranges after merging adjacent blocks of the same country and registry
+    pub ipv4_ranges: usize,
+    pub ipv6_ranges: usize,
+}
+
+impl Report for BuildTableReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = String::new();
+        if verbose {
+            for file in &self.files {
+                text.push_str(&format!(
+                    "Loaded {} records from {}{}\n",
+                    file.records,
+                    file.file,
+                    file.registry
+                        .map(|registry| format!(" ({})", registry))
+                        .unwrap_or_default()
+                ));
+            }
+        }
+        text.push_str(&format!(
+            "✓ Wrote {} IPv4 and {} IPv6 ranges to {}\n",
+            self.ipv4_ranges, self.ipv6_ranges, self.table
+        ));
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["table", "files", "ipv4_ranges", "ipv6_ranges"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.table.clone(),
+            self.files.len().to_string(),
+            self.ipv4_ranges.to_string(),
+            self.ipv6_ranges.to_string(),
+        ]
+    }
+}
+
+/// Build a range table from the five RIRs' `delegated-*-extended` files and
+/// write it to `table`
+pub fn build_country_table(files: &[PathBuf], table: &Path) -> Result<BuildTableReport, Error> {
+    let mut builder = RangeTableBuilder::new();
+    let mut summaries = Vec::with_capacity(files.len());
+
+    for file in files {
+        let source = file.display().to_string();
+        let content = fs::read_to_string(file).map_err(|e| Error::data_parse(&source, None, e))?;
+        let (registry, records) = load_delegated(&content, &source, &mut builder)?;
+        summaries.push(DelegatedFileSummary {
+            file: source,
+            registry,
+            records,
+        });
+    }
+
+    let range_table = builder.build()?;
+    range_table.save(table)?;
+    let (ipv4_ranges, ipv6_ranges) = range_table.len();
+
+    Ok(BuildTableReport {
+        table: table.display().to_string(),
+        files: summaries,
+        ipv4_ranges,
+        ipv6_ranges,
+    })
+}
+
+/// Result of validating an RFC 8805 geofeed (`cc geofeed check`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GeofeedCheckReport {
+    pub file: String,
+    /// Lines without errors
+    pub entries: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<GeofeedIssue>,
+}
 
-    print_ip_details(&ip, verbose);
+impl Report for GeofeedCheckReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Checking geofeed {}...\n", self.file);
+        for issue in &self.issues {
+            if issue.severity == IssueSeverity::Warning && !verbose {
+                continue;
+            }
+            text.push_str(&format!(
+                "  line {}: {}: {}: {}\n",
+                issue.line,
+                issue.severity.as_str(),
+                issue.prefix,
+                issue.message
+            ));
+        }
+        text.push_str(&format!(
+            "✓ {} valid entries, {} errors, {} warnings\n",
+            self.entries, self.errors, self.warnings
+        ));
+        text
+    }
 
-    if verbose {
-        println!("Verbose mode enabled for country code check");
+    fn csv_header() -> &'static [&'static str] {
+        &["file", "line", "severity", "prefix", "message"]
     }
 
-    // TODO: Implement actual geolocation lookup
-    // - Use GeoIP database or API
-    // - Show country code, country name
-    // - Show detailed geolocation info if verbose (city, region, ISP, etc.)
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
 
-    println!("✓ Country code check completed (not implemented yet)");
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.issues
+            .iter()
+            .map(|issue| {
+                vec![
+                    self.file.clone(),
+                    issue.line.to_string(),
+                    issue.severity.as_str().to_string(),
+                    issue.prefix.clone(),
+                    issue.message.clone(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Warnings alone do not fail the check
+    fn is_negative(&self) -> bool {
+        self.errors > 0
+    }
+}
+
+/// Validate a geofeed file
+pub fn geofeed_check_report(file: &Path) -> Result<GeofeedCheckReport, Error> {
+    let source = file.display().to_string();
+    let content = fs::read_to_string(file).map_err(|e| Error::data_parse(&source, None, e))?;
+    let feed = parse_geofeed(&content, &source)?;
+
+    Ok(GeofeedCheckReport {
+        entries: feed.entries.len(),
+        errors: feed.errors(),
+        warnings: feed.warnings(),
+        issues: feed.issues,
+        file: source,
+    })
+}
+
+pub fn check_geofeed(file: &Path, verbose: bool, format: OutputFormat) -> Result<(), Error> {
+    let report = geofeed_check_report(file)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "{} has {} invalid entries",
+            report.file, report.errors
+        )));
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/GeoLite2-City-Test.mmdb"
+    );
+
+    fn fixture() -> GeoChain {
+        open_geo_chain(&GeoSources {
+            db: Some(PathBuf::from(FIXTURE)),
+            ..GeoSources::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_country_code_report() {
+        let db = fixture();
+        let report = country_code_report("1.0.16.20", &db).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("JP"));
+        assert!(!report.is_negative());
+
+        let report = country_code_report("192.0.2.1", &db).unwrap();
+        assert_eq!(report.geo, GeoRecord::default());
+        assert!(report.is_negative());
+
+        assert!(country_code_report("not-an-ip", &db).is_err());
+    }
+
+    #[test]
+    fn test_country_code_report_text() {
+        let db = fixture();
+        let report = country_code_report("1.0.16.20", &db).unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "Checking country code for 1.0.16.20...\n✓ JP (Japan)\n"
+        );
+
+        let text = report.render_text(true);
+        assert!(text.contains("  Continent: AS (Asia)\n"));
+        assert!(text.contains("  City: Tokyo\n"));
+        assert!(text.contains("  Coordinates: 35.6895, 139.6917\n"));
+    }
+
+    #[test]
+    fn test_build_country_table() {
+        let dir = std::env::temp_dir();
+        let delegated = dir.join("ipchecker-test-delegated-ripencc-extended");
+        let table = dir.join("ipchecker-test-countries.bin");
+        fs::write(
+            &delegated,
+            "2|ripencc|20240101|2|19830705|20231231|+0100\n\
+             ripencc|FR|ipv4|2.0.0.0|8388608|20100712|allocated|a1\n\
+             ripencc|DE|ipv6|2001:db8::|32|20100712|allocated|a2\n",
+        )
+        .unwrap();
+
+        let report = build_country_table(std::slice::from_ref(&delegated), &table).unwrap();
+        assert_eq!(report.ipv4_ranges, 1);
+        assert_eq!(report.ipv6_ranges, 1);
+        assert_eq!(report.files[0].registry, Some(Registry::RipeNcc));
+
+        let db = open_geo_chain(&GeoSources {
+            table: Some(table.clone()),
+            ..GeoSources::default()
+        })
+        .unwrap();
+        let report = country_code_report("2.15.255.255", &db).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("FR"));
+        assert_eq!(report.geo.registry.as_deref(), Some("ripencc"));
+        assert_eq!(report.provider, Some(ProviderKind::Rir));
+        assert!(report.render_text(true).contains("  Registry: ripencc\n"));
+
+        // MMDB first, RIR as fallback
+        let mut sources = GeoSources {
+            db: Some(PathBuf::from(FIXTURE)),
+            table: Some(table.clone()),
+            ..GeoSources::default()
+        };
+        let chain = open_geo_chain(&sources).unwrap();
+        let report = country_code_report("2.125.160.216", &chain).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("GB"));
+        assert_eq!(report.provider, Some(ProviderKind::Mmdb));
+        let report = country_code_report("2.15.255.255", &chain).unwrap();
+        assert_eq!(report.provider, Some(ProviderKind::Rir));
+        assert_eq!(
+            report.provider_source.as_deref(),
+            Some(table.display().to_string().as_str())
+        );
+
+        sources.providers = vec![ProviderKind::Rir, ProviderKind::Mmdb];
+        let chain = open_geo_chain(&sources).unwrap();
+        let report = country_code_report("2.125.160.216", &chain).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("FR"));
+        assert_eq!(report.provider, Some(ProviderKind::Rir));
+
+        sources.providers = vec![ProviderKind::Csv];
+        let err = open_geo_chain(&sources).err().unwrap();
+        assert!(matches!(err, Error::Config { .. }));
+
+        fs::remove_file(delegated).unwrap();
+        fs::remove_file(table).unwrap();
+    }
+
+    #[test]
+    fn test_load_csv_table() {
+        let dir = std::env::temp_dir();
+        let ipv4 = dir.join("ipchecker-test-dbip-country-ipv4.csv");
+        let ipv6 = dir.join("ipchecker-test-dbip-country-ipv6.csv");
+        fs::write(&ipv4, "1.0.0.0,1.0.0.255,AU\n1.0.1.0,1.0.3.255,CN\n").unwrap();
+        fs::write(
+            &ipv6,
+            "2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP\n",
+        )
+        .unwrap();
+
+        let db = open_geo_chain(&GeoSources {
+            csv: vec![ipv4.clone(), ipv6.clone()],
+            ..GeoSources::default()
+        })
+        .unwrap();
+        let report = country_code_report("1.0.2.1", &db).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("CN"));
+        let report = country_code_report("2001:200::1", &db).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("JP"));
+
+        assert_eq!(report.provider, Some(ProviderKind::Csv));
+
+        // The same IPv4 export twice overlaps itself
+        let err = CsvTable::open(&[ipv4.clone(), ipv4.clone()]).err().unwrap();
+        assert_eq!(err.exit_code(), crate::error::EXIT_DATA_PARSE);
+
+        fs::remove_file(ipv4).unwrap();
+        fs::remove_file(ipv6).unwrap();
+    }
+
+    #[test]
+    fn test_geofeed() {
+        let feed = std::env::temp_dir().join("ipchecker-test-geofeed.csv");
+        fs::write(
+            &feed,
+            "# Example geofeed\n\
+             1.0.16.0/24,SE,SE-AB,Stockholm,\n\
+             1.0.16.0/25,SE,,,\n\
+             1.0.17.1/24,SE,,,\n",
+        )
+        .unwrap();
+
+        let report = geofeed_check_report(&feed).unwrap();
+        assert_eq!((report.entries, report.errors, report.warnings), (2, 1, 1));
+        assert!(report.is_negative());
+        assert_eq!(
+            report.render_text(false),
+            format!(
+                "Checking geofeed {}...\n  \
+                 line 4: error: 1.0.17.1/24: prefix has host bits set; the network is 1.0.17.0/24\n\
+                 ✓ 2 valid entries, 1 errors, 1 warnings\n",
+                feed.display()
+            )
+        );
+        assert_eq!(report.csv_records()[1][1..3], ["4", "error"]);
+
+        // The geofeed answers before the MMDB by default
+        let db = open_geo_chain(&GeoSources {
+            db: Some(PathBuf::from(FIXTURE)),
+            geofeed: vec![feed.clone()],
+            ..GeoSources::default()
+        })
+        .unwrap();
+        let report = country_code_report("1.0.16.20", &db).unwrap();
+        assert_eq!(report.geo.country_code.as_deref(), Some("SE"));
+        assert_eq!(report.provider, Some(ProviderKind::Geofeed));
+        let report = country_code_report("2.125.160.216", &db).unwrap();
+        assert_eq!(report.provider, Some(ProviderKind::Mmdb));
+
+        fs::remove_file(feed).unwrap();
+    }
+
+    #[test]
+    fn test_country_ranges_report() {
+        let chain = fixture();
+        let countries = ["jp".to_string(), "GB".to_string()];
+        let report = country_ranges_report(
+            &countries,
+            IpFamily::Both,
+            ExportFormat::Plain,
+            "geo",
+            &chain,
+        )
+        .unwrap();
+        assert_eq!(report.countries, ["JP", "GB"]);
+        assert_eq!(
+            report.render_text(false),
+            "1.0.16.0/24\n2.125.160.0/19\n2001:218::/32\n"
+        );
+
+        let report = country_ranges_report(
+            &countries[..1],
+            IpFamily::V6,
+            ExportFormat::Nginx,
+            "cc",
+            &chain,
+        )
+        .unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "geo $cc {\n    default \"\";\n    2001:218::/32 JP;\n}\n"
+        );
+
+        let report = country_ranges_report(
+            &["KR".to_string()],
+            IpFamily::Both,
+            ExportFormat::Plain,
+            "geo",
+            &chain,
+        )
+        .unwrap();
+        assert!(report.is_negative());
+
+        let err = country_ranges_report(
+            &["KOR".to_string()],
+            IpFamily::Both,
+            ExportFormat::Plain,
+            "geo",
+            &chain,
+        )
+        .unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+    }
+
+    #[test]
+    fn test_country_code_report_json_is_flat() {
+        let db = fixture();
+        let report = country_code_report("2.125.160.216", &db).unwrap();
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["country_code"], "GB");
+        assert_eq!(json["registered_country_code"], "FR");
+        assert!(json["city"].is_null());
+    }
+}
@@ -1,8 +1,121 @@
-use crate::ip_utils::parse_cidr;
+use crate::asn::{AsnProvider, describe_range};
+use crate::batch::{parse_list, read_texts};
+use crate::error::Error;
+use crate::ip_utils::{
+    ListEntry, ReverseZone, merge_ranges, network_range, parse_cidr, range_to_cidrs, reverse_zones,
+    subtract_ranges, value_address,
+};
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use crate::reverse_zone::{
+    HostEntry, PLACEHOLDER_NAMESERVERS, PtrRecord, fully_qualified, open_hosts, render_zone,
+};
+use serde::Serialize;
+use std::collections::BTreeMap;
 use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::Path;
+
+/// Result of a CIDR overlap check
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CidrOverlapReport {
+    /// First network as given on the command line
+    pub network1: String,
+    /// Second network as given on the command line
+    pub network2: String,
+    /// First network with host bits cleared (e.g. 192.168.1.0/24)
+    pub network1_address: String,
+    /// Second network with host bits cleared
+    pub network2_address: String,
+    pub overlap: bool,
+    /// The input network that contains the other, when they overlap
+    pub containing_network: Option<String>,
+    /// Parsed address and prefix length of each network
+    #[serde(skip)]
+    pub parsed: [(IpAddr, u8); 2],
+}
+
+impl Report for CidrOverlapReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!(
+            "Checking CIDR overlap between {} and {}...\n",
+            self.network1, self.network2
+        );
+
+        if verbose {
+            text.push_str("Verbose mode enabled for CIDR check\n");
+            for (index, (network, (ip, prefix))) in [&self.network1, &self.network2]
+                .into_iter()
+                .zip(self.parsed)
+                .enumerate()
+            {
+                text.push_str(&format!(
+                    "Network {}: {} -> IP: {}, Prefix: {}\n",
+                    index + 1,
+                    network,
+                    ip,
+                    prefix
+                ));
+            }
+            text.push_str(&format!("Network address 1: {}\n", self.network1_address));
+            text.push_str(&format!("Network address 2: {}\n", self.network2_address));
+        }
+
+        if self.overlap {
+            text.push_str(&format!(
+                "✓ Networks {} and {} OVERLAP\n",
+                self.network1, self.network2
+            ));
+            if verbose && let Some(larger_network) = &self.containing_network {
+                text.push_str(&format!(
+                    "  The network {} contains or overlaps with the other\n",
+                    larger_network
+                ));
+                text.push_str(&format!(
+                    "  Effective overlap determined by /{} prefix\n",
+                    self.parsed[0].1.min(self.parsed[1].1)
+                ));
+            }
+        } else {
+            text.push_str(&format!(
+                "✓ Networks {} and {} do NOT overlap\n",
+                self.network1, self.network2
+            ));
+            if verbose {
+                text.push_str("  These networks are in separate address spaces\n");
+            }
+        }
+
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "network1",
+            "network2",
+            "network1_address",
+            "network2_address",
+            "overlap",
+            "containing_network",
+        ]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.network1.clone(),
+            self.network2.clone(),
+            self.network1_address.clone(),
+            self.network2_address.clone(),
+            self.overlap.to_string(),
+            csv_optional(&self.containing_network),
+        ]
+    }
+
+    fn is_negative(&self) -> bool {
+        !self.overlap
+    }
+}
 
 /// Calculate network address from IP and prefix length
-fn get_network_address(ip: IpAddr, prefix_len: u8) -> Result<IpAddr, Box<dyn std::error::Error>> {
+fn get_network_address(ip: IpAddr, prefix_len: u8) -> Result<IpAddr, Error> {
     match ip {
         IpAddr::V4(ipv4) => {
             let ip_u32 = u32::from(ipv4);
@@ -28,12 +141,7 @@ fn get_network_address(ip: IpAddr, prefix_len: u8) -> Result<IpAddr, Box<dyn std
 }
 
 /// Check if two CIDR networks overlap
-fn networks_overlap(
-    ip1: IpAddr,
-    prefix1: u8,
-    ip2: IpAddr,
-    prefix2: u8,
-) -> Result<bool, Box<dyn std::error::Error>> {
+fn networks_overlap(ip1: IpAddr, prefix1: u8, ip2: IpAddr, prefix2: u8) -> Result<bool, Error> {
     // Different IP versions cannot overlap
     match (ip1, ip2) {
         (IpAddr::V4(_), IpAddr::V6(_)) | (IpAddr::V6(_), IpAddr::V4(_)) => return Ok(false),
@@ -78,66 +186,546 @@ fn networks_overlap(
     }
 }
 
+/// Check two CIDR networks for overlap and return the outcome
+pub fn cidr_overlap_report(network1: &str, network2: &str) -> Result<CidrOverlapReport, Error> {
+    let (ip1, prefix1) = parse_cidr(network1)?;
+    let (ip2, prefix2) = parse_cidr(network2)?;
+
+    let network_addr1 = get_network_address(ip1, prefix1)?;
+    let network_addr2 = get_network_address(ip2, prefix2)?;
+    let overlap = networks_overlap(ip1, prefix1, ip2, prefix2)?;
+
+    let containing_network = if overlap {
+        Some(
+            if prefix1 < prefix2 {
+                network1
+            } else {
+                network2
+            }
+            .to_string(),
+        )
+    } else {
+        None
+    };
+
+    Ok(CidrOverlapReport {
+        network1: network1.to_string(),
+        network2: network2.to_string(),
+        network1_address: format!("{}/{}", network_addr1, prefix1),
+        network2_address: format!("{}/{}", network_addr2, prefix2),
+        overlap,
+        containing_network,
+        parsed: [(ip1, prefix1), (ip2, prefix2)],
+    })
+}
+
+/// Split a batch input line into its two networks (separated by whitespace or a comma)
+pub fn parse_network_pair(line: &str) -> Result<(&str, &str), Error> {
+    let networks: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match networks.as_slice() {
+        [network1, network2] => Ok((network1, network2)),
+        _ => Err(Error::invalid_input(line, "Expected two CIDR networks")),
+    }
+}
+
+/// Check one batch input line holding two networks
+pub fn cidr_overlap_line_report(line: &str) -> Result<CidrOverlapReport, Error> {
+    let (network1, network2) = parse_network_pair(line)?;
+    cidr_overlap_report(network1, network2)
+}
+
 pub fn check_cidr_overlap(
     network1: &str,
     network2: &str,
     verbose: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
-    println!(
-        "Checking CIDR overlap between {} and {}...",
-        network1, network2
-    );
-
-    let (ip1, prefix1) = parse_cidr(network1)?;
-    let (ip2, prefix2) = parse_cidr(network2)?;
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = cidr_overlap_report(network1, network2)?;
+    output::print_report(&report, format, verbose)?;
 
-    if verbose {
-        println!("Verbose mode enabled for CIDR check");
-        println!(
-            "Network 1: {} -> IP: {}, Prefix: {}",
-            network1, ip1, prefix1
-        );
-        println!(
-            "Network 2: {} -> IP: {}, Prefix: {}",
-            network2, ip2, prefix2
-        );
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "Networks {} and {} do not overlap",
+            network1, network2
+        )));
     }
 
-    // Calculate network addresses
-    let network_addr1 = get_network_address(ip1, prefix1)?;
-    let network_addr2 = get_network_address(ip2, prefix2)?;
+    Ok(())
+}
+
+/// How a route relates to the network being checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteRelation {
+    /// The route contains the whole network
+    Covering,
+    /// The route is the network itself
+    Exact,
+    /// The route lies inside the network
+    MoreSpecific,
+}
 
-    if verbose {
-        println!("Network address 1: {}/{}", network_addr1, prefix1);
-        println!("Network address 2: {}/{}", network_addr2, prefix2);
+impl RouteRelation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteRelation::Covering => "covering",
+            RouteRelation::Exact => "exact",
+            RouteRelation::MoreSpecific => "more_specific",
+        }
     }
+}
+
+/// A route overlapping the checked network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RouteEntry {
+    /// CIDR notation, or "start - end" for ranges that are not one block
+    pub route: String,
+    pub asn: u32,
+    pub relation: RouteRelation,
+}
+
+/// Routes overlapping a network, with their origin AS (`cidr routes`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CidrRoutesReport {
+    /// Network as given on the command line
+    pub network: String,
+    /// Network with host bits cleared
+    pub network_address: String,
+    /// Covering routes first, then the network itself and more specifics
+    pub routes: Vec<RouteEntry>,
+    /// File(s) the routing data was loaded from
+    pub source: String,
+}
 
-    // Check for overlap
-    let overlaps = networks_overlap(ip1, prefix1, ip2, prefix2)?;
+impl Report for CidrRoutesReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Checking routes overlapping {}...\n", self.network);
 
-    if overlaps {
-        println!("✓ Networks {} and {} OVERLAP", network1, network2);
         if verbose {
-            let smaller_prefix = prefix1.min(prefix2);
-            let larger_network = if prefix1 < prefix2 {
-                network1
+            text.push_str(&format!("Network address: {}\n", self.network_address));
+        }
+
+        if self.routes.is_empty() {
+            text.push_str(&format!("✓ No routes overlap {}\n", self.network));
+        } else {
+            text.push_str(&format!(
+                "✓ {} route(s) overlap {}\n",
+                self.routes.len(),
+                self.network
+            ));
+            for route in &self.routes {
+                text.push_str(&format!(
+                    "  {} AS{} ({})\n",
+                    route.route,
+                    route.asn,
+                    route.relation.as_str().replace('_', " ")
+                ));
+            }
+        }
+
+        if verbose {
+            text.push_str(&format!("  Source: {}\n", self.source));
+        }
+
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["network", "route", "asn", "relation"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.routes
+            .iter()
+            .map(|route| {
+                vec![
+                    self.network.clone(),
+                    route.route.clone(),
+                    route.asn.to_string(),
+                    route.relation.as_str().to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.routes.is_empty()
+    }
+}
+
+/// List the routes in the ASN data that overlap a network: those covering
+/// it, the network itself and more specific ones
+pub fn cidr_routes_report(
+    network: &str,
+    source: &dyn AsnProvider,
+) -> Result<CidrRoutesReport, Error> {
+    let (ip, prefix_len) = parse_cidr(network)?;
+    let ipv4 = ip.is_ipv4();
+    let (start, end) = network_range(ip, prefix_len);
+
+    let routes = source
+        .routes(ipv4, start, end)?
+        .into_iter()
+        .map(|range| RouteEntry {
+            route: describe_range(range.start, range.end, ipv4),
+            asn: range.asn,
+            relation: if range.start == start && range.end == end {
+                RouteRelation::Exact
+            } else if range.start <= start && range.end >= end {
+                RouteRelation::Covering
             } else {
-                network2
-            };
-            println!(
-                "  The network {} contains or overlaps with the other",
-                larger_network
-            );
-            println!(
-                "  Effective overlap determined by /{} prefix",
-                smaller_prefix
-            );
+                RouteRelation::MoreSpecific
+            },
+        })
+        .collect();
+
+    Ok(CidrRoutesReport {
+        network: network.to_string(),
+        network_address: format!("{}/{}", value_address(start, ipv4), prefix_len),
+        routes,
+        source: source.source(),
+    })
+}
+
+pub fn check_cidr_routes(
+    network: &str,
+    source: &dyn AsnProvider,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = cidr_routes_report(network, source)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!("No routes overlap {}", network)));
+    }
+
+    Ok(())
+}
+
+/// A host mapping row left out of the zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ZoneIssue {
+    pub line: usize,
+    pub ip: IpAddr,
+    pub message: String,
+}
+
+/// One generated zone and its PTR records
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ZoneFile {
+    #[serde(flatten)]
+    pub zone: ReverseZone,
+    /// Records in address order
+    pub records: Vec<PtrRecord>,
+}
+
+/// Reverse zones generated from a hosts mapping (`cidr reverse-zone`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReverseZoneReport {
+    /// Network with host bits cleared
+    pub network: String,
+    /// Hosts mapping file
+    pub hosts: String,
+    pub nameservers: Vec<String>,
+    pub zones: Vec<ZoneFile>,
+    /// Rows outside the network and duplicate PTRs, in line order
+    pub issues: Vec<ZoneIssue>,
+}
+
+impl Report for ReverseZoneReport {
+    /// The zone files themselves, with any issues as leading comments
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = String::new();
+        if verbose {
+            text.push_str(&format!(
+                "; {}: {} zone(s), {} PTR record(s) from {}\n",
+                self.network,
+                self.zones.len(),
+                self.zones
+                    .iter()
+                    .map(|zone| zone.records.len())
+                    .sum::<usize>(),
+                self.hosts
+            ));
+        }
+        for issue in &self.issues {
+            text.push_str(&format!(
+                "; line {}: {}: {}\n",
+                issue.line, issue.ip, issue.message
+            ));
+        }
+        for (index, zone) in self.zones.iter().enumerate() {
+            if index > 0 || !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&render_zone(&zone.zone, &zone.records, &self.nameservers));
         }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["zone", "ip", "owner", "hostname"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.zones
+            .iter()
+            .flat_map(|zone| {
+                zone.records.iter().map(|record| {
+                    vec![
+                        zone.zone.name.clone(),
+                        record.ip.to_string(),
+                        record.owner.clone(),
+                        record.hostname.clone(),
+                    ]
+                })
+            })
+            .collect()
+    }
+
+    /// Rows that had to be left out make the result negative
+    fn is_negative(&self) -> bool {
+        !self.issues.is_empty()
+    }
+}
+
+/// Build the reverse zones of `network` from a hosts mapping. Addresses
+/// outside the network and second PTRs for an address are reported as
+/// issues and left out. IPv4 networks longer than /24 get an RFC 2317 zone.
+pub fn reverse_zone_report(
+    network: &str,
+    hosts: &[HostEntry],
+    hosts_source: &str,
+    nameservers: &[String],
+) -> Result<ReverseZoneReport, Error> {
+    let (ip, prefix_len) = parse_cidr(network)?;
+    let network_address = get_network_address(ip, prefix_len)?;
+    let network = format!("{}/{}", network_address, prefix_len);
+
+    let mut zones: Vec<ZoneFile> = reverse_zones(ip, prefix_len)
+        .into_iter()
+        .map(|zone| ZoneFile {
+            zone,
+            records: Vec::new(),
+        })
+        .collect();
+    let mut issues = Vec::new();
+    let mut seen: BTreeMap<IpAddr, &HostEntry> = BTreeMap::new();
+
+    for host in hosts {
+        let Some((zone, owner)) = zones
+            .iter_mut()
+            .find_map(|zone| zone.zone.relative_name(host.ip).map(|owner| (zone, owner)))
+        else {
+            issues.push(ZoneIssue {
+                line: host.line,
+                ip: host.ip,
+                message: format!("outside {}", network),
+            });
+            continue;
+        };
+        if let Some(first) = seen.get(&host.ip) {
+            issues.push(ZoneIssue {
+                line: host.line,
+                ip: host.ip,
+                message: format!(
+                    "duplicate PTR {}; line {} already maps it to {}",
+                    host.hostname, first.line, first.hostname
+                ),
+            });
+            continue;
+        }
+        seen.insert(host.ip, host);
+        zone.records.push(PtrRecord {
+            ip: host.ip,
+            owner,
+            hostname: host.hostname.clone(),
+        });
+    }
+    for zone in &mut zones {
+        zone.records.sort_by_key(|record| record.ip);
+    }
+
+    let nameservers = if nameservers.is_empty() {
+        PLACEHOLDER_NAMESERVERS.map(str::to_string).to_vec()
     } else {
-        println!("✓ Networks {} and {} do NOT overlap", network1, network2);
+        nameservers
+            .iter()
+            .map(|name| fully_qualified(name))
+            .collect()
+    };
+
+    Ok(ReverseZoneReport {
+        network,
+        hosts: hosts_source.to_string(),
+        nameservers,
+        zones,
+        issues,
+    })
+}
+
+pub fn check_reverse_zone(
+    network: &str,
+    hosts_file: &Path,
+    nameservers: &[String],
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let hosts = open_hosts(hosts_file)?;
+    let report = reverse_zone_report(
+        network,
+        &hosts,
+        &hosts_file.display().to_string(),
+        nameservers,
+    )?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "{} host mapping(s) left out of the zone",
+            report.issues.len()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Address space added and removed between two lists (`cidr diff`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CidrDiffReport {
+    /// Old list file
+    pub old: String,
+    /// New list file
+    pub new: String,
+    /// Minimal CIDRs covered by the new list only, IPv4 first
+    pub added: Vec<String>,
+    /// Minimal CIDRs covered by the old list only, IPv4 first
+    pub removed: Vec<String>,
+}
+
+impl Report for CidrDiffReport {
+    /// "+ network" and "- network" lines, like a unified diff
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = String::new();
         if verbose {
-            println!("  These networks are in separate address spaces");
+            text.push_str(&format!(
+                "Comparing {} with {}: {} added, {} removed\n",
+                self.old,
+                self.new,
+                self.added.len(),
+                self.removed.len()
+            ));
+        }
+        for network in &self.removed {
+            text.push_str(&format!("- {}\n", network));
         }
+        for network in &self.added {
+            text.push_str(&format!("+ {}\n", network));
+        }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["change", "network"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        let removed = self
+            .removed
+            .iter()
+            .map(|network| vec!["removed".to_string(), network.clone()]);
+        let added = self
+            .added
+            .iter()
+            .map(|network| vec!["added".to_string(), network.clone()]);
+        removed.chain(added).collect()
+    }
+
+    /// Any change in coverage is a negative result, as with diff
+    fn is_negative(&self) -> bool {
+        !self.added.is_empty() || !self.removed.is_empty()
+    }
+}
+
+/// Merged ranges of one address family covered by `entries`
+fn coverage(entries: &[ListEntry], ipv4: bool) -> Vec<(u128, u128)> {
+    merge_ranges(
+        entries
+            .iter()
+            .filter(|entry| entry.ip.is_ipv4() == ipv4)
+            .map(ListEntry::range)
+            .collect(),
+    )
+}
+
+/// Minimal CIDRs covered by `entries` but not by `other`
+fn uncovered(entries: &[ListEntry], other: &[ListEntry]) -> Vec<String> {
+    [true, false]
+        .into_iter()
+        .flat_map(|ipv4| {
+            subtract_ranges(&coverage(entries, ipv4), &coverage(other, ipv4))
+                .into_iter()
+                .flat_map(move |(start, end)| range_to_cidrs(start, end, ipv4))
+        })
+        .map(|(ip, prefix_len)| format!("{}/{}", ip, prefix_len))
+        .collect()
+}
+
+/// Compare two lists by the addresses they cover rather than line by
+/// line, so that two /25s replaced by their /24 is no change
+pub fn cidr_diff_report(
+    old: &[ListEntry],
+    new: &[ListEntry],
+    old_source: &str,
+    new_source: &str,
+) -> CidrDiffReport {
+    CidrDiffReport {
+        old: old_source.to_string(),
+        new: new_source.to_string(),
+        added: uncovered(new, old),
+        removed: uncovered(old, new),
+    }
+}
+
+pub fn check_cidr_diff(
+    old: &Path,
+    new: &Path,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let old_entries = parse_list(&read_texts(&[old.to_path_buf()])?)?;
+    let new_entries = parse_list(&read_texts(&[new.to_path_buf()])?)?;
+    let report = cidr_diff_report(
+        &old_entries,
+        &new_entries,
+        &old.display().to_string(),
+        &new.display().to_string(),
+    );
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "{} added and {} removed network(s)",
+            report.added.len(),
+            report.removed.len()
+        )));
     }
 
     Ok(())
@@ -258,20 +846,73 @@ mod tests {
     #[test]
     fn test_check_cidr_overlap_integration() {
         // Test with overlapping networks
-        let result = check_cidr_overlap("192.168.1.0/24", "192.168.0.0/16", false);
-        assert!(result.is_ok());
+        let report = cidr_overlap_report("192.168.1.0/24", "192.168.0.0/16").unwrap();
+        assert!(report.overlap);
 
         // Test with non-overlapping networks
-        let result = check_cidr_overlap("192.168.1.0/24", "10.0.0.0/8", false);
-        assert!(result.is_ok());
+        let report = cidr_overlap_report("192.168.1.0/24", "10.0.0.0/8").unwrap();
+        assert!(!report.overlap);
 
         // Test with invalid CIDR notation
-        let result = check_cidr_overlap("invalid", "192.168.0.0/16", false);
-        assert!(result.is_err());
+        let result = cidr_overlap_report("invalid", "192.168.0.0/16");
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
 
         // Test with invalid prefix length
-        let result = check_cidr_overlap("192.168.1.0/33", "192.168.0.0/16", false);
-        assert!(result.is_err());
+        let result = cidr_overlap_report("192.168.1.0/33", "192.168.0.0/16");
+        assert!(matches!(result, Err(Error::InvalidInput { .. })));
+    }
+
+    #[test]
+    fn test_cidr_overlap_report() {
+        let report = cidr_overlap_report("192.168.1.77/24", "192.168.0.0/16").unwrap();
+        assert!(report.overlap);
+        assert_eq!(report.network1_address, "192.168.1.0/24");
+        assert_eq!(report.containing_network.as_deref(), Some("192.168.0.0/16"));
+
+        let report = cidr_overlap_report("192.168.1.0/24", "10.0.0.0/8").unwrap();
+        assert!(!report.overlap);
+        assert_eq!(report.containing_network, None);
+    }
+
+    #[test]
+    fn test_cidr_overlap_report_text() {
+        let report = cidr_overlap_report("192.168.1.0/24", "192.168.0.0/16").unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "Checking CIDR overlap between 192.168.1.0/24 and 192.168.0.0/16...\n\
+             ✓ Networks 192.168.1.0/24 and 192.168.0.0/16 OVERLAP\n"
+        );
+
+        let text = report.render_text(true);
+        assert!(text.contains("Network 1: 192.168.1.0/24 -> IP: 192.168.1.0, Prefix: 24\n"));
+        assert!(text.contains("  Effective overlap determined by /16 prefix\n"));
+
+        let report = cidr_overlap_report("192.168.1.0/24", "10.0.0.0/8").unwrap();
+        assert!(
+            report
+                .render_text(true)
+                .ends_with("separate address spaces\n")
+        );
+
+        let text = cidr_overlap_report("2001:DB8::/32", "2001:db8:1::/48")
+            .unwrap()
+            .render_text(true);
+        assert!(text.contains("Network 1: 2001:DB8::/32 -> IP: 2001:db8::, Prefix: 32\n"));
+        assert!(text.contains("  Effective overlap determined by /32 prefix\n"));
+    }
+
+    #[test]
+    fn test_parse_network_pair() {
+        assert_eq!(
+            parse_network_pair("10.0.0.0/8 10.1.0.0/16").unwrap(),
+            ("10.0.0.0/8", "10.1.0.0/16")
+        );
+        assert_eq!(
+            parse_network_pair("10.0.0.0/8, 10.1.0.0/16").unwrap(),
+            ("10.0.0.0/8", "10.1.0.0/16")
+        );
+        assert!(parse_network_pair("10.0.0.0/8").is_err());
+        assert!(parse_network_pair("a b c").is_err());
     }
 
     #[test]
@@ -288,4 +929,149 @@ mod tests {
         let result = networks_overlap(ip1, 32, ip2, 32).unwrap();
         assert!(!result); // /32 are individual hosts
     }
+
+    #[test]
+    fn test_cidr_routes_report() {
+        let source = crate::asn::MrtFile::open(&[std::path::PathBuf::from(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/testdata/rib-test.mrt"
+        ))])
+        .unwrap();
+
+        let report = cidr_routes_report("1.0.4.0/23", &source).unwrap();
+        let routes: Vec<(&str, u32, RouteRelation)> = report
+            .routes
+            .iter()
+            .map(|route| (route.route.as_str(), route.asn, route.relation))
+            .collect();
+        assert_eq!(
+            routes,
+            [
+                ("1.0.4.0/22", 38803, RouteRelation::Covering),
+                ("1.0.4.0/24", 4608, RouteRelation::MoreSpecific),
+            ]
+        );
+        assert_eq!(
+            report.render_text(false),
+            "Checking routes overlapping 1.0.4.0/23...\n\
+             ✓ 2 route(s) overlap 1.0.4.0/23\n  \
+             1.0.4.0/22 AS38803 (covering)\n  \
+             1.0.4.0/24 AS4608 (more specific)\n"
+        );
+
+        let report = cidr_routes_report("2001:db8::/32", &source).unwrap();
+        assert_eq!(report.routes[0].relation, RouteRelation::Exact);
+        assert!(
+            cidr_routes_report("11.0.0.0/8", &source)
+                .unwrap()
+                .is_negative()
+        );
+    }
+
+    #[test]
+    fn test_reverse_zone_report() {
+        let hosts = crate::reverse_zone::parse_hosts(
+            "192.0.2.10,web.example.net\n\
+             192.0.2.1,gw.example.net\n\
+             198.51.100.1,other.example.net\n\
+             192.0.2.10,www.example.net\n",
+            "hosts.csv",
+        )
+        .unwrap();
+        let report = reverse_zone_report("192.0.2.0/24", &hosts, "hosts.csv", &[]).unwrap();
+        assert_eq!(report.zones.len(), 1);
+        let owners: Vec<(&str, &str)> = report.zones[0]
+            .records
+            .iter()
+            .map(|record| (record.owner.as_str(), record.hostname.as_str()))
+            .collect();
+        assert_eq!(
+            owners,
+            [("1", "gw.example.net."), ("10", "web.example.net.")]
+        );
+        let issues: Vec<(usize, &str)> = report
+            .issues
+            .iter()
+            .map(|issue| (issue.line, issue.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (3, "outside 192.0.2.0/24"),
+                (
+                    4,
+                    "duplicate PTR www.example.net.; line 1 already maps it to web.example.net."
+                ),
+            ]
+        );
+        assert!(report.is_negative());
+
+        let text = report.render_text(false);
+        assert!(text.starts_with(
+            "; line 3: 198.51.100.1: outside 192.0.2.0/24\n\
+             ; line 4: 192.0.2.10: duplicate PTR www.example.net.; line 1 already maps it to web.example.net.\n\
+             \n$ORIGIN 2.0.192.in-addr.arpa.\n"
+        ));
+        assert!(text.contains("\tIN\tNS\tns1.example.com.\n"));
+        assert!(text.ends_with("1\tIN\tPTR\tgw.example.net.\n10\tIN\tPTR\tweb.example.net.\n"));
+        assert_eq!(
+            report.csv_records()[0],
+            ["2.0.192.in-addr.arpa", "192.0.2.1", "1", "gw.example.net."]
+        );
+    }
+
+    #[test]
+    fn test_reverse_zone_report_ipv6() {
+        let hosts = crate::reverse_zone::parse_hosts("2001:db8::1,host.example.net\n", "hosts.csv")
+            .unwrap();
+        let nameservers = ["ns.example.net".to_string()];
+        let report =
+            reverse_zone_report("2001:db8::/62", &hosts, "hosts.csv", &nameservers).unwrap();
+        assert_eq!(report.nameservers, ["ns.example.net."]);
+        assert_eq!(
+            report
+                .zones
+                .iter()
+                .map(|zone| zone.zone.name.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+                "1.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+                "2.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+                "3.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa",
+            ]
+        );
+        assert_eq!(
+            report.zones[0].records[0].owner,
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0"
+        );
+        assert!(!report.is_negative());
+    }
+
+    #[test]
+    fn test_cidr_diff_report() {
+        let list =
+            |content: &str| parse_list(&[("list.txt".to_string(), content.to_string())]).unwrap();
+        let old = list("10.0.0.0/25\n10.0.0.128/25\n192.0.2.0/24\n2001:db8::/32\n");
+
+        let report = cidr_diff_report(
+            &old,
+            &list("10.0.0.0/24\n192.0.2.0/24\n2001:db8::/32\n"),
+            "old",
+            "new",
+        );
+        assert!(!report.is_negative());
+        assert_eq!(report.render_text(false), "");
+
+        let new = list("10.0.0.0/24\n192.0.2.0/25\n198.51.100.7\n2001:db8::/31\n");
+        let report = cidr_diff_report(&old, &new, "old", "new");
+        assert_eq!(report.added, ["198.51.100.7/32", "2001:db9::/32"]);
+        assert_eq!(report.removed, ["192.0.2.128/25"]);
+        assert_eq!(
+            report.render_text(false),
+            "- 192.0.2.128/25\n+ 198.51.100.7/32\n+ 2001:db9::/32\n"
+        );
+        assert_eq!(report.csv_records()[0], ["removed", "192.0.2.128/25"]);
+        assert!(report.is_negative());
+    }
 }
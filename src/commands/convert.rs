@@ -0,0 +1,315 @@
+use crate::error::Error;
+use crate::ip_utils::{
+    AddressForms, Endpoint, IpFamily, IpInfo, Notation, address_forms, format_ip_details,
+    get_endpoint_info, parse_address_any, parse_ip_lenient, split_endpoint,
+};
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use serde::Serialize;
+use std::net::IpAddr;
+
+/// An address in every supported notation (`convert`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConvertReport {
+    /// Input as given
+    pub input: String,
+    /// Address with the zone ID and port it was given with
+    #[serde(flatten)]
+    pub endpoint: Endpoint,
+    #[serde(flatten)]
+    pub forms: AddressForms,
+    /// Problems with the input, e.g. a link-local address without a zone ID
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl Report for ConvertReport {
+    fn render_text(&self, _verbose: bool) -> String {
+        let forms = &self.forms;
+        let endpoint = &self.endpoint;
+        let mut text = format!("Converting {}...\n✓ {}\n", self.input, endpoint.address());
+        if let Some(port) = endpoint.port {
+            text.push_str(&format!("  Port: {}\n", port));
+        }
+        text.push_str(&format!("  Integer: {}\n", forms.integer));
+        text.push_str(&format!("  Hex: {}\n", forms.hex));
+        text.push_str(&format!("  Binary: {}\n", forms.binary));
+        if endpoint.ip.is_ipv6() {
+            text.push_str(&format!("  Canonical: {}\n", forms.canonical));
+            text.push_str(&format!("  Expanded: {}\n", forms.expanded));
+        }
+        if let Some(mapped) = &forms.ipv4_mapped {
+            text.push_str(&format!("  IPv4-mapped: {}\n", mapped));
+        }
+        if let Some(compatible) = &forms.ipv4_compatible {
+            text.push_str(&format!("  IPv4-compatible: {}\n", compatible));
+        }
+        if let Some(ipv4) = forms.embedded_ipv4 {
+            text.push_str(&format!("  Embedded IPv4: {}\n", ipv4));
+        }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "input",
+            "ip",
+            "integer",
+            "hex",
+            "binary",
+            "expanded",
+            "canonical",
+            "ipv4_mapped",
+            "ipv4_compatible",
+            "embedded_ipv4",
+        ]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        let forms = &self.forms;
+        vec![
+            self.input.clone(),
+            self.endpoint.ip.to_string(),
+            forms.integer.clone(),
+            forms.hex.clone(),
+            forms.binary.clone(),
+            forms.expanded.clone(),
+            forms.canonical.clone(),
+            csv_optional(&forms.ipv4_mapped),
+            csv_optional(&forms.ipv4_compatible),
+            csv_optional(&forms.embedded_ipv4),
+        ]
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.warnings.clone()
+    }
+}
+
+/// Convert an address given as text, a decimal integer or "0x" hex. `family`
+/// decides how integers are read (see `parse_address_any`). With `lenient`,
+/// IPv4 input is read like `inet_aton` does (e.g. 0x7f.1) first. A port
+/// and an IPv6 zone ID (`[fe80::1%eth0]:8443`) are kept alongside.
+pub fn convert_report(
+    input: &str,
+    family: IpFamily,
+    lenient: bool,
+) -> Result<ConvertReport, Error> {
+    let parts = split_endpoint(input)?;
+    let ip = match parse_ip_lenient(parts.host) {
+        Ok(address) if lenient => address.ip,
+        _ => parse_address_any(parts.host, family)?,
+    };
+    let endpoint = parts.with_ip(ip, input)?;
+    Ok(ConvertReport {
+        input: input.to_string(),
+        forms: address_forms(ip),
+        warnings: endpoint.zone_warning().into_iter().collect(),
+        endpoint,
+    })
+}
+
+pub fn check_convert(
+    input: &str,
+    family: IpFamily,
+    lenient: bool,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = convert_report(input, family, lenient)?;
+    output::print_report(&report, format, verbose)
+}
+
+/// What a possibly obfuscated address really points to (`normalize`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct NormalizeReport {
+    /// Input as given
+    pub input: String,
+    /// Decoded address
+    pub ip: IpAddr,
+    /// Address connections go to (the IPv4 address of an IPv4-mapped input)
+    pub effective: IpAddr,
+    /// Whether the input is already in canonical form
+    pub canonical: bool,
+    /// Non-canonical notations used
+    pub notations: Vec<Notation>,
+    /// Details of the effective address
+    pub info: IpInfo,
+}
+
+impl Report for NormalizeReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Normalizing {}...\n", self.input);
+
+        if verbose {
+            text.push_str(&format_ip_details(&self.info));
+        }
+
+        if self.canonical {
+            text.push_str(&format!(
+                "✓ {} is canonical ({})\n",
+                self.info.endpoint(),
+                self.info
+            ));
+        } else {
+            text.push_str(&format!(
+                "✓ {} points to {} ({})\n",
+                self.input,
+                self.info.endpoint(),
+                self.info
+            ));
+            let notations: Vec<&str> = self
+                .notations
+                .iter()
+                .map(|notation| notation.describe())
+                .collect();
+            text.push_str(&format!("  Notation: {}\n", notations.join(", ")));
+        }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "input",
+            "ip",
+            "effective",
+            "canonical",
+            "notations",
+            "category",
+        ]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.input.clone(),
+            self.ip.to_string(),
+            self.effective.to_string(),
+            self.canonical.to_string(),
+            self.notations
+                .iter()
+                .map(|notation| notation.as_str())
+                .collect::<Vec<_>>()
+                .join(";"),
+            self.info.category.as_str().to_string(),
+        ]
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.info.warnings.clone()
+    }
+}
+
+/// Decode an address with the lenient `inet_aton` rules and show its
+/// canonical form. A port and an IPv6 zone ID are kept alongside.
+pub fn normalize_report(input: &str) -> Result<NormalizeReport, Error> {
+    let parts = split_endpoint(input)?;
+    let address = parse_ip_lenient(parts.host)?;
+    let endpoint = parts.with_ip(address.ip, input)?;
+    let effective = Endpoint {
+        ip: address.effective,
+        ..endpoint
+    };
+    Ok(NormalizeReport {
+        input: input.to_string(),
+        ip: address.ip,
+        effective: address.effective,
+        canonical: address.is_canonical(),
+        info: get_endpoint_info(&effective),
+        notations: address.notations,
+    })
+}
+
+pub fn check_normalize(input: &str, verbose: bool, format: OutputFormat) -> Result<(), Error> {
+    let report = normalize_report(input)?;
+    output::print_report(&report, format, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convert_report() {
+        let report = convert_report("3221225985", IpFamily::Both, false).unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "Converting 3221225985...\n\
+             ✓ 192.0.2.1\n  \
+             Integer: 3221225985\n  \
+             Hex: 0xc0000201\n  \
+             Binary: 11000000.00000000.00000010.00000001\n  \
+             IPv4-mapped: ::ffff:192.0.2.1\n  \
+             IPv4-compatible: ::192.0.2.1\n"
+        );
+
+        let report = convert_report("0x1", IpFamily::V6, false).unwrap();
+        assert_eq!(report.endpoint.ip.to_string(), "::1");
+        assert_eq!(report.csv_record()[7..], ["", "", ""]);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["hex"], "0x00000000000000000000000000000001");
+        assert!(json.get("ipv4_mapped").is_none());
+
+        let err = convert_report("not-an-ip", IpFamily::Both, false).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+
+        // Octal only with --lenient; otherwise a decimal IPv6 integer
+        let report = convert_report("017700000001", IpFamily::Both, true).unwrap();
+        assert_eq!(report.endpoint.ip.to_string(), "127.0.0.1");
+        let report = convert_report("017700000001", IpFamily::Both, false).unwrap();
+        assert!(report.endpoint.ip.is_ipv6());
+        let report = convert_report("0x20010db8000000000000000000000001", IpFamily::Both, true);
+        assert_eq!(report.unwrap().endpoint.ip.to_string(), "2001:db8::1");
+    }
+
+    #[test]
+    fn test_convert_endpoint() {
+        let report = convert_report("[fe80::1%eth0]:8443", IpFamily::Both, false).unwrap();
+        assert!(
+            report
+                .render_text(false)
+                .starts_with("Converting [fe80::1%eth0]:8443...\n✓ fe80::1%eth0\n  Port: 8443\n")
+        );
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(
+            (&json["zone"], &json["port"]),
+            (&"eth0".into(), &8443.into())
+        );
+        assert!(json.get("warnings").is_none());
+
+        let report = convert_report("fe80::1", IpFamily::Both, false).unwrap();
+        assert_eq!(report.warnings().len(), 1);
+        assert!(
+            crate::output::render_text(&report, false)
+                .ends_with("⚠ Warning: fe80::1 is link-local but has no zone ID (e.g. fe80::1%eth0); the interface is ambiguous\n")
+        );
+        assert!(convert_report("192.0.2.1%eth0", IpFamily::Both, false).is_err());
+    }
+
+    #[test]
+    fn test_normalize_report() {
+        let report = normalize_report("0x7f.1").unwrap();
+        assert!(!report.canonical);
+        assert_eq!(
+            report.render_text(false),
+            "Normalizing 0x7f.1...\n\
+             ✓ 0x7f.1 points to 127.0.0.1 (IPv4 Loopback)\n  \
+             Notation: hexadecimal part, fewer than four parts\n"
+        );
+        assert_eq!(report.csv_record()[4], "hex;short_form");
+
+        let report = normalize_report("::ffff:10.0.0.1").unwrap();
+        assert_eq!(report.effective.to_string(), "10.0.0.1");
+        assert_eq!(report.info.category.as_str(), "private");
+
+        let report = normalize_report("8.8.8.8").unwrap();
+        assert_eq!(
+            report.render_text(false),
+            "Normalizing 8.8.8.8...\n✓ 8.8.8.8 is canonical (IPv4 Public)\n"
+        );
+
+        assert!(normalize_report("1.2.3.4.5").is_err());
+
+        let report = normalize_report("0x7f.1:8080").unwrap();
+        assert_eq!(report.info.endpoint().to_string(), "127.0.0.1:8080");
+    }
+}
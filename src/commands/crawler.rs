@@ -1,43 +1,150 @@
 use crate::crawler_sources::{
-    get_all_crawler_sources, load_additional_sources_from_file, print_crawler_sources,
+    AdditionalSources, CrawlerIpSource, format_crawler_sources, load_crawler_sources,
 };
-use crate::ip_utils::{parse_ip_address, print_ip_details};
+use crate::error::Error;
+use crate::ip_utils::{IpInfo, format_ip_details, get_endpoint_info, parse_endpoint};
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use serde::Serialize;
+use std::net::IpAddr;
 
-pub fn check_crawler(ip_address: &str, verbose: bool) -> Result<(), Box<dyn std::error::Error>> {
-    let ip = parse_ip_address(ip_address)?;
-
-    println!("Checking if {} is a crawler IP...", ip);
-
-    print_ip_details(&ip, verbose);
+/// Result of a crawler check
+#[derive(Debug, Clone, Serialize)]
+pub struct CrawlerReport {
+    pub ip: IpAddr,
+    pub info: IpInfo,
+    /// `None` while the ranges of the configured sources cannot be checked
+    pub is_crawler: Option<bool>,
+    /// Name of the crawler source whose ranges contain the address
+    pub matched_source: Option<String>,
+    /// Configured crawler sources
+    pub sources: Vec<CrawlerIpSource>,
+    /// Outcome of reading the additional sources file (verbose text output only)
+    #[serde(skip)]
+    pub additional_sources: AdditionalSources,
+}
 
-    if verbose {
-        println!("Verbose mode enabled for crawler check");
-        println!("\nConfigured crawler IP sources:");
-        let mut sources = get_all_crawler_sources();
+impl Report for CrawlerReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!("Checking if {} is a crawler IP...\n", self.info.endpoint());
 
-        match load_additional_sources_from_file("additional_crawler_sources.json") {
-            Ok(additional_sources) => {
-                println!(
-                    "✓ Loaded {} additional sources from JSON file",
-                    additional_sources.len()
-                );
-                sources.extend(additional_sources);
+        if verbose {
+            text.push_str(&format_ip_details(&self.info));
+            text.push_str("Verbose mode enabled for crawler check\n");
+            text.push_str("\nConfigured crawler IP sources:\n");
+            match &self.additional_sources {
+                AdditionalSources::Loaded(count) => text.push_str(&format!(
+                    "✓ Loaded {} additional sources from JSON file\n",
+                    count
+                )),
+                AdditionalSources::Unavailable(e) => {
+                    text.push_str(&format!("ℹ No additional sources file found: {}\n", e))
+                }
             }
-            Err(e) => {
-                println!("ℹ No additional sources file found: {}", e);
+            text.push_str(&format_crawler_sources(&self.sources, verbose));
+        }
+
+        match (self.is_crawler, &self.matched_source) {
+            (Some(true), Some(source)) => {
+                text.push_str(&format!("✓ {} is a crawler IP ({})\n", self.ip, source))
             }
+            (Some(true), None) => text.push_str(&format!("✓ {} is a crawler IP\n", self.ip)),
+            (Some(false), _) => text.push_str(&format!("✓ {} is NOT a crawler IP\n", self.ip)),
+            (None, _) => text.push_str("✓ Crawler check completed (not implemented yet)\n"),
         }
 
-        print_crawler_sources(&sources, verbose);
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["ip", "category", "is_crawler", "matched_source"]
     }
 
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.ip.to_string(),
+            self.info.category.as_str().to_string(),
+            csv_optional(&self.is_crawler),
+            csv_optional(&self.matched_source),
+        ]
+    }
+
+    fn is_negative(&self) -> bool {
+        self.is_crawler == Some(false)
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        self.info.warnings.clone()
+    }
+}
+
+/// Check an IP address against the configured crawler sources and return the outcome
+pub fn crawler_report(ip_address: &str) -> Result<CrawlerReport, Error> {
+    let endpoint = parse_endpoint(ip_address)?;
+    let ip = endpoint.ip;
+
     // TODO: Implement actual crawler detection logic
     // - Fetch IP ranges from configured sources
     // - Check if the given IP falls within any crawler ranges
     // - Reverse DNS lookup for additional verification
     // - Cache results for performance
 
-    println!("✓ Crawler check completed (not implemented yet)");
+    let (sources, additional_sources) = load_crawler_sources();
+    Ok(CrawlerReport {
+        ip,
+        info: get_endpoint_info(&endpoint),
+        is_crawler: None,
+        matched_source: None,
+        sources,
+        additional_sources,
+    })
+}
+
+pub fn check_crawler(ip_address: &str, verbose: bool, format: OutputFormat) -> Result<(), Error> {
+    let report = crawler_report(ip_address)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "{} is not a crawler IP",
+            report.ip
+        )));
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crawler_report() {
+        let report = crawler_report("66.249.66.1").unwrap();
+        assert_eq!(report.ip.to_string(), "66.249.66.1");
+        assert_eq!(report.is_crawler, None);
+        assert!(!report.sources.is_empty());
+
+        assert!(crawler_report("not-an-ip").is_err());
+    }
+
+    #[test]
+    fn test_crawler_report_text() {
+        let report = crawler_report("66.249.66.1").unwrap();
+        let text = report.render_text(false);
+        assert!(text.starts_with("Checking if 66.249.66.1 is a crawler IP...\n"));
+        assert!(!text.contains("Configured crawler IP sources"));
+
+        let text = report.render_text(true);
+        assert!(text.contains("Type: IPv4 Public\n"));
+        assert!(text.contains("PTR: 1.66.249.66.in-addr.arpa\n"));
+        assert!(text.contains("1. Googlebot IP Ranges\n"));
+
+        let report = CrawlerReport {
+            additional_sources: AdditionalSources::Loaded(2),
+            ..report
+        };
+        assert!(report.render_text(true).contains(
+            "Configured crawler IP sources:\n✓ Loaded 2 additional sources from JSON file\n"
+        ));
+    }
+}
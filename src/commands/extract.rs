@@ -0,0 +1,165 @@
+use crate::batch::read_texts;
+use crate::error::Error;
+use crate::extract::{Token, extract_tokens};
+use crate::ip_utils::IpFamily;
+use crate::output::{self, OutputFormat, Report};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// A token together with the input it was found in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtractMatch {
+    /// File name, or "stdin"
+    pub source: String,
+    #[serde(flatten)]
+    pub token: Token,
+}
+
+/// Every address and CIDR found in the input text (`extract`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExtractReport {
+    pub family: IpFamily,
+    /// Prefix each token in the text output with "source:line:column: "
+    #[serde(skip)]
+    pub positions: bool,
+    /// Tokens in input order
+    pub matches: Vec<ExtractMatch>,
+}
+
+impl Report for ExtractReport {
+    /// One token per line, ready to be piped into another command
+    fn render_text(&self, _verbose: bool) -> String {
+        let mut text = String::new();
+        for found in &self.matches {
+            let token = &found.token;
+            if self.positions {
+                text.push_str(&format!(
+                    "{}:{}:{}: {}\n",
+                    found.source, token.line, token.column, token.token
+                ));
+            } else {
+                text.push_str(&format!("{}\n", token.token));
+            }
+        }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["source", "line", "column", "token", "kind", "version"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.matches
+            .iter()
+            .map(|found| {
+                let token = &found.token;
+                vec![
+                    found.source.clone(),
+                    token.line.to_string(),
+                    token.column.to_string(),
+                    token.token.clone(),
+                    token.kind.as_str().to_string(),
+                    token.version.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// Finding nothing is a negative result, as with grep
+    fn is_negative(&self) -> bool {
+        self.matches.is_empty()
+    }
+}
+
+/// Find the addresses and networks of `family` in each `(source, content)`
+/// text
+pub fn extract_report(
+    texts: &[(String, String)],
+    family: IpFamily,
+    positions: bool,
+) -> ExtractReport {
+    let matches = texts
+        .iter()
+        .flat_map(|(source, content)| {
+            extract_tokens(content)
+                .into_iter()
+                .filter(|token| family.includes(token.version == 4))
+                .map(|token| ExtractMatch {
+                    source: source.clone(),
+                    token,
+                })
+        })
+        .collect();
+
+    ExtractReport {
+        family,
+        positions,
+        matches,
+    }
+}
+
+pub fn check_extract(
+    files: &[PathBuf],
+    family: IpFamily,
+    positions: bool,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = extract_report(&read_texts(files)?, family, positions);
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(
+            "No IP addresses or networks found".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_report() {
+        let texts = [(
+            "mail.log".to_string(),
+            "Received: from mx (mx.example [192.0.2.25])\n\tby 2001:db8::25 for 10.0.0.0/8;\n"
+                .to_string(),
+        )];
+        let report = extract_report(&texts, IpFamily::Both, false);
+        assert_eq!(
+            report.render_text(false),
+            "192.0.2.25\n2001:db8::25\n10.0.0.0/8\n"
+        );
+
+        let report = extract_report(&texts, IpFamily::V4, true);
+        assert_eq!(
+            report.render_text(false),
+            "mail.log:1:32: 192.0.2.25\nmail.log:2:22: 10.0.0.0/8\n"
+        );
+        assert_eq!(
+            report.csv_records()[1],
+            ["mail.log", "2", "22", "10.0.0.0/8", "network", "4"]
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["matches"][0]["kind"], "address");
+        assert_eq!(json["matches"][0]["column"], 32);
+
+        assert_eq!(extract_report(&texts, IpFamily::V6, false).matches.len(), 1);
+        assert!(
+            extract_report(
+                &[("x".to_string(), "v1.2.3.4".to_string())],
+                IpFamily::Both,
+                false
+            )
+            .is_negative()
+        );
+    }
+}
@@ -1,3 +1,11 @@
+pub mod asn;
+pub mod calc;
 pub mod cc;
 pub mod cidr;
+pub mod convert;
 pub mod crawler;
+pub mod extract;
+pub mod prefix_list;
+pub mod rpki;
+pub mod sort;
+pub mod ssrf;
@@ -0,0 +1,272 @@
+use crate::asn::parse_asn;
+use crate::config::{CONFIG_FILE, load_config};
+use crate::error::Error;
+use crate::ip_utils::IpFamily;
+use crate::output::{self, OutputFormat, Report};
+use crate::prefix_list::{
+    PrefixListEntry, PrefixListFormat, aggregate_prefixes, render_comment, render_prefix_list,
+};
+use crate::rpsl::IrrDatabase;
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// Prefix list generated from IRR data (`prefix-list`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrefixListReport {
+    /// AS number or as-set as given
+    pub query: String,
+    /// ASNs the query expands to
+    pub asns: Vec<u32>,
+    /// Referenced as-sets missing from the loaded data
+    pub missing_sets: Vec<String>,
+    pub family: IpFamily,
+    /// Longest IPv4 and IPv6 prefixes accepted, when limited
+    pub max_length: Option<u8>,
+    pub max_length_v6: Option<u8>,
+    /// Text rendering of the list
+    #[serde(skip)]
+    pub format: PrefixListFormat,
+    /// Prefix-list or set name
+    #[serde(skip)]
+    pub name: String,
+    /// Aggregated entries in address order, IPv4 first
+    pub entries: Vec<PrefixListEntry>,
+}
+
+impl Report for PrefixListReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = String::new();
+        if verbose {
+            text.push_str(&render_comment(
+                self.format,
+                &format!(
+                    "{}: {} ASNs, {} entries",
+                    self.query,
+                    self.asns.len(),
+                    self.entries.len()
+                ),
+            ));
+            for missing in &self.missing_sets {
+                text.push_str(&render_comment(
+                    self.format,
+                    &format!("as-set {} not found", missing),
+                ));
+            }
+        }
+        text.push_str(&render_prefix_list(&self.entries, self.format, &self.name));
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["prefix", "min_length", "max_length"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(|entry| {
+                vec![
+                    entry.prefix.clone(),
+                    entry.min_length.to_string(),
+                    entry.max_length.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    fn is_negative(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Load the RPSL dumps given with `--rpsl`, or else the files set as
+/// `irr.rpsl` in the configuration file
+pub fn open_irr(paths: &[PathBuf]) -> Result<IrrDatabase, Error> {
+    if !paths.is_empty() {
+        return IrrDatabase::open(paths);
+    }
+
+    let paths = load_config()?.irr.rpsl;
+    if paths.is_empty() {
+        return Err(Error::config(
+            CONFIG_FILE,
+            "no RPSL dumps configured (pass --rpsl or set irr.rpsl)",
+        ));
+    }
+    IrrDatabase::open(&paths)
+}
+
+/// Default list name for a query: "AS-EXAMPLE" becomes "AS_EXAMPLE", which
+/// every supported format accepts as an identifier
+pub fn default_list_name(query: &str) -> String {
+    query
+        .trim()
+        .to_ascii_uppercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect()
+}
+
+/// Options of `prefix-list` besides the query
+#[derive(Debug, Clone, Default)]
+pub struct PrefixListOptions {
+    pub family: IpFamily,
+    pub format: PrefixListFormat,
+    /// List name; defaults to the query
+    pub name: Option<String>,
+    /// Accept IPv4 more specifics up to this length and drop longer routes
+    pub max_length: Option<u8>,
+    /// The same for IPv6
+    pub max_length_v6: Option<u8>,
+}
+
+/// Build the aggregated prefix list of an AS ("AS64496") or as-set
+pub fn prefix_list_report(
+    query: &str,
+    options: &PrefixListOptions,
+    irr: &IrrDatabase,
+) -> Result<PrefixListReport, Error> {
+    if parse_asn(query).is_none() && !query.to_ascii_uppercase().starts_with("AS") {
+        return Err(Error::invalid_input(
+            query,
+            "Expected an AS number or as-set",
+        ));
+    }
+    for (max_length, bits) in [(options.max_length, 32), (options.max_length_v6, 128)] {
+        if let Some(max_length) = max_length
+            && max_length > bits
+        {
+            return Err(Error::invalid_input(
+                max_length.to_string(),
+                "Invalid maximum prefix length",
+            ));
+        }
+    }
+
+    let expansion = irr.expand(query);
+    let prefixes = irr.prefixes(&expansion.asns);
+    let mut entries = Vec::new();
+    for (ipv4, max_length) in [(true, options.max_length), (false, options.max_length_v6)] {
+        if !options.family.includes(ipv4) {
+            continue;
+        }
+        let family: Vec<(u128, u8)> = prefixes
+            .iter()
+            .filter(|prefix| prefix.0 == ipv4)
+            .map(|&(_, start, len)| (start, len))
+            .collect();
+        entries.extend(aggregate_prefixes(&family, ipv4, max_length));
+    }
+
+    Ok(PrefixListReport {
+        query: query.to_string(),
+        asns: expansion.asns.into_iter().collect(),
+        missing_sets: expansion.missing.into_iter().collect(),
+        family: options.family,
+        max_length: options.max_length,
+        max_length_v6: options.max_length_v6,
+        format: options.format,
+        name: options
+            .name
+            .clone()
+            .unwrap_or_else(|| default_list_name(query)),
+        entries,
+    })
+}
+
+pub fn check_prefix_list(
+    query: &str,
+    options: &PrefixListOptions,
+    irr: &IrrDatabase,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = prefix_list_report(query, options, irr)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!("No prefixes found for {}", query)));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn irr() -> IrrDatabase {
+        let mut irr = IrrDatabase::new();
+        irr.load(
+            "\
+route: 192.0.2.0/24
+origin: AS64496
+
+route: 192.0.3.0/24
+origin: AS64497
+
+route: 192.0.3.0/25
+origin: AS64497
+
+route6: 2001:db8::/32
+origin: AS64497
+
+as-set: AS-EXAMPLE
+members: AS64496, AS-CUSTOMERS
+
+as-set: AS-CUSTOMERS
+members: AS64497, AS-GONE
+",
+            "test.db",
+        )
+        .unwrap();
+        irr
+    }
+
+    #[test]
+    fn test_prefix_list_report() {
+        let irr = irr();
+        let options = PrefixListOptions {
+            family: IpFamily::V4,
+            format: PrefixListFormat::Cisco,
+            ..PrefixListOptions::default()
+        };
+        let report = prefix_list_report("AS-EXAMPLE", &options, &irr).unwrap();
+        assert_eq!(report.asns, [64496, 64497]);
+        assert_eq!(report.missing_sets, ["AS-GONE"]);
+        assert_eq!(
+            report.render_text(false),
+            "no ip prefix-list AS_EXAMPLE\n\
+             ip prefix-list AS_EXAMPLE permit 192.0.2.0/23 ge 24 le 24\n\
+             ip prefix-list AS_EXAMPLE permit 192.0.3.0/25\n"
+        );
+        assert!(
+            report
+                .render_text(true)
+                .starts_with("! AS-EXAMPLE: 2 ASNs, 2 entries\n! as-set AS-GONE not found\n")
+        );
+
+        let options = PrefixListOptions {
+            max_length: Some(24),
+            name: Some("customers".to_string()),
+            ..PrefixListOptions::default()
+        };
+        let report = prefix_list_report("AS64497", &options, &irr).unwrap();
+        assert_eq!(report.render_text(false), "192.0.3.0/24\n2001:db8::/32\n");
+        assert_eq!(report.csv_records()[0], ["192.0.3.0/24", "24", "24"]);
+    }
+
+    #[test]
+    fn test_unknown_query() {
+        let irr = irr();
+        let report = prefix_list_report("AS-NOWHERE", &PrefixListOptions::default(), &irr).unwrap();
+        assert!(report.is_negative());
+
+        let err = prefix_list_report("example", &PrefixListOptions::default(), &irr).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+    }
+}
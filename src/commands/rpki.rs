@@ -0,0 +1,221 @@
+use crate::asn::parse_asn;
+use crate::config::{CONFIG_FILE, load_config};
+use crate::error::Error;
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use crate::rpki::{InvalidReason, RovState, Vrp, VrpTable, describe_vrp, parse_route};
+use serde::Serialize;
+use std::path::Path;
+
+/// Result of route origin validation for one announcement
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RpkiReport {
+    /// Prefix as given
+    pub prefix: String,
+    /// Prefix with host bits cleared
+    pub network: String,
+    pub origin: u32,
+    pub state: RovState,
+    /// Why the route is invalid; `None` unless `state` is `invalid`
+    pub reason: Option<InvalidReason>,
+    /// VRPs that authorize the route
+    pub matched: Vec<Vrp>,
+    /// Every VRP covering the prefix, least specific first
+    pub covering: Vec<Vrp>,
+    /// File the VRPs were loaded from
+    pub source: String,
+}
+
+impl Report for RpkiReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let mut text = format!(
+            "Validating {} originated by AS{}...\n",
+            self.prefix, self.origin
+        );
+
+        if verbose {
+            text.push_str(&format!("Network: {}\n", self.network));
+        }
+
+        let route = format!("{} from AS{}", self.network, self.origin);
+        match (self.state, self.reason) {
+            (RovState::Valid, _) => text.push_str(&format!("✓ {} is VALID\n", route)),
+            (RovState::Invalid, Some(InvalidReason::MaxLength)) => text.push_str(&format!(
+                "✓ {} is INVALID: longer than the maxLength authorized for AS{}\n",
+                route, self.origin
+            )),
+            (RovState::Invalid, _) => text.push_str(&format!(
+                "✓ {} is INVALID: AS{} is not an authorized origin\n",
+                route, self.origin
+            )),
+            (RovState::NotFound, _) => {
+                text.push_str(&format!("✓ {} is NOT FOUND: no VRP covers it\n", route))
+            }
+        }
+
+        if verbose {
+            for vrp in &self.covering {
+                let marker = if self.matched.contains(vrp) {
+                    " (matched)"
+                } else {
+                    ""
+                };
+                text.push_str(&format!("  VRP: {}{}\n", describe_vrp(vrp), marker));
+            }
+            text.push_str(&format!("  Source: {}\n", self.source));
+        }
+
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["prefix", "origin", "state", "reason", "covering_vrps"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        vec![
+            self.prefix.clone(),
+            self.origin.to_string(),
+            self.state.as_str().to_string(),
+            csv_optional(&self.reason.map(|reason| reason.as_str())),
+            self.covering.len().to_string(),
+        ]
+    }
+
+    /// Only valid routes are a positive result
+    fn is_negative(&self) -> bool {
+        self.state != RovState::Valid
+    }
+}
+
+/// Load the VRPs given with `--vrps`, or else the file set as `rpki.vrps`
+/// in the configuration file
+pub fn open_vrps(path: Option<&Path>) -> Result<VrpTable, Error> {
+    if let Some(path) = path {
+        return VrpTable::open(path);
+    }
+
+    match load_config()?.rpki.vrps {
+        Some(path) => VrpTable::open(path),
+        None => Err(Error::config(
+            CONFIG_FILE,
+            "no VRP file configured (pass --vrps or set rpki.vrps)",
+        )),
+    }
+}
+
+/// Validate the announcement of `prefix` by `origin` ("64496" or "AS64496")
+pub fn rpki_report(prefix: &str, origin: &str, vrps: &VrpTable) -> Result<RpkiReport, Error> {
+    let (ip, prefix_len) = parse_route(prefix)?;
+    let asn = parse_asn(origin).ok_or_else(|| Error::invalid_input(origin, "Invalid AS number"))?;
+    let validation = vrps.validate(ip, prefix_len, asn);
+
+    Ok(RpkiReport {
+        prefix: prefix.to_string(),
+        network: format!("{}/{}", ip, prefix_len),
+        origin: asn,
+        state: validation.state,
+        reason: validation.reason,
+        matched: validation.matched,
+        covering: validation.covering,
+        source: vrps.source().to_string(),
+    })
+}
+
+/// Validate one batch input line holding a prefix and an origin AS
+/// (separated by whitespace or a comma)
+pub fn rpki_line_report(line: &str, vrps: &VrpTable) -> Result<RpkiReport, Error> {
+    let fields: Vec<&str> = line
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|part| !part.is_empty())
+        .collect();
+
+    match fields.as_slice() {
+        [prefix, origin] => rpki_report(prefix, origin, vrps),
+        _ => Err(Error::invalid_input(
+            line,
+            "Expected a prefix and an origin AS",
+        )),
+    }
+}
+
+pub fn check_rpki(
+    prefix: &str,
+    origin: &str,
+    vrps: &VrpTable,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = rpki_report(prefix, origin, vrps)?;
+    output::print_report(&report, format, verbose)?;
+
+    if report.is_negative() {
+        return Err(Error::NotFound(format!(
+            "{} from AS{} is {}",
+            report.network,
+            report.origin,
+            report.state.as_str().replace('_', " ")
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vrps() -> VrpTable {
+        VrpTable::parse(
+            r#"{"roas": [
+                {"asn": 64496, "prefix": "192.0.2.0/24", "maxLength": 24, "ta": "apnic"},
+                {"asn": 64497, "prefix": "192.0.0.0/16", "maxLength": 16, "ta": "apnic"}
+            ]}"#,
+            "vrps.json",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rpki_report() {
+        let vrps = vrps();
+        let report = rpki_report("192.0.2.0/24", "AS64496", &vrps).unwrap();
+        assert_eq!(report.state, RovState::Valid);
+        assert_eq!(
+            report.render_text(false),
+            "Validating 192.0.2.0/24 originated by AS64496...\n✓ 192.0.2.0/24 from AS64496 is VALID\n"
+        );
+        assert_eq!(
+            report.render_text(true),
+            "Validating 192.0.2.0/24 originated by AS64496...\n\
+             Network: 192.0.2.0/24\n\
+             ✓ 192.0.2.0/24 from AS64496 is VALID\n  \
+             VRP: 192.0.0.0/16-16 AS64497 (apnic)\n  \
+             VRP: 192.0.2.0/24-24 AS64496 (apnic) (matched)\n  \
+             Source: vrps.json\n"
+        );
+
+        let report = rpki_report("192.0.2.1/25", "64496", &vrps).unwrap();
+        assert_eq!(report.network, "192.0.2.0/25");
+        assert_eq!(report.reason, Some(InvalidReason::MaxLength));
+        assert!(report.is_negative());
+        assert_eq!(
+            report.csv_record(),
+            ["192.0.2.1/25", "64496", "invalid", "max_length", "2"]
+        );
+
+        let report = rpki_report("10.0.0.0/8", "64496", &vrps).unwrap();
+        assert_eq!(report.state, RovState::NotFound);
+    }
+
+    #[test]
+    fn test_rpki_line_report() {
+        let vrps = vrps();
+        let report = rpki_line_report("192.0.2.0/24, AS64500", &vrps).unwrap();
+        assert_eq!(report.reason, Some(InvalidReason::Origin));
+
+        let err = rpki_line_report("192.0.2.0/24", &vrps).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+        let err = rpki_line_report("192.0.2.0/24 ASX", &vrps).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+    }
+}
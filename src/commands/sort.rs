@@ -0,0 +1,162 @@
+use crate::batch::{parse_list, read_texts};
+use crate::error::Error;
+use crate::ip_utils::{ListEntry, remove_covered};
+use crate::output::{self, OutputFormat, Report};
+use serde::Serialize;
+use std::path::PathBuf;
+
+/// How `sort` post-processes the list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SortOptions {
+    /// Drop repeated entries
+    pub unique: bool,
+    /// Clear host bits of CIDR entries
+    pub canonicalize: bool,
+    /// Drop entries covered by another entry
+    pub remove_covered: bool,
+}
+
+/// A sorted address list (`sort`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SortReport {
+    /// Entries read
+    pub total: usize,
+    /// Entries dropped as repeated
+    pub duplicates: usize,
+    /// Entries dropped as covered by another entry
+    pub covered: usize,
+    /// Remaining entries, IPv4 before IPv6, in numeric order
+    pub entries: Vec<String>,
+}
+
+impl Report for SortReport {
+    /// One entry per line, like sort(1)
+    fn render_text(&self, _verbose: bool) -> String {
+        self.entries
+            .iter()
+            .map(|entry| format!("{}\n", entry))
+            .collect()
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &["entry"]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.entries.first().cloned().into_iter().collect()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        self.entries
+            .iter()
+            .map(|entry| vec![entry.clone()])
+            .collect()
+    }
+}
+
+/// Sort a list numerically, IPv4 first, applying `options`
+pub fn sort_report(mut entries: Vec<ListEntry>, options: SortOptions) -> SortReport {
+    let total = entries.len();
+    if options.canonicalize {
+        entries = entries.iter().map(ListEntry::canonical).collect();
+    }
+    entries.sort();
+
+    let mut duplicates = 0;
+    if options.unique {
+        entries.dedup();
+        duplicates = total - entries.len();
+    }
+    let mut covered = 0;
+    if options.remove_covered {
+        let before = entries.len();
+        entries = remove_covered(entries);
+        covered = before - entries.len();
+    }
+
+    SortReport {
+        total,
+        duplicates,
+        covered,
+        entries: entries.iter().map(ListEntry::to_string).collect(),
+    }
+}
+
+pub fn check_sort(
+    files: &[PathBuf],
+    options: SortOptions,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = sort_report(parse_list(&read_texts(files)?)?, options);
+    output::print_report(&report, format, verbose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(content: &str) -> Vec<ListEntry> {
+        parse_list(&[("acl.txt".to_string(), content.to_string())]).unwrap()
+    }
+
+    #[test]
+    fn test_sort_report() {
+        let content = "# office\n2001:DB8::1\n10.0.0.10\n192.0.2.77/24\n10.0.0.9\n\n2001:db8::1\n192.0.2.0/24\n192.0.2.5\n";
+
+        let report = sort_report(list(content), SortOptions::default());
+        assert_eq!(
+            report.render_text(false),
+            "10.0.0.9\n10.0.0.10\n192.0.2.0/24\n192.0.2.5\n192.0.2.77/24\n2001:db8::1\n2001:db8::1\n"
+        );
+
+        let options = SortOptions {
+            unique: true,
+            canonicalize: true,
+            remove_covered: false,
+        };
+        let report = sort_report(list(content), options);
+        assert_eq!(
+            report.entries,
+            [
+                "10.0.0.9",
+                "10.0.0.10",
+                "192.0.2.0/24",
+                "192.0.2.5",
+                "2001:db8::1"
+            ]
+        );
+        assert_eq!((report.total, report.duplicates), (7, 2));
+
+        let options = SortOptions {
+            remove_covered: true,
+            ..options
+        };
+        let report = sort_report(list(content), options);
+        assert_eq!(
+            report.entries,
+            ["10.0.0.9", "10.0.0.10", "192.0.2.0/24", "2001:db8::1"]
+        );
+        assert_eq!(report.covered, 1);
+        assert_eq!(report.csv_records().len(), 4);
+
+        let err = parse_list(&[("acl.txt".to_string(), "10.0.0.1\n1.2.3.4.5\n".to_string())])
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "acl.txt line 2: invalid IP address or CIDR: 1.2.3.4.5"
+        );
+        assert_eq!(err.exit_code(), crate::error::EXIT_BAD_INPUT);
+
+        for endpoint in ["203.0.113.9:51234", "[2001:db8::1]:443", "fe80::1%eth0"] {
+            let err = parse_list(&[("acl.txt".to_string(), endpoint.to_string())]).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "acl.txt line 1: ports and zone IDs are not supported in address lists: {}",
+                    endpoint
+                )
+            );
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::error::Error;
+use crate::output::{self, OutputFormat, Report, csv_optional};
+use crate::ssrf::{Resolver, TargetAddress, UrlCheck, check_url};
+use serde::Serialize;
+
+/// Whether a user-supplied URL is safe to fetch (`ssrf-check`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SsrfReport {
+    /// URL as given
+    pub url: String,
+    #[serde(flatten)]
+    pub check: UrlCheck,
+}
+
+/// One address line, showing where a mapped or NAT64 address really leads
+fn describe_address(address: &TargetAddress) -> String {
+    let version = if address.effective.is_ipv4() { 4 } else { 6 };
+    let kind = if address.internal {
+        "internal"
+    } else {
+        "external"
+    };
+    if address.effective == address.ip {
+        format!(
+            "{} (IPv{} {}, {})",
+            address.ip, version, address.category, kind
+        )
+    } else {
+        format!(
+            "{} -> {} (IPv{} {}, {})",
+            address.ip, address.effective, version, address.category, kind
+        )
+    }
+}
+
+impl Report for SsrfReport {
+    fn render_text(&self, verbose: bool) -> String {
+        let check = &self.check;
+        let host = match &check.target.zone {
+            Some(zone) => format!("{}%{}", check.target.host, zone),
+            None => check.target.host.clone(),
+        };
+        let mut text = format!("Checking {} before fetching...\n", self.url);
+
+        if verbose {
+            text.push_str(&format!(
+                "Scheme: {}\n",
+                check.target.scheme.as_deref().unwrap_or("(none)")
+            ));
+            if let Some(port) = check.target.port {
+                text.push_str(&format!("Port: {}\n", port));
+            }
+        }
+
+        match check.denied_by() {
+            Some(address) => text.push_str(&format!(
+                "✓ {} is DENIED: {} is internal\n",
+                host, address.effective
+            )),
+            None => text.push_str(&format!(
+                "✓ {} is ALLOWED: every address is external\n",
+                host
+            )),
+        }
+        if check.literal {
+            text.push_str("  Host is an IP literal\n");
+        }
+        if !check.notations.is_empty() {
+            let notations: Vec<&str> = check
+                .notations
+                .iter()
+                .map(|notation| notation.describe())
+                .collect();
+            text.push_str(&format!("  Notation: {}\n", notations.join(", ")));
+        }
+        for address in &check.addresses {
+            text.push_str(&format!("  Address: {}\n", describe_address(address)));
+        }
+        text
+    }
+
+    fn csv_header() -> &'static [&'static str] {
+        &[
+            "url",
+            "host",
+            "port",
+            "allowed",
+            "ip",
+            "effective",
+            "category",
+            "internal",
+        ]
+    }
+
+    fn csv_record(&self) -> Vec<String> {
+        self.csv_records().into_iter().next().unwrap_or_default()
+    }
+
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        let check = &self.check;
+        check
+            .addresses
+            .iter()
+            .map(|address| {
+                vec![
+                    self.url.clone(),
+                    check.target.host.clone(),
+                    csv_optional(&check.target.port),
+                    check.allowed.to_string(),
+                    address.ip.to_string(),
+                    address.effective.to_string(),
+                    address.category.as_str().to_string(),
+                    address.internal.to_string(),
+                ]
+            })
+            .collect()
+    }
+
+    /// A denied URL is a negative result
+    fn is_negative(&self) -> bool {
+        !self.check.allowed
+    }
+}
+
+/// Check the host of `url` (a URL or bare host name) with `resolver`
+pub fn ssrf_report(url: &str, resolver: &dyn Resolver) -> Result<SsrfReport, Error> {
+    Ok(SsrfReport {
+        url: url.to_string(),
+        check: check_url(url, resolver)?,
+    })
+}
+
+pub fn check_ssrf(
+    url: &str,
+    resolver: &dyn Resolver,
+    verbose: bool,
+    format: OutputFormat,
+) -> Result<(), Error> {
+    let report = ssrf_report(url, resolver)?;
+    output::print_report(&report, format, verbose)?;
+
+    if let Some(address) = report.check.denied_by() {
+        return Err(Error::NotFound(format!(
+            "{} is denied: {} is {}",
+            report.check.target.host,
+            address.effective,
+            address.category.label().to_lowercase()
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::IpAddr;
+
+    struct StubResolver;
+
+    impl Resolver for StubResolver {
+        fn resolve(&self, _host: &str) -> Result<Vec<IpAddr>, Error> {
+            Ok(vec!["192.168.1.10".parse().unwrap()])
+        }
+    }
+
+    #[test]
+    fn test_ssrf_report() {
+        let report = ssrf_report("http://017700000001:8080/", &StubResolver).unwrap();
+        assert!(report.is_negative());
+        assert_eq!(
+            report.render_text(false),
+            "Checking http://017700000001:8080/ before fetching...\n\
+             ✓ 017700000001 is DENIED: 127.0.0.1 is internal\n  \
+             Host is an IP literal\n  \
+             Notation: octal part, single 32-bit integer\n  \
+             Address: 127.0.0.1 (IPv4 Loopback, internal)\n"
+        );
+
+        let report = ssrf_report("https://intranet.example/", &StubResolver).unwrap();
+        assert_eq!(
+            report.csv_records(),
+            [[
+                "https://intranet.example/",
+                "intranet.example",
+                "443",
+                "false",
+                "192.168.1.10",
+                "192.168.1.10",
+                "private",
+                "true"
+            ]]
+        );
+
+        let report = ssrf_report("https://1.1.1.1/", &StubResolver).unwrap();
+        assert!(!report.is_negative());
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["host"], "1.1.1.1");
+        assert_eq!(json["addresses"][0]["category"], "public");
+    }
+}
@@ -0,0 +1,121 @@
+use crate::error::Error;
+use crate::geo::ProviderKind;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Default configuration file, looked up in the working directory
+pub const CONFIG_FILE: &str = "ipchecker.json";
+
+/// Environment variable that overrides the configuration file location
+pub const CONFIG_ENV: &str = "IPCHECKER_CONFIG";
+
+/// Tool configuration (`ipchecker.json`)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub geo: GeoConfig,
+    pub asn: AsnConfig,
+    pub rpki: RpkiConfig,
+    pub irr: IrrConfig,
+}
+
+/// Geolocation data sources
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GeoConfig {
+    /// MaxMind-format database (GeoLite2-Country or GeoLite2-City)
+    pub mmdb: Option<PathBuf>,
+    /// Range table built with `cc build-db`
+    pub table: Option<PathBuf>,
+    /// IP2Location LITE or DB-IP style CSV exports, loaded at startup
+    pub csv: Vec<PathBuf>,
+    /// RFC 8805 geofeeds
+    pub geofeed: Vec<PathBuf>,
+    /// Order in which the configured sources are asked; defaults to geofeed,
+    /// mmdb, rir, csv
+    pub providers: Vec<ProviderKind>,
+}
+
+/// IP-to-ASN data sources
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AsnConfig {
+    /// MaxMind-format ASN database (GeoLite2-ASN)
+    pub mmdb: Option<PathBuf>,
+    /// iptoasn.com TSV file (ip2asn-combined.tsv or similar)
+    pub tsv: Option<PathBuf>,
+    /// Uncompressed MRT TABLE_DUMP_V2 RIB dumps (RouteViews, RIPE RIS)
+    pub mrt: Vec<PathBuf>,
+}
+
+/// RPKI data
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RpkiConfig {
+    /// VRP export of rpki-client or Routinator (JSON)
+    pub vrps: Option<PathBuf>,
+}
+
+/// Routing registry data
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IrrConfig {
+    /// Uncompressed RPSL dumps holding route, route6 and as-set objects
+    pub rpsl: Vec<PathBuf>,
+}
+
+/// Load the configuration from a JSON file
+pub fn load_config_from_file<P: AsRef<Path>>(path: P) -> Result<Config, Error> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| Error::config(path, e))?;
+    serde_json::from_str(&content).map_err(|e| Error::config(path, e))
+}
+
+/// Load the configuration from `$IPCHECKER_CONFIG`, or from `ipchecker.json`
+/// when it exists. Without either, the default (empty) configuration is used.
+pub fn load_config() -> Result<Config, Error> {
+    if let Some(path) = std::env::var_os(CONFIG_ENV) {
+        return load_config_from_file(path);
+    }
+
+    if Path::new(CONFIG_FILE).exists() {
+        load_config_from_file(CONFIG_FILE)
+    } else {
+        Ok(Config::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config: Config =
+            serde_json::from_str(r#"{"geo": {"mmdb": "/var/lib/GeoLite2-City.mmdb"}}"#).unwrap();
+        assert_eq!(
+            config.geo.mmdb,
+            Some(PathBuf::from("/var/lib/GeoLite2-City.mmdb"))
+        );
+
+        let config: Config =
+            serde_json::from_str(r#"{"geo": {"providers": ["rir", "mmdb"]}}"#).unwrap();
+        assert_eq!(
+            config.geo.providers,
+            vec![ProviderKind::Rir, ProviderKind::Mmdb]
+        );
+
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config, Config::default());
+    }
+
+    #[test]
+    fn test_unknown_fields_are_config_errors() {
+        let path = std::env::temp_dir().join("ipchecker-test-bad-config.json");
+        fs::write(&path, r#"{"geo": {"mmbd": "typo.mmdb"}}"#).unwrap();
+        let err = load_config_from_file(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        assert!(matches!(err, Error::Config { .. }));
+    }
+}
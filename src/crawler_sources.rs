@@ -1,3 +1,4 @@
+use crate::error::Error;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::Path;
@@ -74,16 +75,17 @@ const DEFAULT_ADDITIONAL_SOURCES: &[StaticCrawlerIpSource] = &[
 /// Load additional crawler sources from JSON file
 pub fn load_additional_sources_from_file<P: AsRef<Path>>(
     path: P,
-) -> Result<Vec<CrawlerIpSource>, Box<dyn std::error::Error>> {
-    let content = fs::read_to_string(path)?;
-    let sources: Vec<CrawlerIpSource> = serde_json::from_str(&content)?;
+) -> Result<Vec<CrawlerIpSource>, Error> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path).map_err(|e| Error::config(path, e))?;
+    let sources: Vec<CrawlerIpSource> =
+        serde_json::from_str(&content).map_err(|e| Error::config(path, e))?;
     Ok(sources)
 }
 
 /// Generate a sample JSON file for additional crawler sources
-pub fn generate_sample_config_file<P: AsRef<Path>>(
-    path: P,
-) -> Result<(), Box<dyn std::error::Error>> {
+pub fn generate_sample_config_file<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    let path = path.as_ref();
     let sample_sources = vec![
         CrawlerIpSource {
             name: "Example Bot".to_string(),
@@ -99,13 +101,28 @@ pub fn generate_sample_config_file<P: AsRef<Path>>(
         },
     ];
 
-    let json = serde_json::to_string_pretty(&sample_sources)?;
-    fs::write(path, json)?;
+    let json = serde_json::to_string_pretty(&sample_sources)
+        .map_err(|e| Error::Internal(e.to_string()))?;
+    fs::write(path, json).map_err(|e| Error::config(path, e))?;
     Ok(())
 }
 
-/// Get all configured crawler IP sources (built-in + additional from file)
-pub fn get_all_crawler_sources() -> Vec<CrawlerIpSource> {
+/// File the additional crawler sources are read from
+pub const ADDITIONAL_SOURCES_FILE: &str = "additional_crawler_sources.json";
+
+/// Outcome of reading `ADDITIONAL_SOURCES_FILE`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AdditionalSources {
+    /// Number of sources read from the file
+    Loaded(usize),
+    /// Why the file could not be read; the default additional sources are
+    /// used instead
+    Unavailable(String),
+}
+
+/// Get all configured crawler IP sources (built-in + additional from file),
+/// with the outcome of reading the additional sources file
+pub fn load_crawler_sources() -> (Vec<CrawlerIpSource>, AdditionalSources) {
     let mut sources = Vec::new();
 
     // Add built-in sources
@@ -114,20 +131,27 @@ pub fn get_all_crawler_sources() -> Vec<CrawlerIpSource> {
     }
 
     // Try to load additional sources from file
-    const ADDITIONAL_SOURCES_FILE: &str = "additional_crawler_sources.json";
-    match load_additional_sources_from_file(ADDITIONAL_SOURCES_FILE) {
+    let additional = match load_additional_sources_from_file(ADDITIONAL_SOURCES_FILE) {
         Ok(additional_sources) => {
+            let count = additional_sources.len();
             sources.extend(additional_sources);
+            AdditionalSources::Loaded(count)
         }
-        Err(_) => {
+        Err(e) => {
             // If file doesn't exist or has errors, use default additional sources
             for static_source in DEFAULT_ADDITIONAL_SOURCES {
                 sources.push(CrawlerIpSource::from(static_source));
             }
+            AdditionalSources::Unavailable(e.to_string())
         }
-    }
+    };
+
+    (sources, additional)
+}
 
-    sources
+/// Get all configured crawler IP sources (built-in + additional from file)
+pub fn get_all_crawler_sources() -> Vec<CrawlerIpSource> {
+    load_crawler_sources().0
 }
 
 /// Get crawler sources by name (case-insensitive partial match)
@@ -139,17 +163,23 @@ pub fn get_crawler_sources_by_name(name_filter: &str) -> Vec<CrawlerIpSource> {
         .collect()
 }
 
-/// Print crawler source information
-pub fn print_crawler_sources(sources: &[CrawlerIpSource], verbose: bool) {
+/// Format crawler source information as a numbered list
+pub fn format_crawler_sources(sources: &[CrawlerIpSource], verbose: bool) -> String {
+    let mut text = String::new();
     for (index, source) in sources.iter().enumerate() {
-        println!("{}. {}", index + 1, source.name);
+        text.push_str(&format!("{}. {}\n", index + 1, source.name));
         if verbose {
-            println!("   URL: {}", source.url);
-            println!("   Format: {}", source.format);
-            println!("   Description: {}", source.description);
-            println!();
+            text.push_str(&format!("   URL: {}\n", source.url));
+            text.push_str(&format!("   Format: {}\n", source.format));
+            text.push_str(&format!("   Description: {}\n\n", source.description));
         }
     }
+    text
+}
+
+/// Print crawler source information
+pub fn print_crawler_sources(sources: &[CrawlerIpSource], verbose: bool) {
+    print!("{}", format_crawler_sources(sources, verbose));
 }
 
 #[cfg(test)]
@@ -181,6 +211,13 @@ mod tests {
         assert!(no_sources.is_empty());
     }
 
+    #[test]
+    fn test_load_additional_sources_errors_are_config_errors() {
+        let err = load_additional_sources_from_file("does-not-exist.json").unwrap_err();
+        assert!(matches!(err, Error::Config { .. }));
+        assert!(err.to_string().contains("does-not-exist.json"));
+    }
+
     #[test]
     fn test_crawler_source_structure() {
         for source in CRAWLER_IP_SRC_LIST {
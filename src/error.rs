@@ -0,0 +1,182 @@
+use crate::ip_utils::IpParseError;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Exit code for a negative result (no overlap, not a crawler, not found)
+pub const EXIT_NOT_FOUND: i32 = 1;
+/// Exit code for invalid user input
+pub const EXIT_BAD_INPUT: i32 = 2;
+/// Exit code for a missing or malformed configuration file
+pub const EXIT_CONFIG: i32 = 3;
+/// Exit code for a failure to download a data source
+pub const EXIT_NETWORK: i32 = 4;
+/// Exit code for a data source that could not be parsed
+pub const EXIT_DATA_PARSE: i32 = 5;
+/// Exit code for unexpected internal failures (see sysexits.h EX_SOFTWARE)
+pub const EXIT_INTERNAL: i32 = 70;
+
+/// Crate-wide error type
+#[derive(Debug)]
+pub enum Error {
+    /// An address, network or other argument could not be parsed
+    InvalidInput { input: String, reason: String },
+    /// A configuration file could not be read or is malformed
+    Config { path: PathBuf, message: String },
+    /// A data source could not be downloaded
+    Fetch {
+        source: String,
+        url: String,
+        message: String,
+    },
+    /// A data source could not be read or parsed
+    DataParse {
+        source: String,
+        line: Option<usize>,
+        message: String,
+    },
+    /// The check completed but the result was negative
+    NotFound(String),
+    /// Unexpected failure, e.g. while serializing output
+    Internal(String),
+}
+
+impl Error {
+    /// Create an `InvalidInput` error
+    pub fn invalid_input(input: impl Into<String>, reason: impl Into<String>) -> Self {
+        Error::InvalidInput {
+            input: input.into(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Create a `Config` error for the given file
+    pub fn config(path: impl AsRef<Path>, message: impl ToString) -> Self {
+        Error::Config {
+            path: path.as_ref().to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+
+    /// Create a `DataParse` error for the given source and optional line number
+    pub fn data_parse(
+        source: impl Into<String>,
+        line: Option<usize>,
+        message: impl ToString,
+    ) -> Self {
+        Error::DataParse {
+            source: source.into(),
+            line,
+            message: message.to_string(),
+        }
+    }
+
+    /// Stable identifier of the error category, as used in the machine-readable outputs
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::NotFound(_) => "not_found",
+            Error::InvalidInput { .. } => "invalid_input",
+            Error::Config { .. } => "config",
+            Error::Fetch { .. } => "fetch",
+            Error::DataParse { .. } => "data_parse",
+            Error::Internal(_) => "internal",
+        }
+    }
+
+    /// Process exit code for this error category
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::NotFound(_) => EXIT_NOT_FOUND,
+            Error::InvalidInput { .. } => EXIT_BAD_INPUT,
+            Error::Config { .. } => EXIT_CONFIG,
+            Error::Fetch { .. } => EXIT_NETWORK,
+            Error::DataParse { .. } => EXIT_DATA_PARSE,
+            Error::Internal(_) => EXIT_INTERNAL,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidInput { input, reason } => write!(f, "{}: {}", reason, input),
+            Error::Config { path, message } => {
+                write!(f, "Configuration error in {}: {}", path.display(), message)
+            }
+            Error::Fetch {
+                source,
+                url,
+                message,
+            } => write!(f, "Failed to fetch {} from {}: {}", source, url, message),
+            Error::DataParse {
+                source,
+                line: Some(line),
+                message,
+            } => write!(
+                f,
+                "Failed to parse {} at line {}: {}",
+                source, line, message
+            ),
+            Error::DataParse {
+                source,
+                line: None,
+                message,
+            } => write!(f, "Failed to parse {}: {}", source, message),
+            Error::NotFound(message) => write!(f, "{}", message),
+            Error::Internal(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<IpParseError> for Error {
+    fn from(err: IpParseError) -> Self {
+        match err {
+            IpParseError::InvalidFormat(input) => {
+                Error::invalid_input(input, "Invalid IP address format")
+            }
+            IpParseError::InvalidCidr(input) => {
+                Error::invalid_input(input, "Invalid CIDR notation")
+            }
+            IpParseError::InvalidPtr(input) => Error::invalid_input(input, "Invalid PTR name"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes_are_distinct() {
+        let errors = [
+            Error::NotFound("not a crawler".to_string()),
+            Error::invalid_input("x", "Invalid IP address format"),
+            Error::config("ipchecker.json", "expected value"),
+            Error::Fetch {
+                source: "Googlebot IP Ranges".to_string(),
+                url: "https://example.com".to_string(),
+                message: "timed out".to_string(),
+            },
+            Error::data_parse("delegated-apnic", Some(3), "bad record"),
+            Error::Internal("oops".to_string()),
+        ];
+        let mut codes: Vec<i32> = errors.iter().map(Error::exit_code).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), errors.len());
+    }
+
+    #[test]
+    fn test_display_includes_context() {
+        let err = Error::data_parse("delegated-apnic", Some(42), "invalid count");
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse delegated-apnic at line 42: invalid count"
+        );
+
+        let err = Error::from(IpParseError::InvalidFormat("300.1.1.1".to_string()));
+        assert_eq!(err.to_string(), "Invalid IP address format: 300.1.1.1");
+        assert_eq!(err.exit_code(), EXIT_BAD_INPUT);
+    }
+}
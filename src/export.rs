@@ -0,0 +1,168 @@
+use crate::ip_utils::range_to_cidrs;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Text format for exported network lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One CIDR per line
+    #[default]
+    Plain,
+    /// nginx `geo` block mapping each network to its label
+    Nginx,
+    /// nftables interval sets, one per address family
+    Nftables,
+    /// `ipset restore` commands, one hash:net set per address family
+    Ipset,
+}
+
+/// A network to export, with an optional label (e.g. a country code)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ExportEntry {
+    /// CIDR notation
+    pub network: String,
+    #[serde(skip)]
+    pub ipv4: bool,
+    pub label: Option<String>,
+}
+
+impl ExportEntry {
+    /// Split an inclusive integer range into CIDR entries with the same label
+    pub fn from_range(start: u128, end: u128, ipv4: bool, label: Option<&str>) -> Vec<Self> {
+        range_to_cidrs(start, end, ipv4)
+            .into_iter()
+            .map(|(ip, prefix_len)| ExportEntry {
+                network: format!("{}/{}", ip, prefix_len),
+                ipv4,
+                label: label.map(str::to_string),
+            })
+            .collect()
+    }
+}
+
+fn family_suffix(ipv4: bool) -> &'static str {
+    if ipv4 { "v4" } else { "v6" }
+}
+
+/// Render networks in the given format. `name` is the nginx variable or
+/// the nftables/ipset set name; sets get a `_v4`/`_v6` suffix.
+pub fn render_networks(entries: &[ExportEntry], format: ExportFormat, name: &str) -> String {
+    let mut text = String::new();
+    match format {
+        ExportFormat::Plain => {
+            for entry in entries {
+                text.push_str(&format!("{}\n", entry.network));
+            }
+        }
+        ExportFormat::Nginx => {
+            text.push_str(&format!("geo ${} {{\n    default \"\";\n", name));
+            for entry in entries {
+                text.push_str(&format!(
+                    "    {} {};\n",
+                    entry.network,
+                    entry.label.as_deref().unwrap_or("1")
+                ));
+            }
+            text.push_str("}\n");
+        }
+        ExportFormat::Nftables => {
+            for ipv4 in [true, false] {
+                let networks: Vec<&str> = entries
+                    .iter()
+                    .filter(|entry| entry.ipv4 == ipv4)
+                    .map(|entry| entry.network.as_str())
+                    .collect();
+                if networks.is_empty() {
+                    continue;
+                }
+                text.push_str(&format!(
+                    "set {}_{} {{\n    type {}\n    flags interval\n    elements = {{\n",
+                    name,
+                    family_suffix(ipv4),
+                    if ipv4 { "ipv4_addr" } else { "ipv6_addr" }
+                ));
+                for (index, network) in networks.iter().enumerate() {
+                    let separator = if index + 1 < networks.len() { "," } else { "" };
+                    text.push_str(&format!("        {}{}\n", network, separator));
+                }
+                text.push_str("    }\n}\n");
+            }
+        }
+        ExportFormat::Ipset => {
+            for ipv4 in [true, false] {
+                let set = format!("{}_{}", name, family_suffix(ipv4));
+                let mut networks = entries.iter().filter(|entry| entry.ipv4 == ipv4).peekable();
+                if networks.peek().is_none() {
+                    continue;
+                }
+                text.push_str(&format!(
+                    "create {} hash:net family {} -exist\n",
+                    set,
+                    if ipv4 { "inet" } else { "inet6" }
+                ));
+                for entry in networks {
+                    text.push_str(&format!("add {} {} -exist\n", set, entry.network));
+                }
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<ExportEntry> {
+        vec![
+            ExportEntry {
+                network: "1.0.16.0/20".to_string(),
+                ipv4: true,
+                label: Some("JP".to_string()),
+            },
+            ExportEntry {
+                network: "1.11.0.0/16".to_string(),
+                ipv4: true,
+                label: Some("KR".to_string()),
+            },
+            ExportEntry {
+                network: "2001:200::/23".to_string(),
+                ipv4: false,
+                label: Some("JP".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_plain_and_nginx() {
+        assert_eq!(
+            render_networks(&entries(), ExportFormat::Plain, "geo"),
+            "1.0.16.0/20\n1.11.0.0/16\n2001:200::/23\n"
+        );
+        assert_eq!(
+            render_networks(&entries(), ExportFormat::Nginx, "country"),
+            "geo $country {\n    default \"\";\n    1.0.16.0/20 JP;\n    1.11.0.0/16 KR;\n    2001:200::/23 JP;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_nftables() {
+        assert_eq!(
+            render_networks(&entries()[..2], ExportFormat::Nftables, "geoblock"),
+            "set geoblock_v4 {\n    type ipv4_addr\n    flags interval\n    elements = {\n        1.0.16.0/20,\n        1.11.0.0/16\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_ipset() {
+        assert_eq!(
+            render_networks(&entries(), ExportFormat::Ipset, "geoblock"),
+            "create geoblock_v4 hash:net family inet -exist\n\
+             add geoblock_v4 1.0.16.0/20 -exist\n\
+             add geoblock_v4 1.11.0.0/16 -exist\n\
+             create geoblock_v6 hash:net family inet6 -exist\n\
+             add geoblock_v6 2001:200::/23 -exist\n"
+        );
+    }
+}
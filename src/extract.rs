@@ -0,0 +1,265 @@
+use crate::ip_utils::{needs_zone, parse_cidr, parse_ip_address};
+use serde::Serialize;
+
+/// Whether an extracted token is a single address or a network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenKind {
+    Address,
+    Network,
+}
+
+impl TokenKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TokenKind::Address => "address",
+            TokenKind::Network => "network",
+        }
+    }
+}
+
+/// An IP address or CIDR found in text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Token {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column, counted in characters
+    pub column: usize,
+    /// The token as it appears in the text
+    pub token: String,
+    pub kind: TokenKind,
+    /// 4 or 6
+    pub version: u8,
+}
+
+/// Characters an address or CIDR token is made of
+fn is_token_char(c: char) -> bool {
+    c.is_ascii_hexdigit() || matches!(c, '.' | ':' | '/')
+}
+
+/// Characters that may surround a token in prose or URLs and are trimmed
+/// when the whole run is not valid
+fn is_edge_punctuation(c: char) -> bool {
+    matches!(c, '.' | ':' | '/')
+}
+
+/// Characters that make a token part of a larger word, e.g. the "v" of
+/// "v1.2.3.4"
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+/// Characters of an IPv6 zone ID such as the "eth0" of "fe80::1%eth0"
+fn is_zone_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Length of the `%zone` suffix at `chars[end..]` of an IPv6 address that
+/// takes one (link-local), or 0. A trailing "." ends the sentence, not the
+/// zone.
+fn zone_len(chars: &[char], end: usize, token: &str) -> usize {
+    if chars.get(end) != Some(&'%') || !parse_ip_address(token).is_ok_and(needs_zone) {
+        return 0;
+    }
+    let zone = &chars[end + 1..];
+    let len = zone.iter().take_while(|c| is_zone_char(**c)).count();
+    let len = len - zone[..len].iter().rev().take_while(|c| **c == '.').count();
+    if len == 0 { 0 } else { len + 1 }
+}
+
+/// Validate a candidate with `parse_ip_address` or `parse_cidr`
+fn classify(candidate: &str) -> Option<(TokenKind, u8)> {
+    if !candidate.bytes().any(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let (kind, ip) = if candidate.contains('/') {
+        (TokenKind::Network, parse_cidr(candidate).ok()?.0)
+    } else {
+        (TokenKind::Address, parse_ip_address(candidate).ok()?)
+    };
+    Some((kind, if ip.is_ipv4() { 4 } else { 6 }))
+}
+
+/// Find the first token inside a run of token characters. Only the
+/// punctuation at either end may be dropped, so "1.2.3.4.5" yields nothing
+/// rather than "1.2.3.4". An IPv4 address followed by ":port" is found too.
+fn match_run(run: &[char]) -> Option<(usize, usize, TokenKind, u8)> {
+    let leading = run.iter().take_while(|c| is_edge_punctuation(**c)).count();
+    let trailing = run
+        .iter()
+        .rev()
+        .take_while(|c| is_edge_punctuation(**c))
+        .count();
+
+    for start in 0..=leading.min(run.len()) {
+        for end in (run.len().saturating_sub(trailing).max(start)..=run.len()).rev() {
+            let candidate: String = run[start..end].iter().collect();
+            if let Some((kind, version)) = classify(&candidate) {
+                return Some((start, end, kind, version));
+            }
+        }
+    }
+
+    let start = leading.min(run.len());
+    let candidate: String = run[start..run.len().saturating_sub(trailing).max(start)]
+        .iter()
+        .collect();
+    // An address followed by a URL path (192.0.2.1/admin), but not an
+    // invalid prefix length (10.0.0.0/33)
+    if let Some((address, path)) = candidate.split_once('/')
+        && !path.bytes().all(|b| b.is_ascii_digit())
+        && let Some((TokenKind::Address, version)) = classify(address)
+    {
+        return Some((start, start + address.len(), TokenKind::Address, version));
+    }
+    // An IPv4 address and port (192.0.2.1:8080)
+    if let Some((address, port)) = candidate.split_once(':')
+        && !port.is_empty()
+        && port.bytes().all(|b| b.is_ascii_digit())
+        && let Some((TokenKind::Address, 4)) = classify(address)
+    {
+        return Some((start, start + address.len(), TokenKind::Address, 4));
+    }
+    None
+}
+
+/// Where a token may start within a run: at the run itself, or after the
+/// `:` or `.` ending a prefix of hex letters, which is the tail of a key in
+/// `src:10.0.0.1` or `id:192.0.2.1` rather than part of the address
+fn run_offsets(run: &[char]) -> Vec<usize> {
+    let mut offsets = vec![0];
+    for (position, c) in run.iter().enumerate() {
+        match c {
+            ':' | '.' if position > 0 => offsets.push(position + 1),
+            ':' | '.' => {}
+            c if c.is_ascii_hexdigit() && !c.is_ascii_digit() => {}
+            _ => break,
+        }
+    }
+    offsets.retain(|&offset| offset < run.len());
+    offsets
+}
+
+/// Find every IPv4 and IPv6 address and CIDR in `text`. Tokens may be
+/// surrounded by punctuation but not be part of a word or a longer dotted
+/// number.
+pub fn extract_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for (index, line) in text.lines().enumerate() {
+        let chars: Vec<char> = line.chars().collect();
+        let mut i = 0;
+        while i < chars.len() {
+            if !is_token_char(chars[i]) {
+                i += 1;
+                continue;
+            }
+            let run_start = i;
+            while i < chars.len() && is_token_char(chars[i]) {
+                i += 1;
+            }
+            let run = &chars[run_start..i];
+            let found = run_offsets(run).into_iter().find_map(|offset| {
+                let (start, end, kind, version) = match_run(&run[offset..])?;
+                let (start, end) = (run_start + offset + start, run_start + offset + end);
+                let before = start.checked_sub(1).map(|position| chars[position]);
+                let after = chars.get(end).copied();
+                if before.is_some_and(is_word_char) || after.is_some_and(is_word_char) {
+                    return None;
+                }
+                Some((start, end, kind, version))
+            });
+            let Some((start, end, kind, version)) = found else {
+                continue;
+            };
+            let mut token: String = chars[start..end].iter().collect();
+            let mut end = end;
+            if kind == TokenKind::Address && version == 6 {
+                let zone = zone_len(&chars, end, &token);
+                token.extend(&chars[end..end + zone]);
+                end += zone;
+            }
+            tokens.push(Token {
+                line: index + 1,
+                column: start + 1,
+                token,
+                kind,
+                version,
+            });
+            // The rest of the run may hold another token (10.0.0.1/10.0.0.2)
+            i = end;
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(text: &str) -> Vec<String> {
+        extract_tokens(text)
+            .into_iter()
+            .map(|token| token.token)
+            .collect()
+    }
+
+    #[test]
+    fn test_extract_tokens() {
+        assert_eq!(
+            found("Failed login from 203.0.113.9, then (2001:db8::1) and 10.0.0.0/8."),
+            ["203.0.113.9", "2001:db8::1", "10.0.0.0/8"]
+        );
+        assert_eq!(
+            found("GET http://192.0.2.1/admin from [2001:db8::2]:443 via 198.51.100.7:8080"),
+            ["192.0.2.1", "2001:db8::2", "198.51.100.7"]
+        );
+        assert_eq!(
+            found("<user@[192.0.2.25]> addr:10.1.2.3; ::ffff:192.0.2.1"),
+            ["192.0.2.25", "10.1.2.3", "::ffff:192.0.2.1"]
+        );
+        assert_eq!(
+            found("src:10.0.0.1 dst:10.0.0.2 id:192.0.2.1 ab:192.0.2.2"),
+            ["10.0.0.1", "10.0.0.2", "192.0.2.1", "192.0.2.2"]
+        );
+        assert_eq!(
+            found("face.10.0.0.1 cafe:2001:db8::1"),
+            ["10.0.0.1", "cafe:2001:db8::1"]
+        );
+
+        // Version numbers, words and other near misses
+        assert!(found("libfoo 1.2.3.4.5, v1.2.3.4, 1.2.3.4b, 256.1.1.1").is_empty());
+        assert!(found("v1.10.0.0.1 x10.0.0.1 ab:1.2.3.4.5").is_empty());
+        assert!(found("std::vector, Foo :: bar, 12:30:45, 00:1a:2b:3c:4d:5e").is_empty());
+        assert!(found("10.0.0.0/33 and 1.2.3").is_empty());
+    }
+
+    #[test]
+    fn test_extract_zones_and_adjacent_tokens() {
+        assert_eq!(
+            found("ping fe80::1%eth0, [fe80::2%en0]:8443 and ff02::1%wlan-1."),
+            ["fe80::1%eth0", "fe80::2%en0", "ff02::1%wlan-1"]
+        );
+        // Zone IDs only belong to link-local addresses
+        assert_eq!(
+            found("2001:db8::1%eth0 fe80::3% done"),
+            ["2001:db8::1", "fe80::3"]
+        );
+        assert_eq!(
+            found("10.0.0.1/10.0.0.2 and 192.0.2.1/198.51.100.1"),
+            ["10.0.0.1", "10.0.0.2", "192.0.2.1", "198.51.100.1"]
+        );
+    }
+
+    #[test]
+    fn test_extract_positions() {
+        let tokens = extract_tokens("first line\n  fe80::1 and 192.0.2.0/24\n");
+        assert_eq!(tokens.len(), 2);
+        assert_eq!((tokens[0].line, tokens[0].column), (2, 3));
+        assert_eq!((tokens[0].kind, tokens[0].version), (TokenKind::Address, 6));
+        assert_eq!((tokens[1].line, tokens[1].column), (2, 15));
+        assert_eq!(tokens[1].kind, TokenKind::Network);
+
+        let tokens = extract_tokens("é 192.0.2.1");
+        assert_eq!(tokens[0].column, 3);
+    }
+}
@@ -0,0 +1,318 @@
+use super::GeoRecord;
+use super::provider::{GeoProvider, ProviderKind};
+use super::range_table::{RangeEntry, RangeTable, RangeTableBuilder, parse_country_code};
+use crate::error::Error;
+use crate::ip_utils::address_value;
+use std::fs;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::PathBuf;
+
+/// Column layout of a geolocation CSV export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CsvLayout {
+    /// IP2Location LITE DB1+: "from","to","CC","Country name",... with integer bounds
+    Ip2Location,
+    /// DB-IP country lite: start,end,CC with textual bounds
+    DbIpCountry,
+    /// DB-IP city lite: start,end,continent,CC,region,city,lat,lon
+    DbIpCity,
+}
+
+impl CsvLayout {
+    /// Guess the layout from the first data row
+    fn detect(fields: &[&str]) -> Self {
+        if fields[0].parse::<u128>().is_ok() {
+            CsvLayout::Ip2Location
+        } else if fields.len() >= 8 {
+            CsvLayout::DbIpCity
+        } else {
+            CsvLayout::DbIpCountry
+        }
+    }
+
+    fn country_column(&self) -> usize {
+        match self {
+            CsvLayout::Ip2Location | CsvLayout::DbIpCountry => 2,
+            CsvLayout::DbIpCity => 3,
+        }
+    }
+}
+
+/// Start of the IPv4-mapped block (::ffff:0:0/96) used by IP2Location IPv6 files
+const IPV4_MAPPED_START: u128 = 0xffff_0000_0000;
+const IPV4_MAPPED_END: u128 = 0xffff_ffff_ffff;
+
+/// Convert an integer range bound pair to addresses. Ranges inside
+/// ::ffff:0:0/96 are IPv4 ranges stored in an IPv6 file.
+fn integer_range(start: u128, end: u128) -> (IpAddr, IpAddr) {
+    if end <= u32::MAX as u128 {
+        (
+            IpAddr::V4(Ipv4Addr::from(start as u32)),
+            IpAddr::V4(Ipv4Addr::from(end as u32)),
+        )
+    } else if start >= IPV4_MAPPED_START && end <= IPV4_MAPPED_END {
+        (
+            IpAddr::V4(Ipv4Addr::from((start - IPV4_MAPPED_START) as u32)),
+            IpAddr::V4(Ipv4Addr::from((end - IPV4_MAPPED_START) as u32)),
+        )
+    } else {
+        (
+            IpAddr::V6(Ipv6Addr::from(start)),
+            IpAddr::V6(Ipv6Addr::from(end)),
+        )
+    }
+}
+
+/// Add the ranges of an IP2Location LITE or DB-IP style CSV export to
+/// `builder` and return the number of rows added.
+///
+/// Rows without a country (`-`, `ZZ` or empty) are skipped. Rows must be
+/// sorted by start address within each address family; an out-of-order row
+/// is reported with its line number, as are overlapping rows (when the
+/// builder is built).
+pub fn load_csv(
+    content: &str,
+    source: &str,
+    builder: &mut RangeTableBuilder,
+) -> Result<usize, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut layout = None;
+    let mut first_row = true;
+    let mut previous: [Option<(u128, usize)>; 2] = [None, None];
+    let mut count = 0;
+
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map(|position| position.line() as usize);
+            Error::data_parse(source, line, e)
+        })?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let fields: Vec<&str> = record.iter().collect();
+        if fields.len() < 3 || fields[0].starts_with('#') {
+            if fields.iter().all(|field| field.is_empty()) || fields[0].starts_with('#') {
+                continue;
+            }
+            return Err(Error::data_parse(
+                source,
+                Some(line),
+                format!("expected at least 3 columns, found {}", fields.len()),
+            ));
+        }
+
+        let layout = *layout.get_or_insert_with(|| CsvLayout::detect(&fields));
+        // Only the first row may be a header such as "ip_from","ip_to",...
+        let header_allowed = std::mem::replace(&mut first_row, false);
+        let parse_error = |message: String| Error::data_parse(source, Some(line), message);
+
+        let (start, end) = if layout == CsvLayout::Ip2Location {
+            let bound = |text: &str| {
+                text.parse::<u128>()
+                    .map_err(|_| parse_error(format!("invalid integer address '{}'", text)))
+            };
+            match (bound(fields[0]), bound(fields[1])) {
+                (Ok(start), Ok(end)) => integer_range(start, end),
+                (Err(_), _) if header_allowed => continue,
+                (Err(e), _) | (_, Err(e)) => return Err(e),
+            }
+        } else {
+            let bound = |text: &str| {
+                text.parse::<IpAddr>()
+                    .map_err(|_| parse_error(format!("invalid IP address '{}'", text)))
+            };
+            match (bound(fields[0]), bound(fields[1])) {
+                (Ok(start), Ok(end)) => (start, end),
+                (Err(_), _) if header_allowed => continue,
+                (Err(e), _) | (_, Err(e)) => return Err(e),
+            }
+        };
+
+        let Some(country_field) = fields.get(layout.country_column()) else {
+            return Err(parse_error("missing country column".to_string()));
+        };
+        if matches!(*country_field, "" | "-" | "ZZ") {
+            continue;
+        }
+        let country = parse_country_code(country_field)
+            .ok_or_else(|| parse_error(format!("invalid country code '{}'", country_field)))?;
+
+        let (ipv4, start_value) = address_value(start);
+        let family = if ipv4 { 0 } else { 1 };
+        if let Some((previous_start, previous_line)) = previous[family]
+            && start_value < previous_start
+        {
+            return Err(parse_error(format!(
+                "row is not sorted: starts at {}, before the row at line {}",
+                start, previous_line
+            )));
+        }
+        previous[family] = Some((start_value, line));
+
+        builder.push(start, end, country, None, source, line)?;
+        if layout == CsvLayout::Ip2Location
+            && let Some(name) = fields
+                .get(3)
+                .filter(|name| !name.is_empty() && **name != "-")
+        {
+            builder.set_country_name(country, name);
+        }
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// IP2Location LITE or DB-IP style CSV exports loaded into one range table
+pub struct CsvTable {
+    files: Vec<PathBuf>,
+    table: RangeTable,
+}
+
+impl CsvTable {
+    /// Load one or more exports. IPv4 and IPv6 exports may be given as
+    /// separate files; ranges that overlap across files are reported like
+    /// overlaps within a file.
+    pub fn open(files: &[PathBuf]) -> Result<Self, Error> {
+        let mut builder = RangeTableBuilder::new();
+        for file in files {
+            let source = file.display().to_string();
+            let content =
+                fs::read_to_string(file).map_err(|e| Error::data_parse(&source, None, e))?;
+            load_csv(&content, &source, &mut builder)?;
+        }
+        Ok(CsvTable {
+            files: files.to_vec(),
+            table: builder.build()?,
+        })
+    }
+
+    pub fn table(&self) -> &RangeTable {
+        &self.table
+    }
+}
+
+impl GeoProvider for CsvTable {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Csv
+    }
+
+    fn source(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+        Ok(self.table.lookup(ip))
+    }
+
+    fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        Ok(self.table.entries(ipv4).to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(content: &str) -> Result<super::super::RangeTable, Error> {
+        let mut builder = RangeTableBuilder::new();
+        load_csv(content, "test.csv", &mut builder)?;
+        builder.build()
+    }
+
+    #[test]
+    fn test_ip2location() {
+        let table = load(
+            "\"0\",\"16777215\",\"-\",\"-\"\n\
+             \"16777216\",\"16777471\",\"US\",\"United States of America\"\n\
+             \"16777472\",\"16778239\",\"CN\",\"China\"\n",
+        )
+        .unwrap();
+        let record = table.lookup("1.0.0.77".parse().unwrap()).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("US"));
+        assert_eq!(
+            record.country_name.as_deref(),
+            Some("United States of America")
+        );
+        assert!(table.lookup("0.1.2.3".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_ip2location_ipv6_with_mapped_ipv4() {
+        let table = load(
+            "\"281470698520576\",\"281470698520831\",\"US\",\"United States of America\"\n\
+             \"42540528726795050063891204319802818560\",\"42540528806023212578155541913346768895\",\"JP\",\"Japan\"\n",
+        )
+        .unwrap();
+        // ::ffff:1.0.0.0 - ::ffff:1.0.0.255
+        let record = table.lookup("1.0.0.1".parse().unwrap()).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("US"));
+        let record = table.lookup("2001:200::1".parse().unwrap()).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+    }
+
+    #[test]
+    fn test_dbip_country_and_city() {
+        let table =
+            load("1.0.0.0,1.0.0.255,AU\n2001:200::,2001:200:ffff:ffff:ffff:ffff:ffff:ffff,JP\n")
+                .unwrap();
+        assert_eq!(
+            table
+                .lookup("1.0.0.9".parse().unwrap())
+                .unwrap()
+                .country_code
+                .as_deref(),
+            Some("AU")
+        );
+        assert!(table.lookup("2001:200::9".parse().unwrap()).is_some());
+
+        let table =
+            load("1.0.0.0,1.0.0.255,OC,AU,Queensland,South Brisbane,-27.4767,153.017\n").unwrap();
+        assert_eq!(
+            table
+                .lookup("1.0.0.9".parse().unwrap())
+                .unwrap()
+                .country_code
+                .as_deref(),
+            Some("AU")
+        );
+    }
+
+    #[test]
+    fn test_header_row_is_skipped() {
+        let table = load("start_ip,end_ip,country\n1.0.0.0,1.0.0.255,AU\n").unwrap();
+        assert_eq!(table.len(), (1, 0));
+    }
+
+    #[test]
+    fn test_unsorted_rows_are_reported() {
+        let err = load("1.0.1.0,1.0.1.255,CN\n1.0.0.0,1.0.0.255,AU\n").unwrap_err();
+        assert!(matches!(err, Error::DataParse { line: Some(2), .. }));
+        assert!(err.to_string().contains("not sorted"));
+    }
+
+    #[test]
+    fn test_overlapping_rows_are_reported() {
+        let err = load("1.0.0.0,1.0.0.255,AU\n1.0.0.128,1.0.1.255,CN\n").unwrap_err();
+        assert!(matches!(err, Error::DataParse { line: Some(2), .. }));
+        assert!(err.to_string().contains("overlaps"));
+    }
+
+    #[test]
+    fn test_invalid_rows_are_reported() {
+        let err = load("1.0.0.0,1.0.0.255,AU\n1.0.1.0,not-an-ip,CN\n").unwrap_err();
+        assert!(matches!(err, Error::DataParse { line: Some(2), .. }));
+
+        let err = load("1.0.0.0,1.0.0.255,AUS\n").unwrap_err();
+        assert!(err.to_string().contains("invalid country code 'AUS'"));
+    }
+}
@@ -0,0 +1,549 @@
+use super::GeoRecord;
+use super::iso3166::is_country_code;
+use super::provider::{GeoProvider, ProviderKind};
+use super::range_table::{RangeEntry, parse_country_code};
+use crate::error::Error;
+use crate::ip_utils::{address_value, host_mask, network_range, parse_cidr, value_address};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// One valid line of an RFC 8805 geofeed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GeofeedEntry {
+    pub line: usize,
+    /// CIDR notation
+    pub prefix: String,
+    #[serde(skip)]
+    pub ipv4: bool,
+    #[serde(skip)]
+    pub start: u128,
+    #[serde(skip)]
+    pub end: u128,
+    #[serde(skip)]
+    pub prefix_len: u8,
+    /// ISO 3166-1 alpha-2 code, upper case; `None` when the field is empty
+    pub country: Option<String>,
+    /// ISO 3166-2 subdivision code, e.g. "US-CA"
+    pub region: Option<String>,
+    pub city: Option<String>,
+    pub postal_code: Option<String>,
+}
+
+/// How serious a geofeed problem is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSeverity {
+    /// The line is skipped by consumers
+    Error,
+    /// The line is used, but probably not as intended
+    Warning,
+}
+
+impl IssueSeverity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IssueSeverity::Error => "error",
+            IssueSeverity::Warning => "warning",
+        }
+    }
+}
+
+/// A problem found on one line of a geofeed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct GeofeedIssue {
+    pub line: usize,
+    pub severity: IssueSeverity,
+    /// Prefix field as written
+    pub prefix: String,
+    pub message: String,
+}
+
+/// A parsed geofeed: the usable entries and the problems found
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Geofeed {
+    /// Entries in file order; lines with errors are left out
+    pub entries: Vec<GeofeedEntry>,
+    /// Issues in line order
+    pub issues: Vec<GeofeedIssue>,
+}
+
+impl Geofeed {
+    pub fn errors(&self) -> usize {
+        self.issues
+            .iter()
+            .filter(|issue| issue.severity == IssueSeverity::Error)
+            .count()
+    }
+
+    pub fn warnings(&self) -> usize {
+        self.issues.len() - self.errors()
+    }
+}
+
+/// Check an ISO 3166-2 code such as "US-CA" against the entry's country
+fn check_region(region: &str, country: Option<&str>) -> Result<(), String> {
+    let Some((prefix, subdivision)) = region.split_once('-') else {
+        return Err(format!("region '{}' is not an ISO 3166-2 code", region));
+    };
+    if subdivision.is_empty()
+        || subdivision.len() > 3
+        || !subdivision.chars().all(|c| c.is_ascii_alphanumeric())
+    {
+        return Err(format!("region '{}' is not an ISO 3166-2 code", region));
+    }
+    match country {
+        Some(country) if prefix.eq_ignore_ascii_case(country) => Ok(()),
+        Some(country) => Err(format!(
+            "region '{}' is not a subdivision of {}",
+            region, country
+        )),
+        None => Err(format!("region '{}' is given without a country", region)),
+    }
+}
+
+/// Parse an RFC 8805 geofeed: CSV lines of prefix, country, region, city
+/// and postal code, with `#` comments.
+///
+/// Problems with individual lines are collected as issues rather than
+/// failing the parse: invalid prefixes, host bits, invalid country or region
+/// codes and duplicate prefixes are errors, and prefixes nested in another
+/// entry are warnings (the longest match applies). Only malformed CSV is an
+/// `Err`.
+pub fn parse_geofeed(content: &str, source: &str) -> Result<Geofeed, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut feed = Geofeed::default();
+    let mut seen: BTreeMap<(bool, u128, u8), usize> = BTreeMap::new();
+
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map(|position| position.line() as usize);
+            Error::data_parse(source, line, e)
+        })?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let fields: Vec<&str> = record.iter().collect();
+        if fields.iter().all(|field| field.is_empty()) || fields[0].starts_with('#') {
+            continue;
+        }
+
+        let prefix = fields[0];
+        let mut issue = |severity: IssueSeverity, message: String| {
+            feed.issues.push(GeofeedIssue {
+                line,
+                severity,
+                prefix: prefix.to_string(),
+                message,
+            });
+        };
+        let field = |index: usize| {
+            fields
+                .get(index)
+                .filter(|value| !value.is_empty())
+                .map(|value| value.to_string())
+        };
+
+        if fields.len() > 5 {
+            issue(
+                IssueSeverity::Warning,
+                format!("expected at most 5 fields, found {}", fields.len()),
+            );
+        }
+
+        let (ip, prefix_len) = match parse_cidr(prefix) {
+            Ok(network) => network,
+            Err(e) => {
+                issue(IssueSeverity::Error, e.to_string());
+                continue;
+            }
+        };
+        let (ipv4, value) = address_value(ip);
+        let (start, end) = network_range(ip, prefix_len);
+        if value & host_mask(prefix_len, ipv4) != 0 {
+            issue(
+                IssueSeverity::Error,
+                format!(
+                    "prefix has host bits set; the network is {}/{}",
+                    value_address(start, ipv4),
+                    prefix_len
+                ),
+            );
+            continue;
+        }
+
+        let country = match field(1) {
+            None => None,
+            Some(code) => match parse_country_code(&code) {
+                Some(parsed) if is_country_code(parsed) => {
+                    if code.bytes().any(|b| b.is_ascii_lowercase()) {
+                        issue(
+                            IssueSeverity::Warning,
+                            format!("country code '{}' should be upper case", code),
+                        );
+                    }
+                    Some(String::from_utf8_lossy(&parsed).into_owned())
+                }
+                _ => {
+                    issue(
+                        IssueSeverity::Error,
+                        format!("'{}' is not an ISO 3166-1 alpha-2 country code", code),
+                    );
+                    continue;
+                }
+            },
+        };
+
+        let region = field(2).map(|region| region.to_ascii_uppercase());
+        if let Some(region) = &region
+            && let Err(message) = check_region(region, country.as_deref())
+        {
+            issue(IssueSeverity::Error, message);
+            continue;
+        }
+
+        if let Some(first) = seen.insert((ipv4, start, prefix_len), line) {
+            seen.insert((ipv4, start, prefix_len), first);
+            issue(
+                IssueSeverity::Error,
+                format!("duplicate of the entry at line {}", first),
+            );
+            continue;
+        }
+
+        feed.entries.push(GeofeedEntry {
+            line,
+            prefix: format!("{}/{}", ip, prefix_len),
+            ipv4,
+            start,
+            end,
+            prefix_len,
+            country,
+            region,
+            city: field(3),
+            postal_code: field(4),
+        });
+    }
+
+    for (outer, inner) in nested_entries(&feed.entries) {
+        let (outer, inner) = (&feed.entries[outer], &feed.entries[inner]);
+        feed.issues.push(GeofeedIssue {
+            line: inner.line,
+            severity: IssueSeverity::Warning,
+            prefix: inner.prefix.clone(),
+            message: format!(
+                "overlaps {} at line {}; the longer prefix applies",
+                outer.prefix, outer.line
+            ),
+        });
+    }
+    feed.issues.sort_by_key(|issue| issue.line);
+
+    Ok(feed)
+}
+
+/// Entry indices in address order: prefixes of one family sorted by start,
+/// less specific first
+fn sorted_indices(entries: &[GeofeedEntry], ipv4: bool) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..entries.len())
+        .filter(|&index| entries[index].ipv4 == ipv4)
+        .collect();
+    order.sort_by_key(|&index| (entries[index].start, entries[index].prefix_len));
+    order
+}
+
+/// Pairs of (enclosing, nested) entry indices, naming the closest
+/// enclosing entry of each nested one
+fn nested_entries(entries: &[GeofeedEntry]) -> Vec<(usize, usize)> {
+    let mut nested = Vec::new();
+    for ipv4 in [true, false] {
+        let mut stack: Vec<usize> = Vec::new();
+        for index in sorted_indices(entries, ipv4) {
+            while let Some(&top) = stack.last()
+                && entries[top].end < entries[index].start
+            {
+                stack.pop();
+            }
+            if let Some(&top) = stack.last() {
+                nested.push((top, index));
+            }
+            stack.push(index);
+        }
+    }
+    nested
+}
+
+/// Part of the address space answered by one entry after longest-match
+/// resolution
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Segment {
+    start: u128,
+    end: u128,
+    entry: usize,
+}
+
+/// Split nested prefixes into non-overlapping segments, each owned by the
+/// most specific entry covering it
+fn flatten(entries: &[GeofeedEntry], ipv4: bool) -> Vec<Segment> {
+    fn emit(segments: &mut Vec<Segment>, cursor: Option<u128>, end: u128, entry: usize) {
+        if let Some(start) = cursor
+            && start <= end
+        {
+            segments.push(Segment { start, end, entry });
+        }
+    }
+
+    let mut segments = Vec::new();
+    let mut stack: Vec<usize> = Vec::new();
+    // Next address not yet assigned; `None` once the top of the space is reached
+    let mut cursor = Some(0);
+
+    for index in sorted_indices(entries, ipv4) {
+        let entry = &entries[index];
+        while let Some(&top) = stack.last()
+            && entries[top].end < entry.start
+        {
+            stack.pop();
+            emit(&mut segments, cursor, entries[top].end, top);
+            cursor = entries[top].end.checked_add(1);
+        }
+        if let Some(&top) = stack.last()
+            && entry.start > 0
+        {
+            emit(&mut segments, cursor, entry.start - 1, top);
+        }
+        cursor = Some(entry.start);
+        stack.push(index);
+    }
+    while let Some(top) = stack.pop() {
+        emit(&mut segments, cursor, entries[top].end, top);
+        cursor = entries[top].end.checked_add(1);
+    }
+    segments
+}
+
+/// RFC 8805 geofeeds used as a geolocation provider. Lines with errors are
+/// skipped; nested prefixes resolve to the most specific entry.
+pub struct GeofeedTable {
+    files: Vec<PathBuf>,
+    entries: Vec<GeofeedEntry>,
+    ipv4: Vec<Segment>,
+    ipv6: Vec<Segment>,
+}
+
+impl GeofeedTable {
+    /// Build a table from parsed entries. Entries of later feeds that
+    /// duplicate a prefix of an earlier one are ignored.
+    pub fn new(files: Vec<PathBuf>, entries: Vec<GeofeedEntry>) -> Self {
+        let mut seen = BTreeSet::new();
+        let entries: Vec<GeofeedEntry> = entries
+            .into_iter()
+            .filter(|entry| seen.insert((entry.ipv4, entry.start, entry.prefix_len)))
+            .collect();
+        GeofeedTable {
+            ipv4: flatten(&entries, true),
+            ipv6: flatten(&entries, false),
+            files,
+            entries,
+        }
+    }
+
+    /// Load one or more geofeed files
+    pub fn open(files: &[PathBuf]) -> Result<Self, Error> {
+        let mut entries = Vec::new();
+        for file in files {
+            let source = file.display().to_string();
+            let content =
+                fs::read_to_string(file).map_err(|e| Error::data_parse(&source, None, e))?;
+            entries.extend(parse_geofeed(&content, &source)?.entries);
+        }
+        Ok(Self::new(files.to_vec(), entries))
+    }
+
+    /// Most specific entry containing `ip`
+    pub fn find(&self, ip: IpAddr) -> Option<&GeofeedEntry> {
+        let (ipv4, value) = address_value(ip);
+        let segments = if ipv4 { &self.ipv4 } else { &self.ipv6 };
+        let index = segments.partition_point(|segment| segment.start <= value);
+        let segment = segments[..index].last()?;
+        (value <= segment.end).then(|| &self.entries[segment.entry])
+    }
+}
+
+impl GeoProvider for GeofeedTable {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Geofeed
+    }
+
+    fn source(&self) -> String {
+        self.files
+            .iter()
+            .map(|file| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+        Ok(self.find(ip).map(|entry| GeoRecord {
+            country_code: entry.country.clone(),
+            subdivision: entry.region.clone(),
+            city: entry.city.clone(),
+            ..GeoRecord::default()
+        }))
+    }
+
+    fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        let segments = if ipv4 { &self.ipv4 } else { &self.ipv6 };
+        Ok(segments
+            .iter()
+            .filter_map(|segment| {
+                let country = self.entries[segment.entry].country.as_deref()?;
+                Some(RangeEntry {
+                    start: segment.start,
+                    end: segment.end,
+                    country: parse_country_code(country)?,
+                    registry: None,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEED: &str = "\
+# prefix,country,region,city,postal
+192.0.2.0/24,US,US-CA,San Francisco,
+192.0.2.128/25,JP,JP-13,Tokyo
+192.0.2.130/32,,,
+198.51.100.1/24,US,,,
+203.0.113.0/24,UK,,,
+203.0.114.0/24,us,CA-ON,,
+2001:db8::/32,de,DE-BE,Berlin,10115,extra
+2001:db8::/32,FR,,,
+not-a-prefix,US,,,
+";
+
+    fn table() -> GeofeedTable {
+        let feed = parse_geofeed(FEED, "feed.csv").unwrap();
+        GeofeedTable::new(vec![PathBuf::from("feed.csv")], feed.entries)
+    }
+
+    #[test]
+    fn test_parse_geofeed() {
+        let feed = parse_geofeed(FEED, "feed.csv").unwrap();
+        assert_eq!(
+            feed.entries
+                .iter()
+                .map(|entry| entry.prefix.as_str())
+                .collect::<Vec<_>>(),
+            [
+                "192.0.2.0/24",
+                "192.0.2.128/25",
+                "192.0.2.130/32",
+                "2001:db8::/32"
+            ]
+        );
+        assert_eq!(feed.entries[0].region.as_deref(), Some("US-CA"));
+        assert_eq!(feed.entries[3].country.as_deref(), Some("DE"));
+        assert_eq!(feed.entries[3].postal_code.as_deref(), Some("10115"));
+
+        let issues: Vec<(usize, &str, &str)> = feed
+            .issues
+            .iter()
+            .map(|issue| (issue.line, issue.severity.as_str(), issue.message.as_str()))
+            .collect();
+        assert_eq!(
+            issues,
+            [
+                (
+                    3,
+                    "warning",
+                    "overlaps 192.0.2.0/24 at line 2; the longer prefix applies"
+                ),
+                (
+                    4,
+                    "warning",
+                    "overlaps 192.0.2.128/25 at line 3; the longer prefix applies"
+                ),
+                (
+                    5,
+                    "error",
+                    "prefix has host bits set; the network is 198.51.100.0/24"
+                ),
+                (6, "error", "'UK' is not an ISO 3166-1 alpha-2 country code"),
+                (7, "warning", "country code 'us' should be upper case"),
+                (7, "error", "region 'CA-ON' is not a subdivision of US"),
+                (8, "warning", "expected at most 5 fields, found 6"),
+                (8, "warning", "country code 'de' should be upper case"),
+                (9, "error", "duplicate of the entry at line 8"),
+                (10, "error", "Invalid CIDR notation: not-a-prefix"),
+            ]
+        );
+        assert_eq!((feed.errors(), feed.warnings()), (5, 5));
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = table();
+        let lookup = |ip: &str| table.lookup(ip.parse().unwrap()).unwrap();
+
+        let record = lookup("192.0.2.1").unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("US"));
+        assert_eq!(record.city.as_deref(), Some("San Francisco"));
+        assert_eq!(
+            lookup("192.0.2.200").unwrap().country_code.as_deref(),
+            Some("JP")
+        );
+        // The most specific entry has no country
+        assert_eq!(lookup("192.0.2.130").unwrap().country_code, None);
+        assert_eq!(
+            lookup("192.0.2.131").unwrap().subdivision.as_deref(),
+            Some("JP-13")
+        );
+        assert_eq!(lookup("198.51.100.1"), None);
+        assert_eq!(
+            lookup("2001:db8::1").unwrap().country_code.as_deref(),
+            Some("DE")
+        );
+    }
+
+    #[test]
+    fn test_ranges() {
+        let ranges: Vec<(u128, u128, String)> = table()
+            .ranges(true)
+            .unwrap()
+            .into_iter()
+            .map(|entry| (entry.start, entry.end, entry.country_code()))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (0xc0000200, 0xc000027f, "US".to_string()),
+                (0xc0000280, 0xc0000281, "JP".to_string()),
+                (0xc0000283, 0xc00002ff, "JP".to_string()),
+            ]
+        );
+
+        let everything = GeofeedTable::new(
+            Vec::new(),
+            parse_geofeed("::/0,SE\n::/1,NO\n", "feed.csv")
+                .unwrap()
+                .entries,
+        );
+        let ranges = everything.ranges(false).unwrap();
+        assert_eq!(ranges.len(), 2);
+        assert_eq!(ranges[1].start, 1 << 127);
+        assert_eq!(ranges[1].end, u128::MAX);
+        assert_eq!(ranges[1].country_code(), "SE");
+    }
+}
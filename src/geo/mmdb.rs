@@ -0,0 +1,252 @@
+use super::GeoRecord;
+use super::provider::{GeoProvider, ProviderKind};
+use super::range_table::{RangeEntry, parse_country_code};
+use crate::error::Error;
+use crate::ip_utils::network_range;
+use ipnetwork::{IpNetwork, Ipv4Network, Ipv6Network};
+use maxminddb::{MaxMindDBError, Reader, geoip2};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+/// Language used for country, continent and city names
+const NAME_LANGUAGE: &str = "en";
+
+/// A MaxMind-format database (GeoLite2-Country, GeoLite2-City or compatible)
+pub struct MmdbDatabase {
+    path: PathBuf,
+    reader: Reader<Vec<u8>>,
+}
+
+fn name(names: &Option<BTreeMap<&str, &str>>) -> Option<String> {
+    names
+        .as_ref()
+        .and_then(|names| names.get(NAME_LANGUAGE))
+        .map(|name| name.to_string())
+}
+
+impl MmdbDatabase {
+    /// Open a database file, reading it fully into memory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Err(Error::config(path, "database file not found"));
+        }
+        let reader = Reader::open_readfile(path)
+            .map_err(|e| Error::data_parse(path.display().to_string(), None, e))?;
+        Ok(MmdbDatabase {
+            path: path.to_path_buf(),
+            reader,
+        })
+    }
+
+    /// Database type from the metadata, e.g. "GeoLite2-City"
+    pub fn database_type(&self) -> &str {
+        &self.reader.metadata.database_type
+    }
+
+    /// File the database was loaded from
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The underlying reader, for decoding record types other than City
+    pub(crate) fn reader(&self) -> &Reader<Vec<u8>> {
+        &self.reader
+    }
+
+    /// Decode every network of one address family, in address order, and
+    /// pass its integer range and record to `visit`. IPv4 networks of an
+    /// IPv6 database are visited as IPv4 only; the aliases of the IPv4 tree
+    /// (::ffff:0:0/96, 2002::/16) are skipped.
+    pub(crate) fn for_each_network<'de, T: Deserialize<'de> + 'de>(
+        &'de self,
+        ipv4: bool,
+        mut visit: impl FnMut(u128, u128, T),
+    ) -> Result<(), Error> {
+        let data_error =
+            |e: MaxMindDBError| Error::data_parse(self.path.display().to_string(), None, e);
+        if !ipv4 && self.reader.metadata.ip_version != 6 {
+            return Ok(());
+        }
+
+        let root: IpNetwork = if ipv4 {
+            IpNetwork::V4(Ipv4Network::new(Ipv4Addr::UNSPECIFIED, 0).unwrap())
+        } else {
+            IpNetwork::V6(Ipv6Network::new(Ipv6Addr::UNSPECIFIED, 0).unwrap())
+        };
+        for item in self.reader.within::<T>(root).map_err(data_error)? {
+            let item = item.map_err(data_error)?;
+            let (start, end) = network_range(item.ip_net.network(), item.ip_net.prefix());
+            // The IPv4 subtree appears under ::/96 when walking IPv6
+            if !ipv4 && end <= u32::MAX as u128 {
+                continue;
+            }
+            visit(start, end, item.info);
+        }
+        Ok(())
+    }
+
+    /// Every network of one address family that has a country, in address order
+    pub fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        let mut entries = Vec::new();
+        self.for_each_network(ipv4, |start, end, record: geoip2::Country| {
+            if let Some(country) = record
+                .country
+                .and_then(|country| country.iso_code)
+                .and_then(parse_country_code)
+            {
+                entries.push(RangeEntry {
+                    start,
+                    end,
+                    country,
+                    registry: None,
+                });
+            }
+        })?;
+        Ok(entries)
+    }
+
+    /// Look up an address. Returns `None` when the database has no entry for it.
+    pub fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+        // City records are a superset of Country records, so this decodes both
+        let city: geoip2::City = match self.reader.lookup(ip) {
+            Ok(city) => city,
+            Err(MaxMindDBError::AddressNotFoundError(_)) => return Ok(None),
+            Err(e) => return Err(Error::data_parse(self.path.display().to_string(), None, e)),
+        };
+
+        let mut record = GeoRecord::default();
+        if let Some(country) = &city.country {
+            record.country_code = country.iso_code.map(str::to_string);
+            record.country_name = name(&country.names);
+        }
+        if let Some(continent) = &city.continent {
+            record.continent_code = continent.code.map(str::to_string);
+            record.continent_name = name(&continent.names);
+        }
+        if let Some(country) = &city.registered_country {
+            record.registered_country_code = country.iso_code.map(str::to_string);
+            record.registered_country_name = name(&country.names);
+        }
+        if let Some(city_record) = &city.city {
+            record.city = name(&city_record.names);
+        }
+        if let Some(subdivision) = city.subdivisions.as_ref().and_then(|s| s.first()) {
+            record.subdivision = name(&subdivision.names);
+        }
+        if let Some(location) = &city.location {
+            record.latitude = location.latitude;
+            record.longitude = location.longitude;
+        }
+
+        Ok(Some(record))
+    }
+}
+
+impl GeoProvider for MmdbDatabase {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Mmdb
+    }
+
+    fn source(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+        MmdbDatabase::lookup(self, ip)
+    }
+
+    fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        MmdbDatabase::ranges(self, ipv4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/testdata/GeoLite2-City-Test.mmdb"
+    );
+
+    #[test]
+    fn test_city_lookup() {
+        let db = MmdbDatabase::open(FIXTURE).unwrap();
+        assert_eq!(db.database_type(), "GeoLite2-City");
+
+        let record = db.lookup("1.0.16.1".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+        assert_eq!(record.country_name.as_deref(), Some("Japan"));
+        assert_eq!(record.continent_code.as_deref(), Some("AS"));
+        assert_eq!(record.city.as_deref(), Some("Tokyo"));
+        assert_eq!(record.subdivision.as_deref(), Some("Tokyo"));
+        assert_eq!(record.latitude, Some(35.6895));
+    }
+
+    #[test]
+    fn test_country_only_lookup() {
+        let db = MmdbDatabase::open(FIXTURE).unwrap();
+
+        let record = db
+            .lookup("2.125.160.216".parse().unwrap())
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("GB"));
+        assert_eq!(record.registered_country_code.as_deref(), Some("FR"));
+        assert_eq!(record.city, None);
+        assert_eq!(record.latitude, None);
+
+        let record = db.lookup("2001:218::1".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+    }
+
+    #[test]
+    fn test_ranges() {
+        let db = MmdbDatabase::open(FIXTURE).unwrap();
+        let ipv4: Vec<(String, String)> = db
+            .ranges(true)
+            .unwrap()
+            .iter()
+            .map(|entry| {
+                (
+                    crate::ip_utils::value_address(entry.start, true).to_string(),
+                    entry.country_code(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ipv4,
+            [
+                ("1.0.16.0".to_string(), "JP".to_string()),
+                ("2.125.160.0".to_string(), "GB".to_string())
+            ]
+        );
+
+        let ipv6 = db.ranges(false).unwrap();
+        assert_eq!(ipv6.len(), 1);
+        assert_eq!(
+            crate::ip_utils::value_address(ipv6[0].start, false).to_string(),
+            "2001:218::"
+        );
+    }
+
+    #[test]
+    fn test_lookup_not_found() {
+        let db = MmdbDatabase::open(FIXTURE).unwrap();
+        assert_eq!(db.lookup("8.8.8.8".parse().unwrap()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_open_errors() {
+        let err = MmdbDatabase::open("missing.mmdb").err().unwrap();
+        assert!(matches!(err, Error::Config { .. }));
+
+        let err = MmdbDatabase::open(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml"))
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::DataParse { .. }));
+    }
+}
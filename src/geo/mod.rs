@@ -0,0 +1,39 @@
+use serde::Serialize;
+
+mod csv_import;
+mod geofeed;
+mod iso3166;
+mod mmdb;
+mod provider;
+mod range_table;
+mod rir;
+
+pub use csv_import::{CsvTable, load_csv};
+pub use geofeed::{
+    Geofeed, GeofeedEntry, GeofeedIssue, GeofeedTable, IssueSeverity, parse_geofeed,
+};
+pub use iso3166::is_country_code;
+pub use mmdb::MmdbDatabase;
+pub use provider::{GeoAnswer, GeoChain, GeoProvider, ProviderKind};
+pub use range_table::{RangeEntry, RangeTable, RangeTableBuilder, parse_country_code};
+pub use rir::{Registry, RirTable, load_delegated};
+
+/// Geolocation result for a single address. Fields the data source does not
+/// provide are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct GeoRecord {
+    /// ISO 3166-1 alpha-2 code
+    pub country_code: Option<String>,
+    pub country_name: Option<String>,
+    pub continent_code: Option<String>,
+    pub continent_name: Option<String>,
+    /// Country in which the block is registered, which may differ from where it is used
+    pub registered_country_code: Option<String>,
+    pub registered_country_name: Option<String>,
+    pub city: Option<String>,
+    pub subdivision: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    /// Regional Internet Registry that allocated the block (RIR-derived data only)
+    pub registry: Option<String>,
+}
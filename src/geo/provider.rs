@@ -0,0 +1,269 @@
+use super::GeoRecord;
+use super::range_table::RangeEntry;
+use crate::error::Error;
+use crate::ip_utils::{merge_ranges, subtract_ranges};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::IpAddr;
+
+/// Kind of geolocation data source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderKind {
+    /// RFC 8805 geofeeds published by address holders
+    Geofeed,
+    /// MaxMind-format database
+    Mmdb,
+    /// Range table built from RIR delegated statistics
+    Rir,
+    /// IP2Location LITE or DB-IP style CSV exports
+    Csv,
+}
+
+impl ProviderKind {
+    /// Chain order used when none is configured: the address holders' own
+    /// geofeeds, then the most detailed data first
+    pub const DEFAULT_ORDER: [ProviderKind; 4] = [
+        ProviderKind::Geofeed,
+        ProviderKind::Mmdb,
+        ProviderKind::Rir,
+        ProviderKind::Csv,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProviderKind::Geofeed => "geofeed",
+            ProviderKind::Mmdb => "mmdb",
+            ProviderKind::Rir => "rir",
+            ProviderKind::Csv => "csv",
+        }
+    }
+}
+
+impl fmt::Display for ProviderKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A source of geolocation data
+pub trait GeoProvider: Send + Sync {
+    fn kind(&self) -> ProviderKind;
+
+    /// File (or files) the data was loaded from, for reports
+    fn source(&self) -> String;
+
+    /// Look up an address. Returns `None` when the provider has no entry for it.
+    fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error>;
+
+    /// All ranges of one address family that have a country, sorted by
+    /// start address and not overlapping
+    fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error>;
+}
+
+/// A lookup result together with the provider that supplied it
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoAnswer {
+    pub record: GeoRecord,
+    pub provider: ProviderKind,
+    pub source: String,
+}
+
+/// Ordered list of providers; the first one that knows the country answers
+#[derive(Default)]
+pub struct GeoChain {
+    providers: Vec<Box<dyn GeoProvider>>,
+}
+
+impl GeoChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a provider to the end of the chain
+    pub fn push(&mut self, provider: Box<dyn GeoProvider>) {
+        self.providers.push(provider);
+    }
+
+    pub fn providers(&self) -> &[Box<dyn GeoProvider>] {
+        &self.providers
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Ask each provider in turn. A record without a country code (e.g. an
+    /// MMDB entry with only a continent) does not stop the search; errors do.
+    pub fn lookup(&self, ip: IpAddr) -> Result<Option<GeoAnswer>, Error> {
+        for provider in &self.providers {
+            if let Some(record) = provider.lookup(ip)?
+                && record.country_code.is_some()
+            {
+                return Ok(Some(GeoAnswer {
+                    record,
+                    provider: provider.kind(),
+                    source: provider.source(),
+                }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Ranges attributed to the given countries, as `lookup` would answer:
+    /// an address covered by an earlier provider is never attributed by a
+    /// later one. Adjacent ranges of the same country are merged.
+    pub fn country_ranges(
+        &self,
+        countries: &[[u8; 2]],
+        ipv4: bool,
+    ) -> Result<Vec<RangeEntry>, Error> {
+        let mut selected: BTreeMap<[u8; 2], Vec<(u128, u128)>> = BTreeMap::new();
+        let mut claimed: Vec<(u128, u128)> = Vec::new();
+
+        for provider in &self.providers {
+            let ranges = provider.ranges(ipv4)?;
+            for country in countries {
+                let own = merge_ranges(
+                    ranges
+                        .iter()
+                        .filter(|entry| entry.country == *country)
+                        .map(|entry| (entry.start, entry.end))
+                        .collect(),
+                );
+                selected
+                    .entry(*country)
+                    .or_default()
+                    .extend(subtract_ranges(&own, &claimed));
+            }
+            claimed.extend(ranges.iter().map(|entry| (entry.start, entry.end)));
+            claimed = merge_ranges(claimed);
+        }
+
+        let mut entries: Vec<RangeEntry> = selected
+            .into_iter()
+            .flat_map(|(country, ranges)| {
+                merge_ranges(ranges)
+                    .into_iter()
+                    .map(move |(start, end)| RangeEntry {
+                        start,
+                        end,
+                        country,
+                        registry: None,
+                    })
+            })
+            .collect();
+        entries.sort_by_key(|entry| entry.start);
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Stub {
+        kind: ProviderKind,
+        prefix: u8,
+        country: Option<&'static str>,
+    }
+
+    impl GeoProvider for Stub {
+        fn kind(&self) -> ProviderKind {
+            self.kind
+        }
+
+        fn source(&self) -> String {
+            format!("stub-{}", self.kind)
+        }
+
+        fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+            match ip {
+                IpAddr::V4(ipv4) if ipv4.octets()[0] == self.prefix => Ok(Some(GeoRecord {
+                    country_code: self.country.map(str::to_string),
+                    continent_code: Some("EU".to_string()),
+                    ..GeoRecord::default()
+                })),
+                _ => Ok(None),
+            }
+        }
+
+        fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+            let start = (self.prefix as u128) << 24;
+            Ok(match (ipv4, self.country) {
+                (true, Some(country)) => vec![RangeEntry {
+                    start,
+                    end: start + 0xff_ffff,
+                    country: country.as_bytes().try_into().unwrap(),
+                    registry: None,
+                }],
+                _ => Vec::new(),
+            })
+        }
+    }
+
+    fn chain() -> GeoChain {
+        let mut chain = GeoChain::new();
+        chain.push(Box::new(Stub {
+            kind: ProviderKind::Mmdb,
+            prefix: 1,
+            country: Some("JP"),
+        }));
+        chain.push(Box::new(Stub {
+            kind: ProviderKind::Mmdb,
+            prefix: 3,
+            country: None,
+        }));
+        chain.push(Box::new(Stub {
+            kind: ProviderKind::Rir,
+            prefix: 3,
+            country: Some("DE"),
+        }));
+        chain
+    }
+
+    #[test]
+    fn test_first_provider_answers() {
+        let answer = chain().lookup("1.2.3.4".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(answer.provider, ProviderKind::Mmdb);
+        assert_eq!(answer.record.country_code.as_deref(), Some("JP"));
+        assert_eq!(answer.source, "stub-mmdb");
+    }
+
+    #[test]
+    fn test_falls_back_when_country_is_missing() {
+        let answer = chain().lookup("3.2.3.4".parse().unwrap()).unwrap().unwrap();
+        assert_eq!(answer.provider, ProviderKind::Rir);
+        assert_eq!(answer.record.country_code.as_deref(), Some("DE"));
+    }
+
+    #[test]
+    fn test_country_ranges_respect_chain_order() {
+        let mut chain = chain();
+        chain.push(Box::new(Stub {
+            kind: ProviderKind::Csv,
+            prefix: 1,
+            country: Some("DE"),
+        }));
+        let ranges = chain.country_ranges(&[*b"DE", *b"JP"], true).unwrap();
+        // 1.0.0.0/8 belongs to JP via the first provider; the CSV stub's DE
+        // entry for the same block is shadowed
+        let summary: Vec<(u8, String)> = ranges
+            .iter()
+            .map(|entry| ((entry.start >> 24) as u8, entry.country_code()))
+            .collect();
+        assert_eq!(summary, [(1, "JP".to_string()), (3, "DE".to_string())]);
+        assert!(chain.country_ranges(&[*b"JP"], false).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_no_provider_answers() {
+        assert_eq!(chain().lookup("9.9.9.9".parse().unwrap()).unwrap(), None);
+        assert_eq!(
+            GeoChain::new().lookup("1.2.3.4".parse().unwrap()).unwrap(),
+            None
+        );
+    }
+}
@@ -0,0 +1,461 @@
+use super::GeoRecord;
+use super::rir::Registry;
+use crate::error::Error;
+use crate::ip_utils::value_address;
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// File signature of a serialized range table
+const MAGIC: &[u8; 8] = b"IPCRANGE";
+/// Version of the on-disk format
+const FORMAT_VERSION: u8 = 1;
+
+/// A contiguous, inclusive address range attributed to one country
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeEntry {
+    pub start: u128,
+    pub end: u128,
+    /// ISO 3166-1 alpha-2 code, upper case
+    pub country: [u8; 2],
+    /// Regional Internet Registry that allocated the block, when known
+    pub registry: Option<Registry>,
+}
+
+impl RangeEntry {
+    pub fn country_code(&self) -> String {
+        String::from_utf8_lossy(&self.country).into_owned()
+    }
+
+    fn same_value(&self, other: &RangeEntry) -> bool {
+        self.country == other.country && self.registry == other.registry
+    }
+}
+
+/// Sorted, non-overlapping address ranges for IPv4 and IPv6 with binary
+/// search lookup
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RangeTable {
+    ipv4: Vec<RangeEntry>,
+    ipv6: Vec<RangeEntry>,
+    /// Country names supplied by the data source. Not part of the on-disk format.
+    country_names: BTreeMap<[u8; 2], String>,
+}
+
+/// Where a pending range came from, for error messages
+#[derive(Debug, Clone)]
+struct Origin {
+    source: String,
+    line: usize,
+}
+
+/// Collects ranges from one or more data sources and builds a `RangeTable`,
+/// reporting overlapping ranges instead of silently picking one
+#[derive(Debug, Default)]
+pub struct RangeTableBuilder {
+    ipv4: Vec<(RangeEntry, Origin)>,
+    ipv6: Vec<(RangeEntry, Origin)>,
+    country_names: BTreeMap<[u8; 2], String>,
+}
+
+/// Parse a two letter country code into its upper case bytes
+pub fn parse_country_code(code: &str) -> Option<[u8; 2]> {
+    match code.as_bytes() {
+        [a, b] if a.is_ascii_alphabetic() && b.is_ascii_alphabetic() => {
+            Some([a.to_ascii_uppercase(), b.to_ascii_uppercase()])
+        }
+        _ => None,
+    }
+}
+
+impl RangeTableBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the inclusive range `start..=end`. `source` and `line` identify
+    /// the record in error messages.
+    pub fn push(
+        &mut self,
+        start: IpAddr,
+        end: IpAddr,
+        country: [u8; 2],
+        registry: Option<Registry>,
+        source: &str,
+        line: usize,
+    ) -> Result<(), Error> {
+        let (start_value, end_value, ranges) = match (start, end) {
+            (IpAddr::V4(start), IpAddr::V4(end)) => (
+                u32::from(start) as u128,
+                u32::from(end) as u128,
+                &mut self.ipv4,
+            ),
+            (IpAddr::V6(start), IpAddr::V6(end)) => {
+                (u128::from(start), u128::from(end), &mut self.ipv6)
+            }
+            _ => {
+                return Err(Error::data_parse(
+                    source,
+                    Some(line),
+                    format!("range {} - {} mixes IPv4 and IPv6", start, end),
+                ));
+            }
+        };
+
+        if start_value > end_value {
+            return Err(Error::data_parse(
+                source,
+                Some(line),
+                format!("range start {} is after range end {}", start, end),
+            ));
+        }
+
+        ranges.push((
+            RangeEntry {
+                start: start_value,
+                end: end_value,
+                country,
+                registry,
+            },
+            Origin {
+                source: source.to_string(),
+                line,
+            },
+        ));
+        Ok(())
+    }
+
+    /// Record the display name of a country code, for sources that provide one
+    pub fn set_country_name(&mut self, country: [u8; 2], name: &str) {
+        self.country_names
+            .entry(country)
+            .or_insert_with(|| name.to_string());
+    }
+
+    /// Sort the ranges, reject overlaps and merge adjacent ranges with the same value
+    pub fn build(self) -> Result<RangeTable, Error> {
+        Ok(RangeTable {
+            ipv4: Self::finish(self.ipv4, true)?,
+            ipv6: Self::finish(self.ipv6, false)?,
+            country_names: self.country_names,
+        })
+    }
+
+    fn finish(mut ranges: Vec<(RangeEntry, Origin)>, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        ranges.sort_by_key(|(entry, _)| (entry.start, entry.end));
+
+        let mut merged: Vec<RangeEntry> = Vec::with_capacity(ranges.len());
+        for index in 0..ranges.len() {
+            let (entry, origin) = &ranges[index];
+            if index > 0 {
+                let (previous, previous_origin) = &ranges[index - 1];
+                if entry.start <= previous.end {
+                    return Err(Error::data_parse(
+                        origin.source.clone(),
+                        Some(origin.line),
+                        format!(
+                            "range {} - {} overlaps {} - {} ({} line {})",
+                            value_address(entry.start, ipv4),
+                            value_address(entry.end, ipv4),
+                            value_address(previous.start, ipv4),
+                            value_address(previous.end, ipv4),
+                            previous_origin.source,
+                            previous_origin.line
+                        ),
+                    ));
+                }
+            }
+
+            match merged.last_mut() {
+                Some(last)
+                    if last.end.checked_add(1) == Some(entry.start) && last.same_value(entry) =>
+                {
+                    last.end = entry.end;
+                }
+                _ => merged.push(*entry),
+            }
+        }
+
+        Ok(merged)
+    }
+}
+
+fn read_bytes<'a>(data: &mut &'a [u8], count: usize, path: &Path) -> Result<&'a [u8], Error> {
+    if data.len() < count {
+        return Err(Error::data_parse(
+            path.display().to_string(),
+            None,
+            "unexpected end of range table",
+        ));
+    }
+    let (bytes, rest) = data.split_at(count);
+    *data = rest;
+    Ok(bytes)
+}
+
+impl RangeTable {
+    /// Number of IPv4 and IPv6 ranges
+    pub fn len(&self) -> (usize, usize) {
+        (self.ipv4.len(), self.ipv6.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ipv4.is_empty() && self.ipv6.is_empty()
+    }
+
+    /// All ranges of one address family, sorted by start address
+    pub fn entries(&self, ipv4: bool) -> &[RangeEntry] {
+        if ipv4 { &self.ipv4 } else { &self.ipv6 }
+    }
+
+    /// Find the range containing an address
+    pub fn find(&self, ip: IpAddr) -> Option<&RangeEntry> {
+        let (ranges, value) = match ip {
+            IpAddr::V4(ipv4) => (&self.ipv4, u32::from(ipv4) as u128),
+            IpAddr::V6(ipv6) => (&self.ipv6, u128::from(ipv6)),
+        };
+
+        let index = ranges.partition_point(|entry| entry.start <= value);
+        let entry = ranges.get(index.checked_sub(1)?)?;
+        (value <= entry.end).then_some(entry)
+    }
+
+    /// Look up an address. Returns `None` when no range contains it.
+    pub fn lookup(&self, ip: IpAddr) -> Option<GeoRecord> {
+        self.find(ip).map(|entry| GeoRecord {
+            country_code: Some(entry.country_code()),
+            country_name: self.country_names.get(&entry.country).cloned(),
+            registry: entry.registry.map(|registry| registry.to_string()),
+            ..GeoRecord::default()
+        })
+    }
+
+    /// Serialize the table in its compact binary format
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(17 + self.ipv4.len() * 11 + self.ipv6.len() * 35);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(FORMAT_VERSION);
+
+        bytes.extend_from_slice(&(self.ipv4.len() as u32).to_be_bytes());
+        for entry in &self.ipv4 {
+            bytes.extend_from_slice(&(entry.start as u32).to_be_bytes());
+            bytes.extend_from_slice(&(entry.end as u32).to_be_bytes());
+            bytes.extend_from_slice(&entry.country);
+            bytes.push(Registry::to_id(entry.registry));
+        }
+
+        bytes.extend_from_slice(&(self.ipv6.len() as u32).to_be_bytes());
+        for entry in &self.ipv6 {
+            bytes.extend_from_slice(&entry.start.to_be_bytes());
+            bytes.extend_from_slice(&entry.end.to_be_bytes());
+            bytes.extend_from_slice(&entry.country);
+            bytes.push(Registry::to_id(entry.registry));
+        }
+
+        bytes
+    }
+
+    /// Parse a table serialized with `to_bytes`; `path` is used in error messages
+    pub fn from_bytes(mut data: &[u8], path: &Path) -> Result<Self, Error> {
+        let invalid = |message: &str| Error::data_parse(path.display().to_string(), None, message);
+
+        if read_bytes(&mut data, MAGIC.len(), path)? != MAGIC {
+            return Err(invalid("not a range table (bad signature)"));
+        }
+        if read_bytes(&mut data, 1, path)?[0] != FORMAT_VERSION {
+            return Err(invalid("unsupported range table version"));
+        }
+
+        let mut table = RangeTable::default();
+        for (ranges, width) in [(&mut table.ipv4, 4), (&mut table.ipv6, 16)] {
+            let count = u32::from_be_bytes(read_bytes(&mut data, 4, path)?.try_into().unwrap());
+            for _ in 0..count {
+                let value = |data: &mut &[u8]| -> Result<u128, Error> {
+                    let bytes = read_bytes(data, width, path)?;
+                    Ok(bytes
+                        .iter()
+                        .fold(0u128, |acc, byte| (acc << 8) | *byte as u128))
+                };
+                let start = value(&mut data)?;
+                let end = value(&mut data)?;
+                let country = read_bytes(&mut data, 2, path)?;
+                let registry = Registry::from_id(read_bytes(&mut data, 1, path)?[0])
+                    .ok_or_else(|| invalid("unknown registry id"))?;
+                let entry = RangeEntry {
+                    start,
+                    end,
+                    country: [country[0], country[1]],
+                    registry,
+                };
+                if ranges
+                    .last()
+                    .is_some_and(|last: &RangeEntry| last.end >= start)
+                    || start > end
+                {
+                    return Err(invalid("ranges are not sorted"));
+                }
+                ranges.push(entry);
+            }
+        }
+
+        if !data.is_empty() {
+            return Err(invalid("trailing data after range table"));
+        }
+
+        Ok(table)
+    }
+
+    /// Write the table to a file
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        fs::write(path, self.to_bytes())
+            .map_err(|e| Error::invalid_input(path.display().to_string(), e.to_string()))
+    }
+
+    /// Read a table written by `save`
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|e| Error::config(path, e))?;
+        Self::from_bytes(&data, path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    fn sample() -> RangeTable {
+        let mut builder = RangeTableBuilder::new();
+        builder
+            .push(
+                ip("1.0.16.0"),
+                ip("1.0.31.255"),
+                *b"JP",
+                Some(Registry::Apnic),
+                "a",
+                1,
+            )
+            .unwrap();
+        builder
+            .push(
+                ip("1.0.0.0"),
+                ip("1.0.0.255"),
+                *b"AU",
+                Some(Registry::Apnic),
+                "a",
+                2,
+            )
+            .unwrap();
+        // Adjacent with the same value: merged with the JP range above
+        builder
+            .push(
+                ip("1.0.32.0"),
+                ip("1.0.63.255"),
+                *b"JP",
+                Some(Registry::Apnic),
+                "a",
+                3,
+            )
+            .unwrap();
+        builder
+            .push(
+                ip("2001:200::"),
+                ip("2001:200:ffff:ffff:ffff:ffff:ffff:ffff"),
+                *b"JP",
+                None,
+                "a",
+                4,
+            )
+            .unwrap();
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_lookup() {
+        let table = sample();
+        assert_eq!(table.len(), (2, 1));
+
+        let record = table.lookup(ip("1.0.40.1")).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+        assert_eq!(record.registry.as_deref(), Some("apnic"));
+
+        assert_eq!(
+            table.lookup(ip("1.0.0.0")).unwrap().country_code.as_deref(),
+            Some("AU")
+        );
+        assert!(table.lookup(ip("1.0.1.0")).is_none());
+        assert!(table.lookup(ip("0.255.255.255")).is_none());
+        assert!(table.lookup(ip("1.0.64.0")).is_none());
+
+        let record = table.lookup(ip("2001:200::1")).unwrap();
+        assert_eq!(record.registry, None);
+    }
+
+    #[test]
+    fn test_overlap_is_reported() {
+        let mut builder = RangeTableBuilder::new();
+        builder
+            .push(
+                ip("10.0.0.0"),
+                ip("10.0.0.255"),
+                *b"JP",
+                None,
+                "first.csv",
+                1,
+            )
+            .unwrap();
+        builder
+            .push(
+                ip("10.0.0.128"),
+                ip("10.0.1.0"),
+                *b"KR",
+                None,
+                "second.csv",
+                7,
+            )
+            .unwrap();
+        let err = builder.build().unwrap_err();
+        assert!(matches!(err, Error::DataParse { line: Some(7), .. }));
+        assert!(err.to_string().contains("first.csv line 1"));
+    }
+
+    #[test]
+    fn test_push_rejects_invalid_ranges() {
+        let mut builder = RangeTableBuilder::new();
+        assert!(
+            builder
+                .push(ip("10.0.0.1"), ip("10.0.0.0"), *b"JP", None, "x", 1)
+                .is_err()
+        );
+        assert!(
+            builder
+                .push(ip("10.0.0.1"), ip("::1"), *b"JP", None, "x", 2)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let table = sample();
+        let bytes = table.to_bytes();
+        assert_eq!(
+            RangeTable::from_bytes(&bytes, Path::new("t")).unwrap(),
+            table
+        );
+
+        assert!(RangeTable::from_bytes(&bytes[..bytes.len() - 1], Path::new("t")).is_err());
+        assert!(RangeTable::from_bytes(b"NOTATABLE", Path::new("t")).is_err());
+    }
+
+    #[test]
+    fn test_parse_country_code() {
+        assert_eq!(parse_country_code("jp"), Some(*b"JP"));
+        assert_eq!(parse_country_code("JPN"), None);
+        assert_eq!(parse_country_code("1A"), None);
+    }
+}
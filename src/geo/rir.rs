@@ -0,0 +1,275 @@
+use super::GeoRecord;
+use super::provider::{GeoProvider, ProviderKind};
+use super::range_table::{RangeEntry, RangeTable, RangeTableBuilder, parse_country_code};
+use crate::error::Error;
+use serde::Serialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::path::{Path, PathBuf};
+
+/// Regional Internet Registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Registry {
+    Afrinic,
+    Apnic,
+    Arin,
+    Lacnic,
+    RipeNcc,
+}
+
+impl Registry {
+    /// All five registries, in the order of their ids
+    pub const ALL: [Registry; 5] = [
+        Registry::Afrinic,
+        Registry::Apnic,
+        Registry::Arin,
+        Registry::Lacnic,
+        Registry::RipeNcc,
+    ];
+
+    /// Parse the registry name used in delegated statistics files
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "afrinic" => Some(Registry::Afrinic),
+            "apnic" => Some(Registry::Apnic),
+            "arin" => Some(Registry::Arin),
+            "lacnic" => Some(Registry::Lacnic),
+            "ripencc" | "ripe" => Some(Registry::RipeNcc),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Registry::Afrinic => "afrinic",
+            Registry::Apnic => "apnic",
+            Registry::Arin => "arin",
+            Registry::Lacnic => "lacnic",
+            Registry::RipeNcc => "ripencc",
+        }
+    }
+
+    /// Compact id used by the range table format (0 means unknown)
+    pub(crate) fn to_id(registry: Option<Registry>) -> u8 {
+        match registry {
+            None => 0,
+            Some(registry) => Self::ALL.iter().position(|r| *r == registry).unwrap() as u8 + 1,
+        }
+    }
+
+    /// Inverse of `to_id`; `None` for an id that is out of range
+    pub(crate) fn from_id(id: u8) -> Option<Option<Registry>> {
+        match id {
+            0 => Some(None),
+            id => Self::ALL
+                .get(id as usize - 1)
+                .map(|registry| Some(*registry)),
+        }
+    }
+}
+
+impl fmt::Display for Registry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Add the IPv4 and IPv6 allocations of a `delegated-<rir>-extended` statistics
+/// file to `builder`. Returns the registry named in the file and the number of
+/// records added.
+///
+/// IPv4 records hold a start address and an address count, which is not
+/// always a power of two; IPv6 records hold a prefix length. Header, summary,
+/// ASN and unallocated (available/reserved) records are skipped.
+pub fn load_delegated(
+    content: &str,
+    source: &str,
+    builder: &mut RangeTableBuilder,
+) -> Result<(Option<Registry>, usize), Error> {
+    let mut file_registry = None;
+    let mut count = 0;
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split('|').collect();
+        let parse_error = |message: String| Error::data_parse(source, Some(line_number), message);
+
+        // Version line: version|registry|serial|records|startdate|enddate|UTCoffset
+        if fields[0].parse::<f32>().is_ok() {
+            if let Some(name) = fields.get(1) {
+                file_registry = Registry::from_name(name);
+            }
+            continue;
+        }
+        // Summary line: registry|*|type|*|count|summary
+        if fields.get(5) == Some(&"summary") {
+            continue;
+        }
+        if fields.len() < 7 {
+            return Err(parse_error(format!(
+                "expected at least 7 fields, found {}",
+                fields.len()
+            )));
+        }
+
+        let (registry, country, kind, start, value, status) = (
+            fields[0], fields[1], fields[2], fields[3], fields[4], fields[6],
+        );
+        if kind != "ipv4" && kind != "ipv6" {
+            continue;
+        }
+        if status != "allocated" && status != "assigned" {
+            continue;
+        }
+
+        let registry = Registry::from_name(registry)
+            .ok_or_else(|| parse_error(format!("unknown registry '{}'", registry)))?;
+        let country = parse_country_code(country)
+            .ok_or_else(|| parse_error(format!("invalid country code '{}'", country)))?;
+
+        let (first, last) = if kind == "ipv4" {
+            let start: Ipv4Addr = start
+                .parse()
+                .map_err(|_| parse_error(format!("invalid IPv4 address '{}'", start)))?;
+            let count: u32 = value
+                .parse()
+                .ok()
+                .filter(|count| *count > 0)
+                .ok_or_else(|| parse_error(format!("invalid address count '{}'", value)))?;
+            let end = u32::from(start)
+                .checked_add(count - 1)
+                .ok_or_else(|| parse_error(format!("range {}+{} overflows", start, count)))?;
+            (IpAddr::V4(start), IpAddr::V4(Ipv4Addr::from(end)))
+        } else {
+            let start: Ipv6Addr = start
+                .parse()
+                .map_err(|_| parse_error(format!("invalid IPv6 address '{}'", start)))?;
+            let prefix_len: u8 = value
+                .parse()
+                .ok()
+                .filter(|prefix_len| *prefix_len <= 128)
+                .ok_or_else(|| parse_error(format!("invalid prefix length '{}'", value)))?;
+            let host_mask = if prefix_len == 0 {
+                u128::MAX
+            } else {
+                u128::MAX >> prefix_len
+            };
+            let end = u128::from(start) | host_mask;
+            (IpAddr::V6(start), IpAddr::V6(Ipv6Addr::from(end)))
+        };
+
+        builder.push(first, last, country, Some(registry), source, line_number)?;
+        count += 1;
+    }
+
+    Ok((file_registry, count))
+}
+
+/// A range table built from delegated statistics with `cc build-db`
+pub struct RirTable {
+    path: PathBuf,
+    table: RangeTable,
+}
+
+impl RirTable {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        Ok(RirTable {
+            path: path.to_path_buf(),
+            table: RangeTable::open(path)?,
+        })
+    }
+
+    pub fn table(&self) -> &RangeTable {
+        &self.table
+    }
+}
+
+impl GeoProvider for RirTable {
+    fn kind(&self) -> ProviderKind {
+        ProviderKind::Rir
+    }
+
+    fn source(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Option<GeoRecord>, Error> {
+        Ok(self.table.lookup(ip))
+    }
+
+    fn ranges(&self, ipv4: bool) -> Result<Vec<RangeEntry>, Error> {
+        Ok(self.table.entries(ipv4).to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const APNIC_SAMPLE: &str = "\
+2|apnic|20240101|5|19830613|20231231|+1000
+apnic|*|asn|*|1|summary
+apnic|*|ipv4|*|3|summary
+apnic|*|ipv6|*|1|summary
+apnic|JP|asn|2497|1|19970424|allocated|A91A7381
+apnic|JP|ipv4|1.0.16.0|4096|20110412|allocated|A92319D5
+apnic|AU|ipv4|1.0.0.0|256|20110811|assigned|A91872ED
+apnic|JP|ipv4|1.1.64.0|768|20110412|allocated|A92319D5
+apnic||ipv4|1.2.0.0|256||available|
+apnic|JP|ipv6|2001:200::|35|19990813|allocated|A91A7381
+";
+
+    #[test]
+    fn test_load_delegated() {
+        let mut builder = RangeTableBuilder::new();
+        let (registry, count) =
+            load_delegated(APNIC_SAMPLE, "delegated-apnic-extended", &mut builder).unwrap();
+        assert_eq!(registry, Some(Registry::Apnic));
+        assert_eq!(count, 4);
+
+        let table = builder.build().unwrap();
+        let record = table.lookup("1.0.31.255".parse().unwrap()).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+        assert_eq!(record.registry.as_deref(), Some("apnic"));
+
+        // 768 addresses: not CIDR aligned, ends at 1.1.66.255
+        assert!(table.lookup("1.1.66.255".parse().unwrap()).is_some());
+        assert!(table.lookup("1.1.67.0".parse().unwrap()).is_none());
+        assert!(table.lookup("1.2.0.1".parse().unwrap()).is_none());
+
+        let record = table.lookup("2001:200:1fff::1".parse().unwrap()).unwrap();
+        assert_eq!(record.country_code.as_deref(), Some("JP"));
+        assert!(table.lookup("2001:200:2000::".parse().unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_load_delegated_reports_line() {
+        let content = "apnic|JP|ipv4|1.0.16.0|0|20110412|allocated\n";
+        let mut builder = RangeTableBuilder::new();
+        let err = load_delegated(content, "delegated-apnic", &mut builder).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse delegated-apnic at line 1: invalid address count '0'"
+        );
+    }
+
+    #[test]
+    fn test_registry_ids_round_trip() {
+        for registry in Registry::ALL {
+            assert_eq!(
+                Registry::from_id(Registry::to_id(Some(registry))),
+                Some(Some(registry))
+            );
+            assert_eq!(Registry::from_name(registry.as_str()), Some(registry));
+        }
+        assert_eq!(Registry::from_id(0), Some(None));
+        assert_eq!(Registry::from_id(9), None);
+    }
+}
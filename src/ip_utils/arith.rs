@@ -0,0 +1,183 @@
+use super::ranges::{address_value, network_range, value_address};
+use std::net::IpAddr;
+
+fn family_bits(ipv4: bool) -> u32 {
+    if ipv4 { 32 } else { 128 }
+}
+
+fn last_value(ipv4: bool) -> u128 {
+    if ipv4 { u32::MAX as u128 } else { u128::MAX }
+}
+
+/// `ip` moved by `offset` addresses, or `None` when that runs past the
+/// first or last address of its family
+pub fn offset_address(ip: IpAddr, offset: i128) -> Option<IpAddr> {
+    let (ipv4, value) = address_value(ip);
+    let value = value.checked_add_signed(offset)?;
+    (value <= last_value(ipv4)).then(|| value_address(value, ipv4))
+}
+
+/// The address after `ip`, unless `ip` is the last of its family
+pub fn next_address(ip: IpAddr) -> Option<IpAddr> {
+    offset_address(ip, 1)
+}
+
+/// The address before `ip`, unless `ip` is the first of its family
+pub fn previous_address(ip: IpAddr) -> Option<IpAddr> {
+    offset_address(ip, -1)
+}
+
+/// First and last usable host of a network as integers. IPv4 networks
+/// leave out the network and broadcast addresses except for /31 (RFC 3021)
+/// and /32; IPv6 networks leave out the subnet-router anycast address
+/// except for /127 (RFC 6164) and /128.
+pub fn host_range(ip: IpAddr, prefix_len: u8) -> (u128, u128) {
+    let (start, end) = network_range(ip, prefix_len);
+    let ipv4 = ip.is_ipv4();
+    if prefix_len as u32 + 1 >= family_bits(ipv4) {
+        (start, end)
+    } else if ipv4 {
+        (start + 1, end - 1)
+    } else {
+        (start + 1, end)
+    }
+}
+
+/// The `n`th host of a network (see `host_range`), counting from 1, or
+/// from the last host when `n` is negative (-1 is the last host)
+pub fn nth_host(ip: IpAddr, prefix_len: u8, n: i128) -> Option<IpAddr> {
+    let (first, last) = host_range(ip, prefix_len);
+    let index = match n {
+        0 => return None,
+        1.. => first.checked_add(n as u128 - 1)?,
+        _ => last.checked_sub(n.unsigned_abs() - 1)?,
+    };
+    (first..=last)
+        .contains(&index)
+        .then(|| value_address(index, ip.is_ipv4()))
+}
+
+/// Number of addresses from `from` to `to` (0 for the same address), or
+/// `None` when they are of different families
+pub fn address_distance(from: IpAddr, to: IpAddr) -> Option<u128> {
+    let (ipv4, from) = address_value(from);
+    let (to_ipv4, to) = address_value(to);
+    (ipv4 == to_ipv4).then(|| from.abs_diff(to))
+}
+
+/// Number of leading bits two addresses share, or `None` when they are of
+/// different families
+pub fn common_prefix_len(a: IpAddr, b: IpAddr) -> Option<u8> {
+    let (ipv4, a) = address_value(a);
+    let (b_ipv4, b) = address_value(b);
+    if ipv4 != b_ipv4 {
+        return None;
+    }
+    // IPv4 values sit in the low 32 bits
+    let leading = (a ^ b).leading_zeros() - (128 - family_bits(ipv4));
+    Some(leading.min(family_bits(ipv4)) as u8)
+}
+
+/// Position of a subnet among the subnets of its length in `parent`,
+/// counting from 0 (192.0.2.64/26 is subnet 1 of 192.0.2.0/24), or `None`
+/// when the subnet is not inside the parent
+pub fn subnet_index(ip: IpAddr, prefix_len: u8, parent: IpAddr, parent_len: u8) -> Option<u128> {
+    if ip.is_ipv4() != parent.is_ipv4() || prefix_len < parent_len {
+        return None;
+    }
+    let (start, _) = network_range(ip, prefix_len);
+    let (parent_start, parent_end) = network_range(parent, parent_len);
+    if !(parent_start..=parent_end).contains(&start) {
+        return None;
+    }
+    let host_bits = family_bits(ip.is_ipv4()) - prefix_len as u32;
+    Some((start - parent_start).checked_shr(host_bits).unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_offset_address() {
+        assert_eq!(offset_address(ip("192.0.2.250"), 10), Some(ip("192.0.3.4")));
+        assert_eq!(offset_address(ip("192.0.2.1"), -2), Some(ip("192.0.1.255")));
+        assert_eq!(next_address(ip("255.255.255.255")), None);
+        assert_eq!(previous_address(ip("0.0.0.0")), None);
+        assert_eq!(
+            next_address(ip("2001:db8::ffff")),
+            Some(ip("2001:db8::1:0"))
+        );
+        assert_eq!(
+            next_address(ip("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")),
+            None
+        );
+        assert_eq!(offset_address(ip("::1"), i128::MIN), None);
+    }
+
+    #[test]
+    fn test_nth_host() {
+        let network = ip("192.0.2.0");
+        assert_eq!(nth_host(network, 24, 1), Some(ip("192.0.2.1")));
+        assert_eq!(nth_host(network, 24, -1), Some(ip("192.0.2.254")));
+        assert_eq!(nth_host(network, 24, 254), Some(ip("192.0.2.254")));
+        assert_eq!(nth_host(network, 24, 255), None);
+        assert_eq!(nth_host(network, 24, -255), None);
+        assert_eq!(nth_host(network, 24, 0), None);
+        assert_eq!(nth_host(network, 31, 2), Some(ip("192.0.2.1")));
+        assert_eq!(nth_host(network, 32, 1), Some(network));
+        assert_eq!(nth_host(ip("2001:db8::"), 64, 1), Some(ip("2001:db8::1")));
+        assert_eq!(
+            nth_host(ip("2001:db8::"), 64, -1),
+            Some(ip("2001:db8::ffff:ffff:ffff:ffff"))
+        );
+        assert_eq!(
+            nth_host(ip("::"), 0, -1),
+            Some(ip("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"))
+        );
+    }
+
+    #[test]
+    fn test_distance_and_common_prefix() {
+        assert_eq!(address_distance(ip("10.0.0.1"), ip("10.0.1.0")), Some(255));
+        assert_eq!(address_distance(ip("10.0.1.0"), ip("10.0.0.1")), Some(255));
+        assert_eq!(address_distance(ip("10.0.0.1"), ip("::1")), None);
+        assert_eq!(
+            address_distance(ip("::"), ip("ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff")),
+            Some(u128::MAX)
+        );
+
+        assert_eq!(
+            common_prefix_len(ip("192.0.2.1"), ip("192.0.2.200")),
+            Some(24)
+        );
+        assert_eq!(common_prefix_len(ip("10.0.0.1"), ip("10.0.0.1")), Some(32));
+        assert_eq!(common_prefix_len(ip("0.0.0.0"), ip("128.0.0.0")), Some(0));
+        assert_eq!(
+            common_prefix_len(ip("2001:db8::1"), ip("2001:db9::1")),
+            Some(31)
+        );
+        assert_eq!(common_prefix_len(ip("::1"), ip("::1")), Some(128));
+        assert_eq!(common_prefix_len(ip("::1"), ip("0.0.0.1")), None);
+    }
+
+    #[test]
+    fn test_subnet_index() {
+        let parent = ip("192.0.2.0");
+        assert_eq!(subnet_index(ip("192.0.2.64"), 26, parent, 24), Some(1));
+        assert_eq!(subnet_index(ip("192.0.2.255"), 32, parent, 24), Some(255));
+        assert_eq!(subnet_index(ip("192.0.2.0"), 24, parent, 24), Some(0));
+        assert_eq!(subnet_index(ip("192.0.3.0"), 26, parent, 24), None);
+        assert_eq!(subnet_index(ip("192.0.0.0"), 16, parent, 24), None);
+        assert_eq!(
+            subnet_index(ip("2001:db8:0:ff::"), 64, ip("2001:db8::"), 48),
+            Some(255)
+        );
+        assert_eq!(subnet_index(ip("::"), 0, ip("::"), 0), Some(0));
+        assert_eq!(subnet_index(ip("::"), 64, parent, 24), None);
+    }
+}
@@ -0,0 +1,203 @@
+use super::IpParseError;
+use super::ranges::{IpFamily, address_value, value_address};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Text forms of one address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AddressForms {
+    /// Unsigned integer in decimal
+    pub integer: String,
+    /// "0x" and 8 (IPv4) or 32 (IPv6) hex digits
+    pub hex: String,
+    /// Eight binary digits per octet, separated by dots
+    pub binary: String,
+    /// All eight IPv6 groups, zero padded; for IPv4, the IPv4-mapped form
+    pub expanded: String,
+    /// RFC 5952 text form
+    pub canonical: String,
+    /// ::ffff:a.b.c.d form of an IPv4 address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv4_mapped: Option<String>,
+    /// Deprecated ::a.b.c.d form of an IPv4 address (RFC 4291, section 2.5.5.1)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv4_compatible: Option<String>,
+    /// IPv4 address carried by an IPv4-mapped or IPv4-compatible IPv6 address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub embedded_ipv4: Option<Ipv4Addr>,
+}
+
+/// Render every text form of an address
+pub fn address_forms(ip: IpAddr) -> AddressForms {
+    let (_, value) = address_value(ip);
+    let bytes: Vec<u8> = match ip {
+        IpAddr::V4(ipv4) => ipv4.octets().to_vec(),
+        IpAddr::V6(ipv6) => ipv6.octets().to_vec(),
+    };
+    let binary = bytes
+        .iter()
+        .map(|byte| format!("{:08b}", byte))
+        .collect::<Vec<_>>()
+        .join(".");
+
+    match ip {
+        IpAddr::V4(address) => {
+            let mapped = address.to_ipv6_mapped();
+            AddressForms {
+                integer: value.to_string(),
+                hex: format!("0x{:08x}", value),
+                binary,
+                expanded: expanded_ipv6(mapped),
+                canonical: address.to_string(),
+                ipv4_mapped: Some(format!("::ffff:{}", address)),
+                ipv4_compatible: Some(format!("::{}", address)),
+                embedded_ipv4: None,
+            }
+        }
+        IpAddr::V6(address) => AddressForms {
+            integer: value.to_string(),
+            hex: format!("0x{:032x}", value),
+            binary,
+            expanded: expanded_ipv6(address),
+            canonical: address.to_string(),
+            ipv4_mapped: None,
+            ipv4_compatible: None,
+            embedded_ipv4: embedded_ipv4(address),
+        },
+    }
+}
+
+/// All eight groups, zero padded
+fn expanded_ipv6(ip: Ipv6Addr) -> String {
+    ip.segments()
+        .iter()
+        .map(|segment| format!("{:04x}", segment))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// IPv4 address inside an IPv4-mapped (::ffff:0:0/96) or IPv4-compatible
+/// (::/96, except :: and ::1) address
+pub fn embedded_ipv4(ip: Ipv6Addr) -> Option<Ipv4Addr> {
+    let value = u128::from(ip);
+    match value >> 32 {
+        0xffff => Some(Ipv4Addr::from(value as u32)),
+        0 if value > 1 => Some(Ipv4Addr::from(value as u32)),
+        _ => None,
+    }
+}
+
+/// Parse an address written as text, a decimal integer or "0x" hex.
+///
+/// Integers are IPv4 when `family` is `V4`, IPv6 when it is `V6`, and with
+/// `Both`, IPv4 when they fit in 32 bits (hex: at most 8 digits).
+pub fn parse_address_any(input: &str, family: IpFamily) -> Result<IpAddr, IpParseError> {
+    let invalid = || IpParseError::InvalidFormat(input.to_string());
+    let text = input.trim();
+
+    let (value, ipv4_sized) =
+        if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            let hex = hex.replace('_', "");
+            if hex.is_empty() || hex.len() > 32 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+                return Err(invalid());
+            }
+            let value = u128::from_str_radix(&hex, 16).map_err(|_| invalid())?;
+            (value, hex.len() <= 8)
+        } else if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+            let value = text.parse::<u128>().map_err(|_| invalid())?;
+            (value, value <= u32::MAX as u128)
+        } else {
+            return text.parse::<IpAddr>().map_err(|_| invalid());
+        };
+
+    let ipv4 = match family {
+        IpFamily::V4 => true,
+        IpFamily::V6 => false,
+        IpFamily::Both => ipv4_sized,
+    };
+    if ipv4 && value > u32::MAX as u128 {
+        return Err(invalid());
+    }
+    Ok(value_address(value, ipv4))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_ipv4_forms() {
+        let forms = address_forms(ip("192.0.2.1"));
+        assert_eq!(forms.integer, "3221225985");
+        assert_eq!(forms.hex, "0xc0000201");
+        assert_eq!(forms.binary, "11000000.00000000.00000010.00000001");
+        assert_eq!(forms.expanded, "0000:0000:0000:0000:0000:ffff:c000:0201");
+        assert_eq!(forms.ipv4_mapped.as_deref(), Some("::ffff:192.0.2.1"));
+        assert_eq!(forms.ipv4_compatible.as_deref(), Some("::192.0.2.1"));
+        assert_eq!(forms.embedded_ipv4, None);
+    }
+
+    #[test]
+    fn test_ipv6_forms() {
+        let forms = address_forms(ip("2001:0db8:0000:0000:0000:0000:0000:0001"));
+        assert_eq!(forms.canonical, "2001:db8::1");
+        assert_eq!(forms.expanded, "2001:0db8:0000:0000:0000:0000:0000:0001");
+        assert_eq!(forms.hex, "0x20010db8000000000000000000000001");
+        assert!(
+            forms
+                .binary
+                .starts_with("00100000.00000001.00001101.10111000.")
+        );
+        assert_eq!(forms.binary.split('.').count(), 16);
+        assert_eq!(forms.embedded_ipv4, None);
+
+        let forms = address_forms(ip("::ffff:c000:201"));
+        assert_eq!(forms.canonical, "::ffff:192.0.2.1");
+        assert_eq!(forms.embedded_ipv4, Some(Ipv4Addr::new(192, 0, 2, 1)));
+        assert_eq!(
+            address_forms(ip("::c000:201")).embedded_ipv4,
+            Some(Ipv4Addr::new(192, 0, 2, 1))
+        );
+        assert_eq!(address_forms(ip("::1")).embedded_ipv4, None);
+    }
+
+    #[test]
+    fn test_parse_address_any() {
+        assert_eq!(
+            parse_address_any("3221225985", IpFamily::Both).unwrap(),
+            ip("192.0.2.1")
+        );
+        assert_eq!(
+            parse_address_any("0xC0000201", IpFamily::Both).unwrap(),
+            ip("192.0.2.1")
+        );
+        assert_eq!(
+            parse_address_any("0x20010db8000000000000000000000001", IpFamily::Both).unwrap(),
+            ip("2001:db8::1")
+        );
+        assert_eq!(
+            parse_address_any("4294967296", IpFamily::Both).unwrap(),
+            ip("::1:0:0")
+        );
+        assert_eq!(parse_address_any("1", IpFamily::V6).unwrap(), ip("::1"));
+        assert_eq!(
+            parse_address_any("2001:db8::1", IpFamily::V4).unwrap(),
+            ip("2001:db8::1")
+        );
+
+        for invalid in ["4294967296", "0x", "0x1g", "12ab"] {
+            assert!(
+                parse_address_any(invalid, IpFamily::V4).is_err(),
+                "{}",
+                invalid
+            );
+        }
+        assert!(
+            parse_address_any("340282366920938463463374607431768211456", IpFamily::Both).is_err()
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use super::info::{IpInfo, get_ip_info};
+use super::{IpParseError, parse_ip_address};
+use serde::Serialize;
+use std::fmt;
+use std::net::IpAddr;
+
+/// An address as written in logs and socket APIs, with an optional zone
+/// (scope) ID and port: `[fe80::1%eth0]:8443`, `203.0.113.9:51234`,
+/// `fe80::1%2`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Endpoint {
+    pub ip: IpAddr,
+    /// Zone ID of an IPv6 address, e.g. "eth0" or "2"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl Endpoint {
+    pub fn new(ip: IpAddr) -> Self {
+        Endpoint {
+            ip,
+            zone: None,
+            port: None,
+        }
+    }
+
+    /// The address with its zone ID, without the port: "fe80::1%eth0"
+    pub fn address(&self) -> String {
+        match &self.zone {
+            Some(zone) => format!("{}%{}", self.ip, zone),
+            None => self.ip.to_string(),
+        }
+    }
+
+    /// Warning for a link-local address given without a zone ID, which is
+    /// ambiguous on a host with more than one interface
+    pub fn zone_warning(&self) -> Option<String> {
+        (self.zone.is_none() && needs_zone(self.ip)).then(|| {
+            format!(
+                "{} is link-local but has no zone ID (e.g. {}%eth0); the interface is ambiguous",
+                self.ip, self.ip
+            )
+        })
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.port, self.ip) {
+            (Some(port), IpAddr::V6(_)) => write!(f, "[{}]:{}", self.address(), port),
+            (Some(port), IpAddr::V4(_)) => write!(f, "{}:{}", self.address(), port),
+            (None, _) => f.write_str(&self.address()),
+        }
+    }
+}
+
+/// Whether an address is only meaningful together with an interface:
+/// IPv6 link-local unicast (fe80::/10) and interface- or link-local
+/// multicast (ffx1::/16, ffx2::/16)
+pub(crate) fn needs_zone(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V6(ipv6) => {
+            let first = ipv6.segments()[0];
+            first & 0xffc0 == 0xfe80 || (first & 0xff00 == 0xff00 && matches!(first & 0xf, 1 | 2))
+        }
+        IpAddr::V4(_) => false,
+    }
+}
+
+/// Address text with the port and zone ID split off, before the address
+/// itself is parsed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndpointParts<'a> {
+    pub host: &'a str,
+    pub zone: Option<&'a str>,
+    pub port: Option<u16>,
+}
+
+impl EndpointParts<'_> {
+    /// Attach the zone and port to the parsed address. Zone IDs are only
+    /// valid for IPv6.
+    pub fn with_ip(&self, ip: IpAddr, input: &str) -> Result<Endpoint, IpParseError> {
+        if self.zone.is_some() && ip.is_ipv4() {
+            return Err(IpParseError::InvalidFormat(input.to_string()));
+        }
+        Ok(Endpoint {
+            ip,
+            zone: self.zone.map(str::to_string),
+            port: self.port,
+        })
+    }
+}
+
+/// Split `[addr%zone]:port`, `addr:port` (one colon only, so IPv4 or a
+/// bare name) and `addr%zone` into their parts. An unbracketed IPv6 address
+/// never carries a port.
+pub fn split_endpoint(input: &str) -> Result<EndpointParts<'_>, IpParseError> {
+    let invalid = || IpParseError::InvalidFormat(input.to_string());
+    let text = input.trim();
+
+    let (address, port) = if let Some(bracketed) = text.strip_prefix('[') {
+        let (address, after) = bracketed.split_once(']').ok_or_else(invalid)?;
+        match after {
+            "" => (address, None),
+            _ => (address, Some(after.strip_prefix(':').ok_or_else(invalid)?)),
+        }
+    } else if text.matches(':').count() == 1 {
+        let (address, port) = text.split_once(':').ok_or_else(invalid)?;
+        (address, Some(port))
+    } else {
+        (text, None)
+    };
+
+    let port = port
+        .map(|port| {
+            if port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid());
+            }
+            port.parse::<u16>().map_err(|_| invalid())
+        })
+        .transpose()?;
+
+    let (host, zone) = match address.split_once('%') {
+        Some((host, zone)) => {
+            let valid = !zone.is_empty()
+                && zone
+                    .chars()
+                    .all(|c| !c.is_whitespace() && !matches!(c, '%' | '[' | ']' | '/'));
+            if !valid {
+                return Err(invalid());
+            }
+            (host, Some(zone))
+        }
+        None => (address, None),
+    };
+    if host.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(EndpointParts { host, zone, port })
+}
+
+/// Parse an address that may carry brackets, a zone ID and a port
+pub fn parse_endpoint(input: &str) -> Result<Endpoint, IpParseError> {
+    let parts = split_endpoint(input)?;
+    let ip =
+        parse_ip_address(parts.host).map_err(|_| IpParseError::InvalidFormat(input.to_string()))?;
+    parts.with_ip(ip, input)
+}
+
+/// Address details including the zone ID, port and any warnings about them
+pub fn get_endpoint_info(endpoint: &Endpoint) -> IpInfo {
+    let mut info = get_ip_info(&endpoint.ip);
+    info.zone = endpoint.zone.clone();
+    info.port = endpoint.port;
+    info.warnings.extend(endpoint.zone_warning());
+    info
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(input: &str) -> (String, Option<String>, Option<u16>) {
+        let endpoint = parse_endpoint(input).unwrap();
+        (endpoint.ip.to_string(), endpoint.zone, endpoint.port)
+    }
+
+    #[test]
+    fn test_parse_endpoint() {
+        let eth0 = Some("eth0".to_string());
+        assert_eq!(
+            endpoint("[fe80::1%eth0]:8443"),
+            ("fe80::1".to_string(), eth0.clone(), Some(8443))
+        );
+        assert_eq!(
+            endpoint("203.0.113.9:51234"),
+            ("203.0.113.9".to_string(), None, Some(51234))
+        );
+        assert_eq!(
+            endpoint("fe80::1%2"),
+            ("fe80::1".to_string(), Some("2".to_string()), None)
+        );
+        assert_eq!(
+            endpoint("[2001:db8::1]"),
+            ("2001:db8::1".to_string(), None, None)
+        );
+        assert_eq!(
+            endpoint(" 192.0.2.1 "),
+            ("192.0.2.1".to_string(), None, None)
+        );
+
+        for invalid in [
+            "192.0.2.1%eth0",
+            "192.0.2.1:",
+            "192.0.2.1:65536",
+            "[fe80::1",
+            "[fe80::1]8443",
+            "fe80::1%",
+            "[::1]:+80",
+            "2001:db8::1:80x",
+        ] {
+            assert!(parse_endpoint(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_endpoint_display_and_warnings() {
+        let endpoint = parse_endpoint("[FE80::1%eth0]:8443").unwrap();
+        assert_eq!(endpoint.to_string(), "[fe80::1%eth0]:8443");
+        assert_eq!(endpoint.zone_warning(), None);
+        assert_eq!(
+            parse_endpoint("203.0.113.9:80").unwrap().to_string(),
+            "203.0.113.9:80"
+        );
+
+        let info = get_endpoint_info(&parse_endpoint("fe80::1").unwrap());
+        assert_eq!(info.warnings.len(), 1);
+        assert!(info.warnings[0].starts_with("fe80::1 is link-local but has no zone ID"));
+        assert!(parse_endpoint("ff02::1").unwrap().zone_warning().is_some());
+        assert!(parse_endpoint("ff05::1").unwrap().zone_warning().is_none());
+        assert!(
+            parse_endpoint("169.254.1.1")
+                .unwrap()
+                .zone_warning()
+                .is_none()
+        );
+    }
+}
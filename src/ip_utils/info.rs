@@ -0,0 +1,457 @@
+use super::endpoint::Endpoint;
+use super::reverse::ptr_name;
+use serde::Serialize;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Address category, based on the IANA special-purpose address registries
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpCategory {
+    Unspecified,
+    Loopback,
+    Private,
+    SharedAddressSpace,
+    LinkLocal,
+    UniqueLocal,
+    Documentation,
+    Benchmarking,
+    Multicast,
+    Broadcast,
+    Ipv4Mapped,
+    Nat64,
+    #[serde(rename = "6to4")]
+    SixToFour,
+    Reserved,
+    Public,
+}
+
+impl IpCategory {
+    /// Stable identifier, as used in the machine-readable outputs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IpCategory::Unspecified => "unspecified",
+            IpCategory::Loopback => "loopback",
+            IpCategory::Private => "private",
+            IpCategory::SharedAddressSpace => "shared_address_space",
+            IpCategory::LinkLocal => "link_local",
+            IpCategory::UniqueLocal => "unique_local",
+            IpCategory::Documentation => "documentation",
+            IpCategory::Benchmarking => "benchmarking",
+            IpCategory::Multicast => "multicast",
+            IpCategory::Broadcast => "broadcast",
+            IpCategory::Ipv4Mapped => "ipv4_mapped",
+            IpCategory::Nat64 => "nat64",
+            IpCategory::SixToFour => "6to4",
+            IpCategory::Reserved => "reserved",
+            IpCategory::Public => "public",
+        }
+    }
+
+    /// Human readable label, as used in the text output
+    pub fn label(&self) -> &'static str {
+        match self {
+            IpCategory::Unspecified => "Unspecified",
+            IpCategory::Loopback => "Loopback",
+            IpCategory::Private => "Private",
+            IpCategory::SharedAddressSpace => "Shared Address Space",
+            IpCategory::LinkLocal => "Link-Local",
+            IpCategory::UniqueLocal => "Unique Local",
+            IpCategory::Documentation => "Documentation",
+            IpCategory::Benchmarking => "Benchmarking",
+            IpCategory::Multicast => "Multicast",
+            IpCategory::Broadcast => "Broadcast",
+            IpCategory::Ipv4Mapped => "IPv4-Mapped",
+            IpCategory::Nat64 => "NAT64",
+            IpCategory::SixToFour => "6to4",
+            IpCategory::Reserved => "Reserved",
+            IpCategory::Public => "Public",
+        }
+    }
+}
+
+impl fmt::Display for IpCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.label())
+    }
+}
+
+/// Reachability scope of an address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IpScope {
+    Host,
+    Link,
+    Private,
+    Multicast,
+    Reserved,
+    Global,
+}
+
+impl fmt::Display for IpScope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            IpScope::Host => "host",
+            IpScope::Link => "link",
+            IpScope::Private => "private",
+            IpScope::Multicast => "multicast",
+            IpScope::Reserved => "reserved",
+            IpScope::Global => "global",
+        };
+        f.write_str(label)
+    }
+}
+
+/// Structured information about a single IP address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct IpInfo {
+    pub address: IpAddr,
+    /// 4 or 6
+    pub version: u8,
+    pub category: IpCategory,
+    pub scope: IpScope,
+    /// Canonical text form (RFC 5952 for IPv6)
+    pub canonical: String,
+    /// Fully expanded text form (all eight IPv6 groups, zero padded)
+    pub expanded: String,
+    /// Address as an unsigned integer, in decimal (kept as a string so that
+    /// 128-bit values survive JSON consumers)
+    pub integer: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub octets: Option<[u8; 4]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub segments: Option<[u16; 8]>,
+    /// Name used for PTR lookups (in-addr.arpa / ip6.arpa)
+    pub reverse_dns: String,
+    /// Zone (scope) ID given with the address, e.g. "eth0"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Port given with the address
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Problems with the input, e.g. a link-local address without a zone ID
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+}
+
+impl IpInfo {
+    /// The address as it was given, with its zone ID and port
+    pub fn endpoint(&self) -> Endpoint {
+        Endpoint {
+            ip: self.address,
+            zone: self.zone.clone(),
+            port: self.port,
+        }
+    }
+}
+
+impl fmt::Display for IpInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "IPv{} {}", self.version, self.category)
+    }
+}
+
+/// IPv4 special-purpose ranges, most specific first
+const IPV4_SPECIAL_PURPOSE: &[(Ipv4Addr, u8, IpCategory)] = &[
+    (Ipv4Addr::new(0, 0, 0, 0), 32, IpCategory::Unspecified),
+    (Ipv4Addr::new(255, 255, 255, 255), 32, IpCategory::Broadcast),
+    (Ipv4Addr::new(0, 0, 0, 0), 8, IpCategory::Reserved),
+    (Ipv4Addr::new(10, 0, 0, 0), 8, IpCategory::Private),
+    (
+        Ipv4Addr::new(100, 64, 0, 0),
+        10,
+        IpCategory::SharedAddressSpace,
+    ),
+    (Ipv4Addr::new(127, 0, 0, 0), 8, IpCategory::Loopback),
+    (Ipv4Addr::new(169, 254, 0, 0), 16, IpCategory::LinkLocal),
+    (Ipv4Addr::new(172, 16, 0, 0), 12, IpCategory::Private),
+    (Ipv4Addr::new(192, 0, 0, 0), 24, IpCategory::Reserved),
+    (Ipv4Addr::new(192, 0, 2, 0), 24, IpCategory::Documentation),
+    (Ipv4Addr::new(192, 88, 99, 0), 24, IpCategory::Reserved),
+    (Ipv4Addr::new(192, 168, 0, 0), 16, IpCategory::Private),
+    (Ipv4Addr::new(198, 18, 0, 0), 15, IpCategory::Benchmarking),
+    (
+        Ipv4Addr::new(198, 51, 100, 0),
+        24,
+        IpCategory::Documentation,
+    ),
+    (Ipv4Addr::new(203, 0, 113, 0), 24, IpCategory::Documentation),
+    (Ipv4Addr::new(224, 0, 0, 0), 4, IpCategory::Multicast),
+    (Ipv4Addr::new(240, 0, 0, 0), 4, IpCategory::Reserved),
+];
+
+/// IPv6 special-purpose ranges, most specific first
+const IPV6_SPECIAL_PURPOSE: &[(Ipv6Addr, u8, IpCategory)] = &[
+    (
+        Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
+        128,
+        IpCategory::Unspecified,
+    ),
+    (
+        Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 1),
+        128,
+        IpCategory::Loopback,
+    ),
+    (
+        Ipv6Addr::new(0, 0, 0, 0, 0, 0xffff, 0, 0),
+        96,
+        IpCategory::Ipv4Mapped,
+    ),
+    (
+        Ipv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0, 0),
+        96,
+        IpCategory::Nat64,
+    ),
+    (
+        Ipv6Addr::new(0x64, 0xff9b, 1, 0, 0, 0, 0, 0),
+        48,
+        IpCategory::Private,
+    ),
+    (
+        Ipv6Addr::new(0x100, 0, 0, 0, 0, 0, 0, 0),
+        64,
+        IpCategory::Reserved,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0x2, 0, 0, 0, 0, 0, 0),
+        48,
+        IpCategory::Benchmarking,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0),
+        32,
+        IpCategory::Documentation,
+    ),
+    (
+        Ipv6Addr::new(0x2001, 0, 0, 0, 0, 0, 0, 0),
+        23,
+        IpCategory::Reserved,
+    ),
+    (
+        Ipv6Addr::new(0x2002, 0, 0, 0, 0, 0, 0, 0),
+        16,
+        IpCategory::SixToFour,
+    ),
+    (
+        Ipv6Addr::new(0x3fff, 0, 0, 0, 0, 0, 0, 0),
+        20,
+        IpCategory::Documentation,
+    ),
+    (
+        Ipv6Addr::new(0xfc00, 0, 0, 0, 0, 0, 0, 0),
+        7,
+        IpCategory::UniqueLocal,
+    ),
+    (
+        Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0),
+        10,
+        IpCategory::LinkLocal,
+    ),
+    (
+        Ipv6Addr::new(0xff00, 0, 0, 0, 0, 0, 0, 0),
+        8,
+        IpCategory::Multicast,
+    ),
+    (
+        Ipv6Addr::new(0, 0, 0, 0, 0, 0, 0, 0),
+        8,
+        IpCategory::Reserved,
+    ),
+];
+
+fn classify_ipv4(ip: Ipv4Addr) -> IpCategory {
+    let ip_u32 = u32::from(ip);
+    IPV4_SPECIAL_PURPOSE
+        .iter()
+        .find(|(network, prefix_len, _)| {
+            let mask = if *prefix_len == 0 {
+                0
+            } else {
+                0xffffffff << (32 - prefix_len)
+            };
+            ip_u32 & mask == u32::from(*network)
+        })
+        .map(|(_, _, category)| *category)
+        .unwrap_or(IpCategory::Public)
+}
+
+fn classify_ipv6(ip: Ipv6Addr) -> IpCategory {
+    let ip_u128 = u128::from(ip);
+    IPV6_SPECIAL_PURPOSE
+        .iter()
+        .find(|(network, prefix_len, _)| {
+            let mask = if *prefix_len == 0 {
+                0
+            } else {
+                0xffffffffffffffffffffffffffffffff << (128 - prefix_len)
+            };
+            ip_u128 & mask == u128::from(*network)
+        })
+        .map(|(_, _, category)| *category)
+        .unwrap_or(IpCategory::Public)
+}
+
+/// The IPv4 address carried by an IPv4-mapped (low 32 bits), NAT64 (low 32
+/// bits, RFC 6052 /96) or 6to4 (bits 16-47, RFC 3056) address
+fn embedded_ipv4(ip: &IpAddr, category: IpCategory) -> Option<Ipv4Addr> {
+    let IpAddr::V6(ipv6) = ip else {
+        return None;
+    };
+    let value = u128::from(*ipv6);
+    match category {
+        IpCategory::Ipv4Mapped | IpCategory::Nat64 => Some(Ipv4Addr::from(value as u32)),
+        IpCategory::SixToFour => Some(Ipv4Addr::from((value >> 80) as u32)),
+        _ => None,
+    }
+}
+
+fn scope_for(ip: &IpAddr, category: IpCategory) -> IpScope {
+    match category {
+        IpCategory::Unspecified | IpCategory::Loopback => IpScope::Host,
+        IpCategory::LinkLocal => IpScope::Link,
+        IpCategory::Private | IpCategory::SharedAddressSpace | IpCategory::UniqueLocal => {
+            IpScope::Private
+        }
+        IpCategory::Multicast => IpScope::Multicast,
+        IpCategory::Documentation
+        | IpCategory::Benchmarking
+        | IpCategory::Broadcast
+        | IpCategory::Reserved => IpScope::Reserved,
+        // IPv6 forms carrying an IPv4 address are only as reachable as that address
+        IpCategory::Ipv4Mapped | IpCategory::Nat64 | IpCategory::SixToFour => {
+            match embedded_ipv4(ip, category) {
+                Some(ipv4) => scope_for(&IpAddr::V4(ipv4), classify_ipv4(ipv4)),
+                None => IpScope::Reserved,
+            }
+        }
+        IpCategory::Public => IpScope::Global,
+    }
+}
+
+/// Get structured information about an IP address
+pub fn get_ip_info(ip: &IpAddr) -> IpInfo {
+    let category = match ip {
+        IpAddr::V4(ipv4) => classify_ipv4(*ipv4),
+        IpAddr::V6(ipv6) => classify_ipv6(*ipv6),
+    };
+
+    let (version, expanded, integer, octets, segments) = match ip {
+        IpAddr::V4(ipv4) => (
+            4,
+            ipv4.to_string(),
+            u32::from(*ipv4).to_string(),
+            Some(ipv4.octets()),
+            None,
+        ),
+        IpAddr::V6(ipv6) => {
+            let segments = ipv6.segments();
+            let expanded = segments
+                .iter()
+                .map(|segment| format!("{:04x}", segment))
+                .collect::<Vec<_>>()
+                .join(":");
+            (
+                6,
+                expanded,
+                u128::from(*ipv6).to_string(),
+                None,
+                Some(segments),
+            )
+        }
+    };
+
+    IpInfo {
+        address: *ip,
+        version,
+        category,
+        scope: scope_for(ip, category),
+        canonical: ip.to_string(),
+        expanded,
+        integer,
+        octets,
+        segments,
+        reverse_dns: ptr_name(*ip),
+        zone: None,
+        port: None,
+        warnings: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(ip: &str) -> IpInfo {
+        get_ip_info(&ip.parse::<IpAddr>().unwrap())
+    }
+
+    #[test]
+    fn test_ipv4_categories() {
+        assert_eq!(info("127.0.0.1").category, IpCategory::Loopback);
+        assert_eq!(info("10.1.2.3").category, IpCategory::Private);
+        assert_eq!(info("100.64.0.1").category, IpCategory::SharedAddressSpace);
+        assert_eq!(info("169.254.1.1").category, IpCategory::LinkLocal);
+        assert_eq!(info("192.0.2.1").category, IpCategory::Documentation);
+        assert_eq!(info("224.0.0.1").category, IpCategory::Multicast);
+        assert_eq!(info("255.255.255.255").category, IpCategory::Broadcast);
+        assert_eq!(info("0.0.0.0").category, IpCategory::Unspecified);
+        assert_eq!(info("8.8.8.8").category, IpCategory::Public);
+    }
+
+    #[test]
+    fn test_ipv6_categories() {
+        assert_eq!(info("::1").category, IpCategory::Loopback);
+        assert_eq!(info("fe80::1").category, IpCategory::LinkLocal);
+        assert_eq!(info("fd00::1").category, IpCategory::UniqueLocal);
+        assert_eq!(info("2001:db8::1").category, IpCategory::Documentation);
+        assert_eq!(info("2002:c000:201::1").category, IpCategory::SixToFour);
+        assert_eq!(info("ff02::1").category, IpCategory::Multicast);
+        assert_eq!(info("2606:4700::1111").category, IpCategory::Public);
+    }
+
+    #[test]
+    fn test_scope() {
+        assert_eq!(info("127.0.0.1").scope, IpScope::Host);
+        assert_eq!(info("192.168.1.1").scope, IpScope::Private);
+        assert_eq!(info("8.8.8.8").scope, IpScope::Global);
+        assert_eq!(info("::ffff:10.0.0.1").scope, IpScope::Private);
+        assert_eq!(info("::ffff:8.8.8.8").scope, IpScope::Global);
+        assert_eq!(info("64:ff9b::10.0.0.1").scope, IpScope::Private);
+        assert_eq!(info("64:ff9b::8.8.8.8").scope, IpScope::Global);
+        assert_eq!(info("2002:7f00:1::1").scope, IpScope::Host);
+        assert_eq!(info("2002:c0a8:101::1").scope, IpScope::Private);
+        assert_eq!(info("2002:808:808::1").scope, IpScope::Global);
+    }
+
+    #[test]
+    fn test_text_forms() {
+        let v4 = info("192.168.1.10");
+        assert_eq!(v4.version, 4);
+        assert_eq!(v4.integer, "3232235786");
+        assert_eq!(v4.octets, Some([192, 168, 1, 10]));
+        assert_eq!(v4.reverse_dns, "10.1.168.192.in-addr.arpa");
+        assert_eq!(v4.to_string(), "IPv4 Private");
+
+        let v6 = info("2001:db8::1");
+        assert_eq!(v6.version, 6);
+        assert_eq!(v6.canonical, "2001:db8::1");
+        assert_eq!(v6.expanded, "2001:0db8:0000:0000:0000:0000:0000:0001");
+        assert_eq!(
+            v6.reverse_dns,
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.8.b.d.0.1.0.0.2.ip6.arpa"
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(info("8.8.8.8")).unwrap();
+        assert_eq!(json["category"], "public");
+        assert_eq!(json["scope"], "global");
+        assert_eq!(json["version"], 4);
+        assert!(json.get("segments").is_none());
+
+        for category in [IpCategory::SharedAddressSpace, IpCategory::Ipv4Mapped] {
+            assert_eq!(serde_json::to_value(category).unwrap(), category.as_str());
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use super::IpParseError;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Non-canonical notation found by `parse_ip_lenient`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Notation {
+    /// A part starts with `0x`, e.g. 0x7f.0.0.1
+    Hex,
+    /// A part starts with `0`, e.g. 0177.0.0.1
+    Octal,
+    /// Fewer than four parts, the last filling the remaining bytes, e.g. 127.1
+    ShortForm,
+    /// A single 32-bit number, e.g. 2130706433
+    Integer,
+    /// A trailing dot, e.g. 127.0.0.1.
+    TrailingDot,
+    /// IPv6 text that is not in RFC 5952 form, e.g. upper case or
+    /// uncompressed zeros
+    NonCanonicalIpv6,
+    /// IPv4-mapped IPv6 address, which dual-stack sockets connect to over IPv4
+    Ipv4Mapped,
+}
+
+impl Notation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Notation::Hex => "hex",
+            Notation::Octal => "octal",
+            Notation::ShortForm => "short_form",
+            Notation::Integer => "integer",
+            Notation::TrailingDot => "trailing_dot",
+            Notation::NonCanonicalIpv6 => "non_canonical_ipv6",
+            Notation::Ipv4Mapped => "ipv4_mapped",
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Notation::Hex => "hexadecimal part",
+            Notation::Octal => "octal part",
+            Notation::ShortForm => "fewer than four parts",
+            Notation::Integer => "single 32-bit integer",
+            Notation::TrailingDot => "trailing dot",
+            Notation::NonCanonicalIpv6 => "not RFC 5952 text",
+            Notation::Ipv4Mapped => "IPv4-mapped IPv6 address",
+        }
+    }
+}
+
+/// An address decoded by `parse_ip_lenient`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LenientAddress {
+    pub ip: IpAddr,
+    /// Address connections actually go to: the IPv4 address inside an
+    /// IPv4-mapped address, else `ip`
+    pub effective: IpAddr,
+    /// Non-canonical notations used, empty for canonical input
+    pub notations: Vec<Notation>,
+}
+
+impl LenientAddress {
+    pub fn is_canonical(&self) -> bool {
+        self.notations.is_empty()
+    }
+}
+
+/// Parse one part of an inet_aton address: decimal, octal with a leading
+/// `0`, or hex with a leading `0x`
+fn parse_part(part: &str, notations: &mut Vec<Notation>) -> Option<u32> {
+    let (digits, radix, notation) =
+        if let Some(hex) = part.strip_prefix("0x").or_else(|| part.strip_prefix("0X")) {
+            (hex, 16, Some(Notation::Hex))
+        } else if part.len() > 1 && part.starts_with('0') {
+            (&part[1..], 8, Some(Notation::Octal))
+        } else {
+            (part, 10, None)
+        };
+    if part.is_empty() || !digits.chars().all(|c| c.is_digit(radix)) {
+        return None;
+    }
+    let value = if digits.is_empty() {
+        // "0x" alone is zero, as in inet_aton and the WHATWG URL parser
+        0
+    } else {
+        u32::from_str_radix(digits, radix).ok()?
+    };
+    if let Some(notation) = notation
+        && !notations.contains(&notation)
+    {
+        notations.push(notation);
+    }
+    Some(value)
+}
+
+/// Decode every IPv4 form `inet_aton` accepts: one to four parts, each
+/// decimal, octal or hex, the last part filling the remaining bytes
+fn parse_inet_aton(text: &str, notations: &mut Vec<Notation>) -> Option<Ipv4Addr> {
+    let text = match text.strip_suffix('.') {
+        Some(stripped) if !stripped.is_empty() => {
+            notations.push(Notation::TrailingDot);
+            stripped
+        }
+        _ => text,
+    };
+    let parts: Vec<&str> = text.split('.').collect();
+    if parts.len() > 4 {
+        return None;
+    }
+    let values = parts
+        .iter()
+        .map(|part| parse_part(part, notations))
+        .collect::<Option<Vec<u32>>>()?;
+
+    let (last, leading) = values.split_last()?;
+    if leading.iter().any(|&value| value > 255) {
+        return None;
+    }
+    let last_bits = 8 * (4 - leading.len() as u32);
+    if last_bits < 32 && *last >= 1 << last_bits {
+        return None;
+    }
+    let value = leading
+        .iter()
+        .fold(0u32, |value, &part| (value << 8) | part)
+        .checked_shl(last_bits)
+        .unwrap_or(0)
+        | last;
+
+    match parts.len() {
+        1 => notations.push(Notation::Integer),
+        4 => {}
+        _ => notations.push(Notation::ShortForm),
+    }
+    Some(Ipv4Addr::from(value))
+}
+
+/// Parse an address the way `inet_aton` and browsers do, and report which
+/// non-canonical notations it uses. For IPv4 this accepts 0x7f.1,
+/// 017700000001 and 2130706433 as 127.0.0.1; IPv6 text is parsed normally
+/// and compared with its RFC 5952 form.
+///
+/// This is deliberately more permissive than `parse_ip_address` and meant
+/// for showing what an untrusted address really points to.
+pub fn parse_ip_lenient(input: &str) -> Result<LenientAddress, IpParseError> {
+    let text = input.trim();
+    let mut notations = Vec::new();
+
+    if text.contains(':') {
+        let ipv6: Ipv6Addr = text
+            .parse()
+            .map_err(|_| IpParseError::InvalidFormat(input.to_string()))?;
+        if ipv6.to_string() != text {
+            notations.push(Notation::NonCanonicalIpv6);
+        }
+        let effective = match ipv6.to_ipv4_mapped() {
+            Some(ipv4) => {
+                notations.push(Notation::Ipv4Mapped);
+                IpAddr::V4(ipv4)
+            }
+            None => IpAddr::V6(ipv6),
+        };
+        return Ok(LenientAddress {
+            ip: IpAddr::V6(ipv6),
+            effective,
+            notations,
+        });
+    }
+
+    let ipv4 = parse_inet_aton(text, &mut notations)
+        .ok_or_else(|| IpParseError::InvalidFormat(input.to_string()))?;
+    notations.sort();
+    Ok(LenientAddress {
+        ip: IpAddr::V4(ipv4),
+        effective: IpAddr::V4(ipv4),
+        notations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenient(text: &str) -> (String, Vec<Notation>) {
+        let address = parse_ip_lenient(text).unwrap();
+        (address.effective.to_string(), address.notations)
+    }
+
+    #[test]
+    fn test_inet_aton_forms() {
+        use Notation::*;
+        assert_eq!(lenient("127.0.0.1"), ("127.0.0.1".to_string(), vec![]));
+        assert_eq!(
+            lenient("0x7f.1"),
+            ("127.0.0.1".to_string(), vec![Hex, ShortForm])
+        );
+        assert_eq!(
+            lenient("017700000001"),
+            ("127.0.0.1".to_string(), vec![Octal, Integer])
+        );
+        assert_eq!(
+            lenient("2130706433"),
+            ("127.0.0.1".to_string(), vec![Integer])
+        );
+        assert_eq!(
+            lenient("0177.0.0.01"),
+            ("127.0.0.1".to_string(), vec![Octal])
+        );
+        assert_eq!(
+            lenient("10.0x10203"),
+            ("10.1.2.3".to_string(), vec![Hex, ShortForm])
+        );
+        assert_eq!(
+            lenient("192.168.257"),
+            ("192.168.1.1".to_string(), vec![ShortForm])
+        );
+        assert_eq!(
+            lenient("127.0.0.1."),
+            ("127.0.0.1".to_string(), vec![TrailingDot])
+        );
+        assert_eq!(lenient("0x"), ("0.0.0.0".to_string(), vec![Hex, Integer]));
+        assert_eq!(
+            lenient("4294967295"),
+            ("255.255.255.255".to_string(), vec![Integer])
+        );
+
+        for invalid in [
+            "1.2.3.4.5",
+            "256.1.1.1",
+            "1.2.65536",
+            "4294967296",
+            "08.1.1.1",
+            "1..1",
+            "0xg",
+            "",
+            ".",
+            "example.com",
+        ] {
+            assert!(parse_ip_lenient(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_ipv6_notations() {
+        let address = parse_ip_lenient("2001:db8::1").unwrap();
+        assert!(address.is_canonical());
+
+        let address = parse_ip_lenient("2001:DB8:0:0:0:0:0:1").unwrap();
+        assert_eq!(address.notations, [Notation::NonCanonicalIpv6]);
+
+        let address = parse_ip_lenient("::ffff:127.0.0.1").unwrap();
+        assert_eq!(address.notations, [Notation::Ipv4Mapped]);
+        assert_eq!(address.effective, "127.0.0.1".parse::<IpAddr>().unwrap());
+    }
+}
@@ -0,0 +1,191 @@
+use super::ranges::{address_value, host_mask, network_range, value_address};
+use super::{IpParseError, parse_cidr, parse_ip_address};
+use std::cmp::Ordering;
+use std::fmt;
+use std::net::IpAddr;
+
+/// One entry of an address list: a single address or a CIDR block
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListEntry {
+    pub ip: IpAddr,
+    /// `None` for a plain address
+    pub prefix_len: Option<u8>,
+}
+
+impl ListEntry {
+    /// Parse "192.0.2.1" or "192.0.2.0/24"
+    pub fn parse(text: &str) -> Result<Self, IpParseError> {
+        let text = text.trim();
+        if text.contains('/') {
+            let (ip, prefix_len) = parse_cidr(text)?;
+            Ok(ListEntry {
+                ip,
+                prefix_len: Some(prefix_len),
+            })
+        } else {
+            Ok(ListEntry {
+                ip: parse_ip_address(text)?,
+                prefix_len: None,
+            })
+        }
+    }
+
+    fn max_len(&self) -> u8 {
+        if self.ip.is_ipv4() { 32 } else { 128 }
+    }
+
+    /// First and last address covered, as integers
+    pub fn range(&self) -> (u128, u128) {
+        network_range(self.ip, self.prefix_len.unwrap_or(self.max_len()))
+    }
+
+    /// The entry with its host bits cleared (192.0.2.1/24 -> 192.0.2.0/24)
+    pub fn canonical(&self) -> Self {
+        match self.prefix_len {
+            Some(prefix_len) => {
+                let (ipv4, value) = address_value(self.ip);
+                ListEntry {
+                    ip: value_address(value & !host_mask(prefix_len, ipv4), ipv4),
+                    prefix_len: Some(prefix_len),
+                }
+            }
+            None => *self,
+        }
+    }
+
+    /// IPv4 first, then by address, then shorter prefixes first, with a
+    /// plain address after the equivalent /32 or /128
+    fn sort_key(&self) -> (bool, u128, u8, bool) {
+        (
+            self.ip.is_ipv6(),
+            address_value(self.ip).1,
+            self.prefix_len.unwrap_or(self.max_len()),
+            self.prefix_len.is_none(),
+        )
+    }
+}
+
+impl Ord for ListEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.sort_key().cmp(&other.sort_key())
+    }
+}
+
+impl PartialOrd for ListEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for ListEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.prefix_len {
+            Some(prefix_len) => write!(f, "{}/{}", self.ip, prefix_len),
+            None => write!(f, "{}", self.ip),
+        }
+    }
+}
+
+/// Drop every entry whose addresses are all covered by another entry. Of
+/// entries covering the same range, the first in sort order is kept.
+/// Returns the remaining entries, sorted.
+pub fn remove_covered(mut entries: Vec<ListEntry>) -> Vec<ListEntry> {
+    // Wider ranges before the ranges they cover
+    entries.sort_by_key(|entry| {
+        let (start, end) = entry.range();
+        (entry.ip.is_ipv6(), start, std::cmp::Reverse(end), *entry)
+    });
+
+    let mut kept: Vec<ListEntry> = Vec::with_capacity(entries.len());
+    let mut cover: Option<(bool, u128)> = None;
+    for entry in entries {
+        let (_, end) = entry.range();
+        let ipv6 = entry.ip.is_ipv6();
+        match cover {
+            Some((family, cover_end)) if family == ipv6 && end <= cover_end => continue,
+            _ => cover = Some((ipv6, end)),
+        }
+        kept.push(entry);
+    }
+    kept.sort();
+    kept
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(texts: &[&str]) -> Vec<ListEntry> {
+        texts
+            .iter()
+            .map(|text| ListEntry::parse(text).unwrap())
+            .collect()
+    }
+
+    fn texts(entries: &[ListEntry]) -> Vec<String> {
+        entries.iter().map(ListEntry::to_string).collect()
+    }
+
+    #[test]
+    fn test_sort_order() {
+        let mut list = entries(&[
+            "2001:db8::1",
+            "10.0.0.10",
+            "10.0.0.9",
+            "::1",
+            "10.0.0.0/8",
+            "10.0.0.0/16",
+            "10.0.0.0",
+            "10.0.0.0/32",
+            "9.255.255.255",
+        ]);
+        list.sort();
+        assert_eq!(
+            texts(&list),
+            [
+                "9.255.255.255",
+                "10.0.0.0/8",
+                "10.0.0.0/16",
+                "10.0.0.0/32",
+                "10.0.0.0",
+                "10.0.0.9",
+                "10.0.0.10",
+                "::1",
+                "2001:db8::1"
+            ]
+        );
+        assert_eq!(
+            ListEntry::parse("192.0.2.77/24")
+                .unwrap()
+                .canonical()
+                .to_string(),
+            "192.0.2.0/24"
+        );
+        assert!(ListEntry::parse("192.0.2.0/33").is_err());
+    }
+
+    #[test]
+    fn test_remove_covered() {
+        let list = entries(&[
+            "10.1.2.3",
+            "10.0.0.0/8",
+            "10.1.0.0/16",
+            "192.0.2.0/24",
+            "192.0.2.0/24",
+            "192.0.3.1",
+            "2001:db8::/32",
+            "2001:db8:1::1",
+            "::ffff:10.0.0.1",
+        ]);
+        assert_eq!(
+            texts(&remove_covered(list)),
+            [
+                "10.0.0.0/8",
+                "192.0.2.0/24",
+                "192.0.3.1",
+                "::ffff:10.0.0.1",
+                "2001:db8::/32"
+            ]
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use std::net::IpAddr;
+
+mod arith;
+mod convert;
+mod endpoint;
+mod info;
+mod lenient;
+mod list;
+mod ranges;
+mod reverse;
+
+pub use arith::{
+    address_distance, common_prefix_len, host_range, next_address, nth_host, offset_address,
+    previous_address, subnet_index,
+};
+pub use convert::{AddressForms, address_forms, embedded_ipv4, parse_address_any};
+pub(crate) use endpoint::needs_zone;
+pub use endpoint::{Endpoint, EndpointParts, get_endpoint_info, parse_endpoint, split_endpoint};
+pub use info::{IpCategory, IpInfo, IpScope, get_ip_info};
+pub use lenient::{LenientAddress, Notation, parse_ip_lenient};
+pub use list::{ListEntry, remove_covered};
+pub use ranges::{
+    IpFamily, address_value, host_mask, merge_ranges, network_range, range_to_cidrs,
+    subtract_ranges, value_address,
+};
+pub use reverse::{ReverseZone, parse_ptr_name, ptr_name, reverse_zones};
+
+#[derive(Debug)]
+pub enum IpParseError {
+    InvalidFormat(String),
+    InvalidCidr(String),
+    InvalidPtr(String),
+}
+
+impl std::fmt::Display for IpParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpParseError::InvalidFormat(ip) => write!(f, "Invalid IP address format: {}", ip),
+            IpParseError::InvalidCidr(cidr) => write!(f, "Invalid CIDR notation: {}", cidr),
+            IpParseError::InvalidPtr(name) => write!(f, "Invalid PTR name: {}", name),
+        }
+    }
+}
+
+impl std::error::Error for IpParseError {}
+
+/// Parse and validate an IP address string
+pub fn parse_ip_address(ip_str: &str) -> Result<IpAddr, IpParseError> {
+    ip_str
+        .parse()
+        .map_err(|_| IpParseError::InvalidFormat(ip_str.to_string()))
+}
+
+/// Parse and validate a CIDR notation string
+pub fn parse_cidr(cidr_str: &str) -> Result<(IpAddr, u8), IpParseError> {
+    let parts: Vec<&str> = cidr_str.split('/').collect();
+    if parts.len() != 2 {
+        return Err(IpParseError::InvalidCidr(cidr_str.to_string()));
+    }
+
+    let ip = parse_ip_address(parts[0])?;
+    let prefix = parts[1]
+        .parse::<u8>()
+        .map_err(|_| IpParseError::InvalidCidr(cidr_str.to_string()))?;
+
+    // Validate prefix length based on IP version
+    let max_prefix = match ip {
+        IpAddr::V4(_) => 32,
+        IpAddr::V6(_) => 128,
+    };
+
+    if prefix > max_prefix {
+        return Err(IpParseError::InvalidCidr(format!(
+            "Invalid prefix length {} for {} address",
+            prefix,
+            if matches!(ip, IpAddr::V4(_)) {
+                "IPv4"
+            } else {
+                "IPv6"
+            }
+        )));
+    }
+
+    Ok((ip, prefix))
+}
+
+/// Format the detailed IP information shown in verbose mode
+pub fn format_ip_details(info: &IpInfo) -> String {
+    let forms = address_forms(info.address);
+    let mut details = format!(
+        "IP Address: {}\nType: {}\n",
+        info.endpoint().address(),
+        info
+    );
+    if let Some(port) = info.port {
+        details.push_str(&format!("Port: {}\n", port));
+    }
+    details.push_str(&format!("PTR: {}\n", info.reverse_dns));
+    details.push_str(&format!("Integer: {}\nHex: {}\n", forms.integer, forms.hex));
+    details.push_str(&format!("Binary: {}\n", forms.binary));
+    if info.address.is_ipv6() {
+        details.push_str(&format!("Expanded: {}\n", forms.expanded));
+    }
+    if let Some(ipv4) = forms.embedded_ipv4 {
+        details.push_str(&format!("Embedded IPv4: {}\n", ipv4));
+    }
+    details
+}
+
+/// Print detailed IP information in verbose mode
+pub fn print_ip_details(ip: &IpAddr, verbose: bool) {
+    if verbose {
+        print!("{}", format_ip_details(&get_ip_info(ip)));
+    }
+}
@@ -0,0 +1,185 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// Address families selected for an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum IpFamily {
+    V4,
+    V6,
+    #[default]
+    Both,
+}
+
+impl IpFamily {
+    /// Whether the family selection includes IPv4 (`true`) or IPv6 (`false`)
+    pub fn includes(&self, ipv4: bool) -> bool {
+        match self {
+            IpFamily::V4 => ipv4,
+            IpFamily::V6 => !ipv4,
+            IpFamily::Both => true,
+        }
+    }
+}
+
+/// An address as an integer, with `true` for IPv4
+pub fn address_value(ip: IpAddr) -> (bool, u128) {
+    match ip {
+        IpAddr::V4(ipv4) => (true, u32::from(ipv4) as u128),
+        IpAddr::V6(ipv6) => (false, u128::from(ipv6)),
+    }
+}
+
+/// Inverse of `address_value`
+pub fn value_address(value: u128, ipv4: bool) -> IpAddr {
+    if ipv4 {
+        IpAddr::V4(Ipv4Addr::from(value as u32))
+    } else {
+        IpAddr::V6(Ipv6Addr::from(value))
+    }
+}
+
+/// Host bits of a prefix, e.g. 0xff for a /24 IPv4 network
+pub fn host_mask(prefix_len: u8, ipv4: bool) -> u128 {
+    let bits = if ipv4 { 32 } else { 128 };
+    match bits - prefix_len as u32 {
+        0 => 0,
+        128 => u128::MAX,
+        host_bits => (1u128 << host_bits) - 1,
+    }
+}
+
+/// First and last address of a network as integers
+pub fn network_range(ip: IpAddr, prefix_len: u8) -> (u128, u128) {
+    let (ipv4, value) = address_value(ip);
+    let mask = host_mask(prefix_len, ipv4);
+    (value & !mask, value | mask)
+}
+
+/// Sort inclusive ranges and merge the ones that overlap or touch
+pub fn merge_ranges(mut ranges: Vec<(u128, u128)>) -> Vec<(u128, u128)> {
+    ranges.sort_unstable();
+    let mut merged: Vec<(u128, u128)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Remove the addresses in `remove` from `ranges`. Both must be sorted and
+/// non-overlapping (as returned by `merge_ranges`).
+pub fn subtract_ranges(ranges: &[(u128, u128)], remove: &[(u128, u128)]) -> Vec<(u128, u128)> {
+    let mut result = Vec::new();
+    let mut index = 0;
+    for &(start, end) in ranges {
+        let mut start = start;
+        // Skip removals that end before this range
+        while index < remove.len() && remove[index].1 < start {
+            index += 1;
+        }
+        let mut next = index;
+        let mut exhausted = false;
+        while next < remove.len() && remove[next].0 <= end {
+            let (remove_start, remove_end) = remove[next];
+            if remove_start > start {
+                result.push((start, remove_start - 1));
+            }
+            if remove_end >= end {
+                exhausted = true;
+                break;
+            }
+            start = remove_end + 1;
+            next += 1;
+        }
+        if !exhausted {
+            result.push((start, end));
+        }
+    }
+    result
+}
+
+/// Split an inclusive range into the smallest list of CIDR blocks covering it
+pub fn range_to_cidrs(start: u128, end: u128, ipv4: bool) -> Vec<(IpAddr, u8)> {
+    let bits = if ipv4 { 32 } else { 128 };
+    let mut cidrs = Vec::new();
+    let mut start = start;
+    loop {
+        // Largest block aligned at `start` that does not pass `end`
+        let mut host_bits = start.trailing_zeros().min(bits);
+        let last = loop {
+            let last = start | host_mask((bits - host_bits) as u8, ipv4);
+            if last <= end {
+                break last;
+            }
+            host_bits -= 1;
+        };
+        cidrs.push((value_address(start, ipv4), (bits - host_bits) as u8));
+        if last >= end {
+            return cidrs;
+        }
+        start = last + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cidrs(start: &str, end: &str) -> Vec<String> {
+        let (ipv4, start) = address_value(start.parse().unwrap());
+        let (_, end) = address_value(end.parse().unwrap());
+        range_to_cidrs(start, end, ipv4)
+            .into_iter()
+            .map(|(ip, prefix_len)| format!("{}/{}", ip, prefix_len))
+            .collect()
+    }
+
+    #[test]
+    fn test_range_to_cidrs() {
+        assert_eq!(cidrs("1.0.16.0", "1.0.31.255"), ["1.0.16.0/20"]);
+        assert_eq!(
+            cidrs("1.1.64.0", "1.1.66.255"),
+            ["1.1.64.0/23", "1.1.66.0/24"]
+        );
+        assert_eq!(
+            cidrs("10.0.0.1", "10.0.0.6"),
+            ["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"]
+        );
+        assert_eq!(cidrs("0.0.0.0", "255.255.255.255"), ["0.0.0.0/0"]);
+        assert_eq!(
+            cidrs("::", "ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"),
+            ["::/0"]
+        );
+        assert_eq!(
+            cidrs("255.255.255.254", "255.255.255.255"),
+            ["255.255.255.254/31"]
+        );
+    }
+
+    #[test]
+    fn test_merge_and_subtract() {
+        let merged = merge_ranges(vec![(10, 20), (0, 4), (5, 7), (15, 30), (40, 50)]);
+        assert_eq!(merged, [(0, 7), (10, 30), (40, 50)]);
+
+        let remove = [(3, 4), (12, 12), (25, 45)];
+        assert_eq!(
+            subtract_ranges(&merged, &remove),
+            [(0, 2), (5, 7), (10, 11), (13, 24), (46, 50)]
+        );
+        assert_eq!(subtract_ranges(&merged, &[(0, 100)]), []);
+        assert_eq!(subtract_ranges(&merged, &[]), merged);
+        assert_eq!(merge_ranges(vec![(0, u128::MAX), (5, 6)]), [(0, u128::MAX)]);
+    }
+
+    #[test]
+    fn test_network_range() {
+        let (start, end) = network_range("192.168.1.77".parse().unwrap(), 24);
+        assert_eq!(value_address(start, true).to_string(), "192.168.1.0");
+        assert_eq!(value_address(end, true).to_string(), "192.168.1.255");
+        assert_eq!(network_range("::".parse().unwrap(), 0), (0, u128::MAX));
+    }
+}
@@ -0,0 +1,265 @@
+use super::IpParseError;
+use super::ranges::{address_value, host_mask, value_address};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+const IPV4_SUFFIX: &str = "in-addr.arpa";
+const IPV6_SUFFIX: &str = "ip6.arpa";
+
+/// A reverse DNS zone holding the PTR records of (part of) a network
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReverseZone {
+    /// Zone name without the trailing dot, e.g. "2.0.192.in-addr.arpa" or,
+    /// for an RFC 2317 delegation, "128/26.2.0.192.in-addr.arpa"
+    pub name: String,
+    /// For RFC 2317 classless delegations, the /24 zone that holds the NS
+    /// records and a CNAME for each address
+    pub parent: Option<String>,
+    /// First and last address the zone covers
+    pub first: IpAddr,
+    pub last: IpAddr,
+}
+
+impl ReverseZone {
+    /// Whether the zone is an RFC 2317 classless delegation
+    pub fn is_classless(&self) -> bool {
+        self.parent.is_some()
+    }
+
+    /// Owner name of the PTR record of `ip` in this zone, relative to the
+    /// zone (e.g. "10" in "2.0.192.in-addr.arpa", "@" for the apex), or
+    /// `None` when the zone does not cover `ip`
+    pub fn relative_name(&self, ip: IpAddr) -> Option<String> {
+        let (ipv4, value) = address_value(ip);
+        let (first_ipv4, first) = address_value(self.first);
+        let (_, last) = address_value(self.last);
+        if ipv4 != first_ipv4 || value < first || value > last {
+            return None;
+        }
+
+        let name = ptr_name(ip);
+        let zone_labels = if self.is_classless() {
+            // "128/26.2.0.192.in-addr.arpa" stands in for "2.0.192.in-addr.arpa"
+            3 + 2
+        } else {
+            self.name.split('.').count()
+        };
+        let labels: Vec<&str> = name.split('.').collect();
+        let relative = &labels[..labels.len() - zone_labels];
+        if relative.is_empty() {
+            Some("@".to_string())
+        } else {
+            Some(relative.join("."))
+        }
+    }
+}
+
+/// Build the in-addr.arpa / ip6.arpa name queried for the PTR record of an
+/// address
+pub fn ptr_name(ip: IpAddr) -> String {
+    match ip {
+        IpAddr::V4(ipv4) => {
+            let [a, b, c, d] = ipv4.octets();
+            format!("{}.{}.{}.{}.{}", d, c, b, a, IPV4_SUFFIX)
+        }
+        IpAddr::V6(ipv6) => {
+            let hex = format!("{:032x}", u128::from(ipv6));
+            let mut name = String::with_capacity(72);
+            for nibble in hex.chars().rev() {
+                name.push(nibble);
+                name.push('.');
+            }
+            name.push_str(IPV6_SUFFIX);
+            name
+        }
+    }
+}
+
+/// Parse a PTR query name such as "1.2.0.192.in-addr.arpa." back into the
+/// address. Names must have all 4 octets (IPv4) or 32 nibbles (IPv6);
+/// letter case and a trailing dot are ignored.
+pub fn parse_ptr_name(name: &str) -> Result<IpAddr, IpParseError> {
+    let invalid = || IpParseError::InvalidPtr(name.to_string());
+    let lower = name.trim().trim_end_matches('.').to_ascii_lowercase();
+
+    if let Some(labels) = lower.strip_suffix(&format!(".{}", IPV4_SUFFIX)) {
+        let octets = labels
+            .split('.')
+            .map(|label| {
+                let canonical = label == "0" || !label.starts_with('0');
+                match label.parse::<u8>() {
+                    Ok(octet) if canonical && label.bytes().all(|b| b.is_ascii_digit()) => {
+                        Ok(octet)
+                    }
+                    _ => Err(invalid()),
+                }
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        let [d, c, b, a] = octets[..] else {
+            return Err(invalid());
+        };
+        return Ok(IpAddr::V4(Ipv4Addr::new(a, b, c, d)));
+    }
+
+    if let Some(labels) = lower.strip_suffix(&format!(".{}", IPV6_SUFFIX)) {
+        let nibbles: Vec<&str> = labels.split('.').collect();
+        if nibbles.len() != 32 {
+            return Err(invalid());
+        }
+        let mut value: u128 = 0;
+        for nibble in nibbles.iter().rev() {
+            let digit = match nibble.as_bytes() {
+                [digit] => (*digit as char).to_digit(16).ok_or_else(invalid)?,
+                _ => return Err(invalid()),
+            };
+            value = (value << 4) | digit as u128;
+        }
+        return Ok(IpAddr::V6(Ipv6Addr::from(value)));
+    }
+
+    Err(invalid())
+}
+
+/// Name of the zone for the first `labels` octets or nibbles of `network`
+fn zone_name(network: u128, ipv4: bool, labels: u8) -> String {
+    let mut parts: Vec<String> = if ipv4 {
+        (0..labels)
+            .map(|index| ((network >> (24 - 8 * index as u32)) & 0xff).to_string())
+            .collect()
+    } else {
+        (0..labels)
+            .map(|index| format!("{:x}", (network >> (124 - 4 * index as u32)) & 0xf))
+            .collect()
+    };
+    parts.reverse();
+    parts.push((if ipv4 { IPV4_SUFFIX } else { IPV6_SUFFIX }).to_string());
+    parts.join(".")
+}
+
+/// Reverse zones to delegate for a network. Prefixes on an octet (IPv4) or
+/// nibble (IPv6) boundary map to one zone; others are split into the zones
+/// of the next longer boundary, e.g. a /22 into four /24 zones. IPv4
+/// prefixes longer than /24 get an RFC 2317 classless zone named
+/// "<first octet>/<prefix length>" under their /24. Host bits are ignored.
+pub fn reverse_zones(ip: IpAddr, prefix_len: u8) -> Vec<ReverseZone> {
+    let (ipv4, value) = address_value(ip);
+    let network = value & !host_mask(prefix_len, ipv4);
+
+    if ipv4 && prefix_len > 24 && prefix_len < 32 {
+        let parent = zone_name(network, true, 3);
+        return vec![ReverseZone {
+            name: format!("{}/{}.{}", network & 0xff, prefix_len, parent),
+            parent: Some(parent),
+            first: value_address(network, true),
+            last: value_address(network | host_mask(prefix_len, true), true),
+        }];
+    }
+
+    let bits_per_label = if ipv4 { 8 } else { 4 };
+    let labels = prefix_len.div_ceil(bits_per_label);
+    let zone_len = labels * bits_per_label;
+    let count = 1u128 << (zone_len - prefix_len);
+    let step = host_mask(zone_len, ipv4).wrapping_add(1);
+
+    (0..count)
+        .map(|index| {
+            let start = network + index * step;
+            ReverseZone {
+                name: zone_name(start, ipv4, labels),
+                parent: None,
+                first: value_address(start, ipv4),
+                last: value_address(start | host_mask(zone_len, ipv4), ipv4),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(text: &str) -> IpAddr {
+        text.parse().unwrap()
+    }
+
+    fn names(network: &str, prefix_len: u8) -> Vec<String> {
+        reverse_zones(ip(network), prefix_len)
+            .into_iter()
+            .map(|zone| zone.name)
+            .collect()
+    }
+
+    #[test]
+    fn test_ptr_name_round_trip() {
+        for address in ["192.0.2.1", "0.0.0.0", "2001:db8::abcd", "::"] {
+            let name = ptr_name(ip(address));
+            assert_eq!(parse_ptr_name(&name).unwrap(), ip(address));
+        }
+        assert_eq!(
+            parse_ptr_name("1.2.0.192.IN-ADDR.ARPA.").unwrap(),
+            ip("192.0.2.1")
+        );
+
+        for invalid in [
+            "2.0.192.in-addr.arpa",
+            "1.02.0.192.in-addr.arpa",
+            "256.2.0.192.in-addr.arpa",
+            "1.2.0.192.example.com",
+            "8.b.d.0.1.0.0.2.ip6.arpa",
+            "1.2.0.192",
+        ] {
+            assert!(parse_ptr_name(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_reverse_zones() {
+        assert_eq!(names("192.0.2.0", 24), ["2.0.192.in-addr.arpa"]);
+        assert_eq!(names("10.0.0.0", 8), ["10.in-addr.arpa"]);
+        assert_eq!(
+            names("198.51.100.0", 22),
+            [
+                "100.51.198.in-addr.arpa",
+                "101.51.198.in-addr.arpa",
+                "102.51.198.in-addr.arpa",
+                "103.51.198.in-addr.arpa"
+            ]
+        );
+        assert_eq!(names("192.0.2.1", 32), ["1.2.0.192.in-addr.arpa"]);
+        assert_eq!(names("0.0.0.0", 0), ["in-addr.arpa"]);
+        assert_eq!(names("2001:db8::", 32), ["8.b.d.0.1.0.0.2.ip6.arpa"]);
+        assert_eq!(
+            names("2001:db8::", 31),
+            ["8.b.d.0.1.0.0.2.ip6.arpa", "9.b.d.0.1.0.0.2.ip6.arpa"]
+        );
+        assert_eq!(names("::", 0), ["ip6.arpa"]);
+    }
+
+    #[test]
+    fn test_classless_zone() {
+        let zones = reverse_zones(ip("192.0.2.130"), 26);
+        assert_eq!(zones.len(), 1);
+        let zone = &zones[0];
+        assert_eq!(zone.name, "128/26.2.0.192.in-addr.arpa");
+        assert_eq!(zone.parent.as_deref(), Some("2.0.192.in-addr.arpa"));
+        assert_eq!(
+            (zone.first, zone.last),
+            (ip("192.0.2.128"), ip("192.0.2.191"))
+        );
+
+        assert_eq!(
+            zone.relative_name(ip("192.0.2.130")).as_deref(),
+            Some("130")
+        );
+        assert_eq!(zone.relative_name(ip("192.0.2.1")), None);
+
+        let zone = &reverse_zones(ip("192.0.2.1"), 32)[0];
+        assert_eq!(zone.relative_name(ip("192.0.2.1")).as_deref(), Some("@"));
+
+        let zone = &reverse_zones(ip("2001:db8::"), 32)[0];
+        assert_eq!(
+            zone.relative_name(ip("2001:db8::1")).unwrap(),
+            "1.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0.0"
+        );
+    }
+}
@@ -1,9 +1,17 @@
-use clap::{Parser, Subcommand};
-
-mod commands;
-
-pub use ipchecker::crawler_sources;
-pub use ipchecker::ip_utils;
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use ipchecker::asn::AsnProvider;
+use ipchecker::batch::{self, InputSource};
+use ipchecker::commands;
+use ipchecker::commands::calc::Calculation;
+use ipchecker::error::Error;
+use ipchecker::export::ExportFormat;
+use ipchecker::geo::{GeoChain, ProviderKind};
+use ipchecker::ip_utils::IpFamily;
+use ipchecker::output::{self, OutputFormat};
+use ipchecker::prefix_list::PrefixListFormat;
+use ipchecker::ssrf::SystemResolver;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(version, about = env!("CARGO_PKG_DESCRIPTION"))]
@@ -12,6 +20,14 @@ struct Cli {
     #[arg(short, long, global = true)]
     verbose: bool,
 
+    /// Output format
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+
+    /// Read inputs from FILE, one per line ("-" for stdin)
+    #[arg(short, long, global = true, value_name = "FILE")]
+    input: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -20,38 +36,678 @@ struct Cli {
 enum Commands {
     /// Check if an IP address belongs to a known crawler
     Crawler {
-        /// IP address to check
-        ip_address: String,
+        /// IP address to check ("-" to read one per line from stdin)
+        ip_address: Option<String>,
     },
     /// Check CIDR network overlap
     Cidr {
-        /// First CIDR network (e.g., 192.168.1.0/24)
-        network1: String,
+        #[command(subcommand)]
+        action: Option<CidrAction>,
+        /// First CIDR network (e.g., 192.168.1.0/24), or "-" to read pairs from stdin
+        network1: Option<String>,
         /// Second CIDR network (e.g., 192.168.0.0/16)
-        network2: String,
+        network2: Option<String>,
     },
     /// Check country code for an IP address
     Cc {
-        /// IP address to check
-        ip_address: String,
+        #[command(subcommand)]
+        action: Option<Box<CcAction>>,
+        /// IP address to check ("-" to read one per line from stdin)
+        ip_address: Option<String>,
+        #[command(flatten)]
+        geo: GeoArgs,
+    },
+    /// Look up the origin AS of an IP address
+    Asn {
+        #[command(subcommand)]
+        action: Option<AsnAction>,
+        /// IP address to check ("-" to read one per line from stdin)
+        ip_address: Option<String>,
+        #[command(flatten)]
+        data: AsnArgs,
+    },
+    /// Validate the origin AS of a route against RPKI VRPs (RFC 6811)
+    Rpki {
+        /// Announced prefix (e.g. 192.0.2.0/24), or "-" to read
+        /// "prefix origin" pairs from stdin
+        prefix: Option<String>,
+        /// Origin AS number, e.g. 64496 or AS64496
+        origin: Option<String>,
+        /// VRP export of rpki-client or Routinator in JSON (defaults to
+        /// rpki.vrps in ipchecker.json)
+        #[arg(long, value_name = "FILE")]
+        vrps: Option<PathBuf>,
+    },
+    /// Generate an aggregated prefix list for an AS or as-set from IRR data
+    PrefixList {
+        /// AS number (e.g. AS64496) or as-set (e.g. AS-EXAMPLE)
+        query: String,
+        /// Uncompressed RPSL dump with route, route6 and as-set objects;
+        /// repeat for several files (defaults to irr.rpsl in ipchecker.json)
+        #[arg(long, value_name = "FILE")]
+        rpsl: Vec<PathBuf>,
+        /// Address families to include
+        #[arg(long, value_enum, default_value_t = IpFamily::Both)]
+        family: IpFamily,
+        /// Router configuration format
+        #[arg(long, value_enum, default_value_t = PrefixListFormat::Plain)]
+        format: PrefixListFormat,
+        /// Prefix-list or set name (defaults to the query, e.g. AS_EXAMPLE)
+        #[arg(long)]
+        name: Option<String>,
+        /// Accept IPv4 more specifics up to this length; longer routes are left out
+        #[arg(long, value_name = "LEN")]
+        max_length: Option<u8>,
+        /// Accept IPv6 more specifics up to this length; longer routes are left out
+        #[arg(long, value_name = "LEN")]
+        max_length_v6: Option<u8>,
+    },
+    /// Show an address as an integer, hex, binary and in its IPv6 forms
+    Convert {
+        /// Address as text, a decimal integer or 0x-prefixed hex ("-" to
+        /// read one per line from stdin)
+        address: Option<String>,
+        /// Family of integer and hex input (both: IPv4 when it fits in 32 bits)
+        #[arg(long, value_enum, default_value_t = IpFamily::Both)]
+        family: IpFamily,
+        /// Also accept the IPv4 forms inet_aton and browsers accept, such as
+        /// 0x7f.1 and 0177.0.0.1
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// Decode an address written in any inet_aton form (e.g. 0x7f.1,
+    /// 017700000001) and show the address it really points to
+    Normalize {
+        /// Address to decode ("-" to read one per line from stdin)
+        address: Option<String>,
+    },
+    /// Check whether a user-supplied URL is safe to fetch: deny it when its
+    /// host is, or resolves to, any internal address
+    SsrfCheck {
+        /// URL or host name ("-" to read one per line from stdin)
+        url: Option<String>,
+    },
+    /// Find every IP address and CIDR in free text such as logs or emails,
+    /// one per line
+    Extract {
+        /// Files to scan (stdin when none are given, or for "-")
+        files: Vec<PathBuf>,
+        /// Address families to include
+        #[arg(long, value_enum, default_value_t = IpFamily::Both)]
+        family: IpFamily,
+        /// Prefix each token with "file:line:column: "
+        #[arg(short = 'n', long)]
+        positions: bool,
+    },
+    /// Sort a list of addresses and CIDRs numerically, IPv4 before IPv6
+    Sort {
+        /// Files to sort (stdin when none are given, or for "-")
+        files: Vec<PathBuf>,
+        /// Drop repeated entries
+        #[arg(short, long)]
+        unique: bool,
+        /// Clear the host bits of CIDR entries (192.0.2.1/24 -> 192.0.2.0/24)
+        #[arg(short, long)]
+        canonicalize: bool,
+        /// Drop entries covered by another entry in the list
+        #[arg(long)]
+        remove_covered: bool,
+    },
+    /// Address arithmetic: offsets, neighbours, hosts, distances and subnets
+    Calc {
+        #[command(subcommand)]
+        action: CalcAction,
     },
 }
 
-fn main() {
-    let cli = Cli::parse();
+/// ASN data sources shared by `asn`, `asn prefixes` and `cidr routes`
+#[derive(Args, Default)]
+struct AsnArgs {
+    /// MaxMind-format ASN database (defaults to asn.mmdb in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    db: Option<PathBuf>,
+    /// iptoasn.com ip2asn TSV file (defaults to asn.tsv in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    tsv: Option<PathBuf>,
+    /// Uncompressed MRT TABLE_DUMP_V2 RIB dump (RouteViews rib.*, RIPE RIS
+    /// bview.*); repeat for several files (defaults to asn.mrt in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    mrt: Vec<PathBuf>,
+}
 
-    let result = match cli.command {
+impl AsnArgs {
+    /// Combine with the options given before the subcommand; options given
+    /// after it take precedence
+    fn or(self, other: AsnArgs) -> AsnArgs {
+        if self.db.is_some() || self.tsv.is_some() || !self.mrt.is_empty() {
+            self
+        } else {
+            other
+        }
+    }
+
+    fn open(self) -> Result<Box<dyn AsnProvider>, Error> {
+        commands::asn::open_asn_source(&commands::asn::AsnSources {
+            db: self.db,
+            tsv: self.tsv,
+            mrt: self.mrt,
+        })
+    }
+}
+
+/// Geolocation data sources shared by `cc` and `cc ranges`
+#[derive(Args, Default)]
+struct GeoArgs {
+    /// MaxMind-format database (defaults to geo.mmdb in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    db: Option<PathBuf>,
+    /// Range table built with `cc build-db` (defaults to geo.table in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    table: Option<PathBuf>,
+    /// IP2Location LITE or DB-IP style CSV export; repeat for separate
+    /// IPv4 and IPv6 files (defaults to geo.csv in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    csv: Vec<PathBuf>,
+    /// RFC 8805 geofeed; repeat for several feeds (defaults to geo.geofeed
+    /// in ipchecker.json)
+    #[arg(long, value_name = "FILE")]
+    geofeed: Vec<PathBuf>,
+    /// Order in which the data sources are asked, e.g. mmdb,rir
+    /// (defaults to geo.providers in ipchecker.json, then geofeed,mmdb,rir,csv)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    providers: Vec<ProviderKind>,
+}
+
+impl GeoArgs {
+    /// Combine with the options given before the subcommand; options given
+    /// after it take precedence
+    fn or(self, other: GeoArgs) -> GeoArgs {
+        GeoArgs {
+            db: self.db.or(other.db),
+            table: self.table.or(other.table),
+            csv: if self.csv.is_empty() {
+                other.csv
+            } else {
+                self.csv
+            },
+            geofeed: if self.geofeed.is_empty() {
+                other.geofeed
+            } else {
+                self.geofeed
+            },
+            providers: if self.providers.is_empty() {
+                other.providers
+            } else {
+                self.providers
+            },
+        }
+    }
+
+    fn open(self) -> Result<GeoChain, Error> {
+        commands::cc::open_geo_chain(&commands::cc::GeoSources {
+            db: self.db,
+            table: self.table,
+            csv: self.csv,
+            geofeed: self.geofeed,
+            providers: self.providers,
+        })
+    }
+}
+
+#[derive(Subcommand)]
+enum CidrAction {
+    /// List the routes overlapping a network with their origin AS
+    Routes {
+        /// CIDR network (e.g., 1.0.0.0/16)
+        network: String,
+        #[command(flatten)]
+        data: AsnArgs,
+    },
+    /// Generate BIND reverse zones for a network from a hosts mapping
+    ReverseZone {
+        /// CIDR network (e.g., 192.0.2.0/24 or 2001:db8::/48)
+        network: String,
+        /// CSV file of IP address and host name rows
+        #[arg(long, value_name = "FILE")]
+        hosts: PathBuf,
+        /// Name server for the NS records; repeat for several
+        /// (defaults to ns1.example.com and ns2.example.com placeholders)
+        #[arg(long = "ns", value_name = "NAME")]
+        nameservers: Vec<String>,
+    },
+    /// Show the address space added and removed between two address/CIDR
+    /// lists, as minimal CIDRs
+    Diff {
+        /// Old list, one address or CIDR per line ("-" for stdin)
+        old: PathBuf,
+        /// New list
+        new: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum CalcAction {
+    /// Add an offset to an address (e.g. 192.0.2.1 5)
+    Add {
+        address: String,
+        #[arg(allow_negative_numbers = true)]
+        offset: String,
+    },
+    /// Subtract an offset from an address
+    Sub {
+        address: String,
+        #[arg(allow_negative_numbers = true)]
+        offset: String,
+    },
+    /// The next address
+    Next { address: String },
+    /// The previous address
+    Prev { address: String },
+    /// The Nth usable host of a network, counting from 1 (-1 is the last)
+    Host {
+        /// CIDR network (e.g., 192.0.2.0/24)
+        network: String,
+        #[arg(allow_negative_numbers = true)]
+        n: String,
+    },
+    /// Number of addresses between two addresses
+    Distance { from: String, to: String },
+    /// Number of leading bits two addresses share
+    CommonPrefix { a: String, b: String },
+    /// Position of a subnet among the subnets of its size in a parent,
+    /// counting from 0
+    SubnetIndex {
+        /// CIDR subnet (e.g., 192.0.2.64/26)
+        subnet: String,
+        /// CIDR parent network (e.g., 192.0.2.0/24)
+        parent: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CcAction {
+    /// Build a country range table from RIR delegated-*-extended statistics files
+    BuildDb {
+        /// Range table file to write
+        #[arg(long, value_name = "FILE")]
+        table: PathBuf,
+        /// delegated-<rir>-extended files (AFRINIC, APNIC, ARIN, LACNIC, RIPE NCC)
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// List the aggregated CIDR blocks attributed to countries (e.g. JP,KR)
+    Ranges {
+        /// Comma-separated ISO 3166-1 alpha-2 country codes
+        #[arg(required = true, value_delimiter = ',')]
+        countries: Vec<String>,
+        /// Address families to export
+        #[arg(long, value_enum, default_value_t = IpFamily::Both)]
+        family: IpFamily,
+        /// Text format of the list
+        #[arg(long, value_enum, default_value_t = ExportFormat::Plain)]
+        format: ExportFormat,
+        /// nginx variable or nftables/ipset set name
+        #[arg(long, default_value = "geoblock")]
+        name: String,
+        #[command(flatten)]
+        geo: GeoArgs,
+    },
+    /// Work with RFC 8805 geofeeds
+    Geofeed {
+        #[command(subcommand)]
+        action: GeofeedAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum GeofeedAction {
+    /// Validate a geofeed: prefixes, host bits, overlaps and ISO 3166 codes
+    Check {
+        /// Geofeed CSV file
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum AsnAction {
+    /// List the aggregated CIDR blocks originated by an AS
+    Prefixes {
+        /// AS number, e.g. 13335 or AS13335
+        asn: String,
+        /// Address families to export
+        #[arg(long, value_enum, default_value_t = IpFamily::Both)]
+        family: IpFamily,
+        /// Text format of the list
+        #[arg(long, value_enum, default_value_t = ExportFormat::Plain)]
+        format: ExportFormat,
+        /// nginx variable or nftables/ipset set name
+        #[arg(long, default_value = "asn")]
+        name: String,
+        #[command(flatten)]
+        data: AsnArgs,
+    },
+}
+
+/// Unwrap a positional argument that is required outside of batch mode
+fn required(value: Option<String>, name: &str) -> String {
+    value.unwrap_or_else(|| {
+        Cli::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                format!(
+                    "the argument <{}> is required unless --input is given",
+                    name
+                ),
+            )
+            .exit()
+    })
+}
+
+/// Reject the global `--input` for a subcommand without a batch mode
+fn reject_input(input: Option<&PathBuf>, command: &str) -> Result<(), Error> {
+    match input {
+        Some(input) => Err(Error::invalid_input(
+            input.display().to_string(),
+            format!("--input is not supported by {}", command),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Run the selected command and return the process exit code
+fn run(cli: Cli) -> Result<i32, Error> {
+    let (verbose, format) = (cli.verbose, cli.output);
+
+    match cli.command {
         Commands::Crawler { ip_address } => {
-            commands::crawler::check_crawler(&ip_address, cli.verbose)
+            match InputSource::from_args(cli.input.as_ref(), ip_address.as_deref()) {
+                Some(source) => batch::run_batch(&source, format, verbose, |line| {
+                    commands::crawler::crawler_report(line)
+                })
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let ip_address = required(ip_address, "IP_ADDRESS");
+                    commands::crawler::check_crawler(&ip_address, verbose, format).map(|_| 0)
+                }
+            }
+        }
+        Commands::Cidr {
+            action: Some(CidrAction::Routes { network, data }),
+            ..
+        } => {
+            reject_input(cli.input.as_ref(), "cidr routes")?;
+            let source = data.open()?;
+            commands::cidr::check_cidr_routes(&network, source.as_ref(), verbose, format).map(|_| 0)
+        }
+        Commands::Cidr {
+            action:
+                Some(CidrAction::ReverseZone {
+                    network,
+                    hosts,
+                    nameservers,
+                }),
+            ..
+        } => {
+            reject_input(cli.input.as_ref(), "cidr reverse-zone")?;
+            commands::cidr::check_reverse_zone(&network, &hosts, &nameservers, verbose, format)
+                .map(|_| 0)
+        }
+        Commands::Cidr {
+            action: Some(CidrAction::Diff { old, new }),
+            ..
+        } => {
+            reject_input(cli.input.as_ref(), "cidr diff")?;
+            commands::cidr::check_cidr_diff(&old, &new, verbose, format).map(|_| 0)
+        }
+        Commands::Cidr {
+            action: None,
+            network1,
+            network2,
+        } => match InputSource::from_args(cli.input.as_ref(), network1.as_deref()) {
+            Some(source) => batch::run_batch(
+                &source,
+                format,
+                verbose,
+                commands::cidr::cidr_overlap_line_report,
+            )
+            .map(|summary| summary.exit_code()),
+            None => {
+                let network1 = required(network1, "NETWORK1");
+                let network2 = required(network2, "NETWORK2");
+                commands::cidr::check_cidr_overlap(&network1, &network2, verbose, format).map(|_| 0)
+            }
+        },
+        Commands::Cc {
+            action: Some(action),
+            geo,
+            ..
+        } => match *action {
+            CcAction::BuildDb { table, files } => {
+                reject_input(cli.input.as_ref(), "cc build-db")?;
+                let report = commands::cc::build_country_table(&files, &table)?;
+                output::print_report(&report, format, verbose).map(|_| 0)
+            }
+            CcAction::Geofeed {
+                action: GeofeedAction::Check { file },
+            } => {
+                reject_input(cli.input.as_ref(), "cc geofeed check")?;
+                commands::cc::check_geofeed(&file, verbose, format).map(|_| 0)
+            }
+            CcAction::Ranges {
+                countries,
+                family,
+                format: export_format,
+                name,
+                geo: ranges_geo,
+            } => {
+                reject_input(cli.input.as_ref(), "cc ranges")?;
+                let chain = ranges_geo.or(geo).open()?;
+                commands::cc::check_country_ranges(
+                    &countries,
+                    family,
+                    export_format,
+                    &name,
+                    &chain,
+                    verbose,
+                    format,
+                )
+                .map(|_| 0)
+            }
+        },
+        Commands::Cc {
+            action: None,
+            ip_address,
+            geo,
+        } => {
+            let db = geo.open()?;
+            match InputSource::from_args(cli.input.as_ref(), ip_address.as_deref()) {
+                Some(source) => batch::run_batch(&source, format, verbose, |line| {
+                    commands::cc::country_code_report(line, &db)
+                })
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let ip_address = required(ip_address, "IP_ADDRESS");
+                    commands::cc::check_country_code(&ip_address, &db, verbose, format).map(|_| 0)
+                }
+            }
+        }
+        Commands::Rpki {
+            prefix,
+            origin,
+            vrps,
+        } => {
+            let vrps = commands::rpki::open_vrps(vrps.as_deref())?;
+            match InputSource::from_args(cli.input.as_ref(), prefix.as_deref()) {
+                Some(input) => batch::run_batch(&input, format, verbose, |line| {
+                    commands::rpki::rpki_line_report(line, &vrps)
+                })
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let prefix = required(prefix, "PREFIX");
+                    let origin = required(origin, "ORIGIN");
+                    commands::rpki::check_rpki(&prefix, &origin, &vrps, verbose, format).map(|_| 0)
+                }
+            }
+        }
+        Commands::PrefixList {
+            query,
+            rpsl,
+            family,
+            format: list_format,
+            name,
+            max_length,
+            max_length_v6,
+        } => {
+            reject_input(cli.input.as_ref(), "prefix-list")?;
+            let irr = commands::prefix_list::open_irr(&rpsl)?;
+            let options = commands::prefix_list::PrefixListOptions {
+                family,
+                format: list_format,
+                name,
+                max_length,
+                max_length_v6,
+            };
+            commands::prefix_list::check_prefix_list(&query, &options, &irr, verbose, format)
+                .map(|_| 0)
+        }
+        Commands::Convert {
+            address,
+            family,
+            lenient,
+        } => match InputSource::from_args(cli.input.as_ref(), address.as_deref()) {
+            Some(source) => batch::run_batch(&source, format, verbose, |line| {
+                commands::convert::convert_report(line, family, lenient)
+            })
+            .map(|summary| summary.exit_code()),
+            None => {
+                let address = required(address, "ADDRESS");
+                commands::convert::check_convert(&address, family, lenient, verbose, format)
+                    .map(|_| 0)
+            }
+        },
+        Commands::Normalize { address } => {
+            match InputSource::from_args(cli.input.as_ref(), address.as_deref()) {
+                Some(source) => batch::run_batch(
+                    &source,
+                    format,
+                    verbose,
+                    commands::convert::normalize_report,
+                )
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let address = required(address, "ADDRESS");
+                    commands::convert::check_normalize(&address, verbose, format).map(|_| 0)
+                }
+            }
+        }
+        Commands::SsrfCheck { url } => {
+            match InputSource::from_args(cli.input.as_ref(), url.as_deref()) {
+                Some(source) => batch::run_batch(&source, format, verbose, |line| {
+                    commands::ssrf::ssrf_report(line, &SystemResolver)
+                })
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let url = required(url, "URL");
+                    commands::ssrf::check_ssrf(&url, &SystemResolver, verbose, format).map(|_| 0)
+                }
+            }
         }
-        Commands::Cidr { network1, network2 } => {
-            commands::cidr::check_cidr_overlap(&network1, &network2, cli.verbose)
+        Commands::Extract {
+            mut files,
+            family,
+            positions,
+        } => {
+            files.extend(cli.input);
+            commands::extract::check_extract(&files, family, positions, verbose, format).map(|_| 0)
         }
-        Commands::Cc { ip_address } => commands::cc::check_country_code(&ip_address, cli.verbose),
-    };
+        Commands::Sort {
+            mut files,
+            unique,
+            canonicalize,
+            remove_covered,
+        } => {
+            files.extend(cli.input);
+            let options = commands::sort::SortOptions {
+                unique,
+                canonicalize,
+                remove_covered,
+            };
+            commands::sort::check_sort(&files, options, verbose, format).map(|_| 0)
+        }
+        Commands::Calc { action } => {
+            reject_input(cli.input.as_ref(), "calc")?;
+            let calculation = match action {
+                CalcAction::Add { address, offset } => Calculation::Add { address, offset },
+                CalcAction::Sub { address, offset } => Calculation::Subtract { address, offset },
+                CalcAction::Next { address } => Calculation::Next { address },
+                CalcAction::Prev { address } => Calculation::Previous { address },
+                CalcAction::Host { network, n } => Calculation::Host { network, n },
+                CalcAction::Distance { from, to } => Calculation::Distance { from, to },
+                CalcAction::CommonPrefix { a, b } => Calculation::CommonPrefix { a, b },
+                CalcAction::SubnetIndex { subnet, parent } => {
+                    Calculation::SubnetIndex { subnet, parent }
+                }
+            };
+            commands::calc::check_calc(&calculation, verbose, format).map(|_| 0)
+        }
+        Commands::Asn {
+            action:
+                Some(AsnAction::Prefixes {
+                    asn,
+                    family,
+                    format: export_format,
+                    name,
+                    data: prefixes_data,
+                }),
+            data,
+            ..
+        } => {
+            reject_input(cli.input.as_ref(), "asn prefixes")?;
+            let source = prefixes_data.or(data).open()?;
+            commands::asn::check_asn_prefixes(
+                &asn,
+                family,
+                export_format,
+                &name,
+                source.as_ref(),
+                verbose,
+                format,
+            )
+            .map(|_| 0)
+        }
+        Commands::Asn {
+            action: None,
+            ip_address,
+            data,
+        } => {
+            let source = data.open()?;
+            match InputSource::from_args(cli.input.as_ref(), ip_address.as_deref()) {
+                Some(input) => batch::run_batch(&input, format, verbose, |line| {
+                    commands::asn::asn_report(line, source.as_ref())
+                })
+                .map(|summary| summary.exit_code()),
+                None => {
+                    let ip_address = required(ip_address, "IP_ADDRESS");
+                    commands::asn::check_asn(&ip_address, source.as_ref(), verbose, format)
+                        .map(|_| 0)
+                }
+            }
+        }
+    }
+}
 
-    if let Err(e) = result {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+fn main() {
+    let cli = Cli::parse();
+
+    match run(cli) {
+        Ok(0) => {}
+        Ok(code) => std::process::exit(code),
+        Err(e) => {
+            // Negative results are already described by the command output
+            if !matches!(e, Error::NotFound(_)) {
+                eprintln!("Error: {}", e);
+            }
+            std::process::exit(e.exit_code());
+        }
     }
 }
@@ -0,0 +1,167 @@
+use crate::error::Error;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// Output format selected with the global `--output` flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable text (default)
+    #[default]
+    Text,
+    /// Pretty-printed JSON object
+    Json,
+    /// YAML document
+    Yaml,
+    /// CSV with a header row
+    Csv,
+    /// One JSON object per line
+    Ndjson,
+}
+
+/// A command result that can be rendered in every output format
+pub trait Report: Serialize {
+    /// Human readable rendering, as printed by the text output
+    fn render_text(&self, verbose: bool) -> String;
+
+    /// Column names for CSV output
+    fn csv_header() -> &'static [&'static str];
+
+    /// Field values for CSV output, in the same order as `csv_header`
+    fn csv_record(&self) -> Vec<String>;
+
+    /// All CSV rows of the report. Reports that hold a list (of networks,
+    /// prefixes, ...) override this to emit one row per item.
+    fn csv_records(&self) -> Vec<Vec<String>> {
+        vec![self.csv_record()]
+    }
+
+    /// Whether the report describes a negative result (no overlap, not a crawler, ...)
+    fn is_negative(&self) -> bool {
+        false
+    }
+
+    /// Problems with the input that did not stop the check, e.g. a
+    /// link-local address without a zone ID
+    fn warnings(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// Text rendering of a report followed by its warnings
+pub fn render_text<R: Report>(report: &R, verbose: bool) -> String {
+    let mut text = report.render_text(verbose);
+    for warning in report.warnings() {
+        text.push_str(&format!("⚠ Warning: {}\n", warning));
+    }
+    text
+}
+
+/// Render a report in the given output format
+pub fn render<R: Report>(report: &R, format: OutputFormat, verbose: bool) -> Result<String, Error> {
+    let internal = |e: &dyn std::fmt::Display| Error::Internal(e.to_string());
+    match format {
+        OutputFormat::Text => Ok(render_text(report, verbose)),
+        OutputFormat::Json => serde_json::to_string_pretty(report)
+            .map(|json| json + "\n")
+            .map_err(|e| internal(&e)),
+        OutputFormat::Ndjson => serde_json::to_string(report)
+            .map(|json| json + "\n")
+            .map_err(|e| internal(&e)),
+        OutputFormat::Yaml => serde_yaml::to_string(report).map_err(|e| internal(&e)),
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer
+                .write_record(R::csv_header())
+                .map_err(|e| internal(&e))?;
+            for record in report.csv_records() {
+                writer.write_record(record).map_err(|e| internal(&e))?;
+            }
+            let bytes = writer.into_inner().map_err(|e| internal(&e))?;
+            String::from_utf8(bytes).map_err(|e| internal(&e))
+        }
+    }
+}
+
+/// Print a report to stdout in the given output format
+pub fn print_report<R: Report>(
+    report: &R,
+    format: OutputFormat,
+    verbose: bool,
+) -> Result<(), Error> {
+    print!("{}", render(report, format, verbose)?);
+    Ok(())
+}
+
+/// Format an optional value for a CSV cell (empty when absent)
+pub fn csv_optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(ToString::to_string).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        name: String,
+        count: u32,
+    }
+
+    impl Report for Sample {
+        fn render_text(&self, verbose: bool) -> String {
+            if verbose {
+                format!("{} ({})\n", self.name, self.count)
+            } else {
+                format!("{}\n", self.name)
+            }
+        }
+
+        fn csv_header() -> &'static [&'static str] {
+            &["name", "count"]
+        }
+
+        fn csv_record(&self) -> Vec<String> {
+            vec![self.name.clone(), self.count.to_string()]
+        }
+    }
+
+    fn sample() -> Sample {
+        Sample {
+            name: "a, b".to_string(),
+            count: 3,
+        }
+    }
+
+    #[test]
+    fn test_render_json_formats() {
+        let json = render(&sample(), OutputFormat::Json, false).unwrap();
+        assert!(json.contains("\"count\": 3"));
+
+        let ndjson = render(&sample(), OutputFormat::Ndjson, false).unwrap();
+        assert_eq!(ndjson, "{\"name\":\"a, b\",\"count\":3}\n");
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let yaml = render(&sample(), OutputFormat::Yaml, false).unwrap();
+        assert_eq!(yaml, "name: a, b\ncount: 3\n");
+    }
+
+    #[test]
+    fn test_render_csv_quotes_fields() {
+        let csv = render(&sample(), OutputFormat::Csv, false).unwrap();
+        assert_eq!(csv, "name,count\n\"a, b\",3\n");
+    }
+
+    #[test]
+    fn test_render_text() {
+        assert_eq!(
+            render(&sample(), OutputFormat::Text, false).unwrap(),
+            "a, b\n"
+        );
+        assert_eq!(
+            render(&sample(), OutputFormat::Text, true).unwrap(),
+            "a, b (3)\n"
+        );
+    }
+}
@@ -0,0 +1,423 @@
+use crate::ip_utils::{
+    host_mask, merge_ranges, network_range, parse_cidr, range_to_cidrs, subtract_ranges,
+    value_address,
+};
+use clap::ValueEnum;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Router configuration format for prefix lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum PrefixListFormat {
+    /// The address space covered, as the fewest CIDR blocks, one per line
+    #[default]
+    Plain,
+    /// Cisco IOS `ip prefix-list` / `ipv6 prefix-list`
+    Cisco,
+    /// Junos `policy-options` prefix-list (route-filter-list when entries
+    /// accept more specific prefixes)
+    Juniper,
+    /// BIRD 2 prefix sets, one per address family
+    Bird,
+}
+
+/// A prefix-list entry: every prefix inside `prefix` whose length lies
+/// between `min_length` and `max_length`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrefixListEntry {
+    /// CIDR notation
+    pub prefix: String,
+    #[serde(skip)]
+    pub ipv4: bool,
+    #[serde(skip)]
+    pub prefix_len: u8,
+    pub min_length: u8,
+    pub max_length: u8,
+}
+
+impl PrefixListEntry {
+    fn is_exact(&self) -> bool {
+        self.min_length == self.prefix_len && self.max_length == self.prefix_len
+    }
+}
+
+/// Length intervals per network, keyed by (prefix length, network address)
+type Nodes = BTreeMap<(u8, u128), Vec<(u8, u8)>>;
+
+/// Remove the intervals in `remove` from `intervals`; both sorted and disjoint
+fn subtract_intervals(intervals: &[(u8, u8)], remove: &[(u8, u8)]) -> Vec<(u8, u8)> {
+    let widen = |list: &[(u8, u8)]| -> Vec<(u128, u128)> {
+        list.iter()
+            .map(|&(low, high)| (low as u128, high as u128))
+            .collect()
+    };
+    subtract_ranges(&widen(intervals), &widen(remove))
+        .into_iter()
+        .map(|(low, high)| (low as u8, high as u8))
+        .collect()
+}
+
+/// Intersection of two sorted, disjoint interval lists
+fn intersect_intervals(left: &[(u8, u8)], right: &[(u8, u8)]) -> Vec<(u8, u8)> {
+    let mut result = Vec::new();
+    for &(left_low, left_high) in left {
+        for &(right_low, right_high) in right {
+            let (low, high) = (left_low.max(right_low), left_high.min(right_high));
+            if low <= high {
+                result.push((low, high));
+            }
+        }
+    }
+    result.sort_unstable();
+    result
+}
+
+fn normalize(intervals: &[(u8, u8)]) -> Vec<(u8, u8)> {
+    merge_ranges(
+        intervals
+            .iter()
+            .map(|&(low, high)| (low as u128, high as u128))
+            .collect(),
+    )
+    .into_iter()
+    .map(|(low, high)| (low as u8, high as u8))
+    .collect()
+}
+
+/// Drop the lengths of each network that an enclosing network already accepts
+fn prune_covered(nodes: &mut Nodes, bits: u8) {
+    let keys: Vec<(u8, u128)> = nodes.keys().copied().collect();
+    for (len, start) in keys {
+        let mut intervals = nodes[&(len, start)].clone();
+        for ancestor_len in 0..len {
+            let ancestor = start & !host_mask(ancestor_len, bits == 32);
+            if let Some(covering) = nodes.get(&(ancestor_len, ancestor)) {
+                intervals = subtract_intervals(&intervals, covering);
+            }
+        }
+        if intervals.is_empty() {
+            nodes.remove(&(len, start));
+        } else {
+            nodes.insert((len, start), intervals);
+        }
+    }
+}
+
+/// Aggregate route prefixes of one family into prefix-list entries, the way
+/// bgpq4 `-A` does: sibling networks accepting the same lengths become one
+/// entry for their parent with a `ge`/`le` range, and entries already
+/// accepted by an enclosing entry are dropped. The entries accept exactly
+/// the given prefixes, plus their more specifics up to `max_length` when it
+/// is set; prefixes longer than `max_length` are left out.
+pub fn aggregate_prefixes(
+    prefixes: &[(u128, u8)],
+    ipv4: bool,
+    max_length: Option<u8>,
+) -> Vec<PrefixListEntry> {
+    let bits: u8 = if ipv4 { 32 } else { 128 };
+    let mut nodes = Nodes::new();
+    for &(start, len) in prefixes {
+        let high = match max_length {
+            Some(max_length) if len > max_length => continue,
+            Some(max_length) => max_length.min(bits),
+            None => len,
+        };
+        nodes.entry((len, start)).or_default().push((len, high));
+    }
+    for intervals in nodes.values_mut() {
+        *intervals = normalize(intervals);
+    }
+    prune_covered(&mut nodes, bits);
+
+    // Merge siblings bottom-up
+    for len in (1..=bits).rev() {
+        let starts: Vec<u128> = nodes
+            .range((len, 0)..=(len, u128::MAX))
+            .map(|(&(_, start), _)| start)
+            .collect();
+        let half = 1u128 << (bits - len);
+        for start in starts {
+            if start & half != 0 {
+                continue;
+            }
+            let sibling = start | half;
+            let (Some(left), Some(right)) = (nodes.get(&(len, start)), nodes.get(&(len, sibling)))
+            else {
+                continue;
+            };
+            let shared = intersect_intervals(left, right);
+            if shared.is_empty() {
+                continue;
+            }
+            for child in [start, sibling] {
+                let rest = subtract_intervals(&nodes[&(len, child)], &shared);
+                if rest.is_empty() {
+                    nodes.remove(&(len, child));
+                } else {
+                    nodes.insert((len, child), rest);
+                }
+            }
+            let parent = nodes.entry((len - 1, start)).or_default();
+            parent.extend(shared);
+            *parent = normalize(parent);
+        }
+    }
+    prune_covered(&mut nodes, bits);
+
+    // Address order, enclosing networks first
+    let mut flat: Vec<(u128, u8, u8, u8)> = nodes
+        .into_iter()
+        .flat_map(|((len, start), intervals)| {
+            intervals
+                .into_iter()
+                .map(move |(low, high)| (start, len, low, high))
+        })
+        .collect();
+    flat.sort_unstable();
+    flat.into_iter()
+        .map(|(start, len, low, high)| PrefixListEntry {
+            prefix: format!("{}/{}", value_address(start, ipv4), len),
+            ipv4,
+            prefix_len: len,
+            min_length: low,
+            max_length: high,
+        })
+        .collect()
+}
+
+/// Render a comment line in the syntax of the format
+pub fn render_comment(format: PrefixListFormat, text: &str) -> String {
+    let marker = match format {
+        PrefixListFormat::Cisco => "!",
+        _ => "#",
+    };
+    format!("{} {}\n", marker, text)
+}
+
+/// Cisco `ge`/`le` suffix of an entry
+fn cisco_range(entry: &PrefixListEntry) -> String {
+    let bits = if entry.ipv4 { 32 } else { 128 };
+    if entry.min_length > entry.prefix_len {
+        if entry.max_length == bits {
+            format!(" ge {}", entry.min_length)
+        } else {
+            format!(" ge {} le {}", entry.min_length, entry.max_length)
+        }
+    } else if entry.max_length > entry.prefix_len {
+        format!(" le {}", entry.max_length)
+    } else {
+        String::new()
+    }
+}
+
+/// Junos route-filter match type of an entry
+fn juniper_range(entry: &PrefixListEntry) -> String {
+    if entry.is_exact() {
+        "exact".to_string()
+    } else if entry.min_length == entry.prefix_len {
+        format!("upto /{}", entry.max_length)
+    } else {
+        format!(
+            "prefix-length-range /{}-/{}",
+            entry.min_length, entry.max_length
+        )
+    }
+}
+
+/// Render entries as a prefix list called `name`. BIRD sets get a
+/// `_v4`/`_v6` suffix, as a set holds one address family.
+pub fn render_prefix_list(
+    entries: &[PrefixListEntry],
+    format: PrefixListFormat,
+    name: &str,
+) -> String {
+    let mut text = String::new();
+    match format {
+        PrefixListFormat::Plain => {
+            for ipv4 in [true, false] {
+                let ranges = merge_ranges(
+                    entries
+                        .iter()
+                        .filter(|entry| entry.ipv4 == ipv4)
+                        .filter_map(|entry| parse_cidr(&entry.prefix).ok())
+                        .map(|(ip, len)| network_range(ip, len))
+                        .collect(),
+                );
+                for (start, end) in ranges {
+                    for (ip, len) in range_to_cidrs(start, end, ipv4) {
+                        text.push_str(&format!("{}/{}\n", ip, len));
+                    }
+                }
+            }
+        }
+        PrefixListFormat::Cisco => {
+            for ipv4 in [true, false] {
+                let keyword = if ipv4 { "ip" } else { "ipv6" };
+                let mut family = entries.iter().filter(|entry| entry.ipv4 == ipv4).peekable();
+                if family.peek().is_none() {
+                    continue;
+                }
+                text.push_str(&format!("no {} prefix-list {}\n", keyword, name));
+                for entry in family {
+                    text.push_str(&format!(
+                        "{} prefix-list {} permit {}{}\n",
+                        keyword,
+                        name,
+                        entry.prefix,
+                        cisco_range(entry)
+                    ));
+                }
+            }
+        }
+        PrefixListFormat::Juniper => {
+            let exact = entries.iter().all(PrefixListEntry::is_exact);
+            text.push_str("policy-options {\nreplace:\n");
+            if exact {
+                text.push_str(&format!("    prefix-list {} {{\n", name));
+                for entry in entries {
+                    text.push_str(&format!("        {};\n", entry.prefix));
+                }
+            } else {
+                text.push_str(&format!("    route-filter-list {} {{\n", name));
+                for entry in entries {
+                    text.push_str(&format!(
+                        "        {} {};\n",
+                        entry.prefix,
+                        juniper_range(entry)
+                    ));
+                }
+            }
+            text.push_str("    }\n}\n");
+        }
+        PrefixListFormat::Bird => {
+            for ipv4 in [true, false] {
+                let family: Vec<&PrefixListEntry> =
+                    entries.iter().filter(|entry| entry.ipv4 == ipv4).collect();
+                if family.is_empty() {
+                    continue;
+                }
+                text.push_str(&format!(
+                    "define {}_{} = [\n",
+                    name,
+                    if ipv4 { "v4" } else { "v6" }
+                ));
+                for (index, entry) in family.iter().enumerate() {
+                    let range = if entry.is_exact() {
+                        String::new()
+                    } else {
+                        format!("{{{},{}}}", entry.min_length, entry.max_length)
+                    };
+                    let separator = if index + 1 < family.len() { "," } else { "" };
+                    text.push_str(&format!("    {}{}{}\n", entry.prefix, range, separator));
+                }
+                text.push_str("];\n");
+            }
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefixes(cidrs: &[&str]) -> Vec<(u128, u8)> {
+        cidrs
+            .iter()
+            .map(|cidr| {
+                let (ip, len) = parse_cidr(cidr).unwrap();
+                (network_range(ip, len).0, len)
+            })
+            .collect()
+    }
+
+    fn describe(entries: &[PrefixListEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| format!("{} {}-{}", entry.prefix, entry.min_length, entry.max_length))
+            .collect()
+    }
+
+    #[test]
+    fn test_aggregate_siblings() {
+        let entries = aggregate_prefixes(
+            &prefixes(&[
+                "192.0.2.0/24",
+                "192.0.3.0/24",
+                "192.0.2.0/23",
+                "198.51.100.0/24",
+            ]),
+            true,
+            None,
+        );
+        assert_eq!(
+            describe(&entries),
+            ["192.0.2.0/23 23-24", "198.51.100.0/24 24-24"]
+        );
+    }
+
+    #[test]
+    fn test_aggregate_with_max_length() {
+        let entries = aggregate_prefixes(
+            &prefixes(&["10.0.0.0/16", "10.0.5.0/24", "10.1.0.0/16", "10.2.0.128/25"]),
+            true,
+            Some(24),
+        );
+        // The /24 is inside the /16 range, the /16s merge, the /25 is too long
+        assert_eq!(describe(&entries), ["10.0.0.0/15 16-24"]);
+    }
+
+    #[test]
+    fn test_render_formats() {
+        let mut entries = aggregate_prefixes(
+            &prefixes(&["192.0.2.0/24", "192.0.3.0/24", "198.51.100.0/24"]),
+            true,
+            None,
+        );
+        entries.extend(aggregate_prefixes(
+            &prefixes(&["2001:db8::/32"]),
+            false,
+            None,
+        ));
+
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Plain, "AS_EXAMPLE"),
+            "192.0.2.0/23\n198.51.100.0/24\n2001:db8::/32\n"
+        );
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Cisco, "AS_EXAMPLE"),
+            "no ip prefix-list AS_EXAMPLE\n\
+             ip prefix-list AS_EXAMPLE permit 192.0.2.0/23 ge 24 le 24\n\
+             ip prefix-list AS_EXAMPLE permit 198.51.100.0/24\n\
+             no ipv6 prefix-list AS_EXAMPLE\n\
+             ipv6 prefix-list AS_EXAMPLE permit 2001:db8::/32\n"
+        );
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Juniper, "AS_EXAMPLE"),
+            "policy-options {\nreplace:\n    route-filter-list AS_EXAMPLE {\n        \
+             192.0.2.0/23 prefix-length-range /24-/24;\n        \
+             198.51.100.0/24 exact;\n        \
+             2001:db8::/32 exact;\n    }\n}\n"
+        );
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Bird, "AS_EXAMPLE"),
+            "define AS_EXAMPLE_v4 = [\n    192.0.2.0/23{24,24},\n    198.51.100.0/24\n];\n\
+             define AS_EXAMPLE_v6 = [\n    2001:db8::/32\n];\n"
+        );
+    }
+
+    #[test]
+    fn test_juniper_prefix_list() {
+        let entries = aggregate_prefixes(&prefixes(&["198.51.100.0/24"]), true, None);
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Juniper, "NN"),
+            "policy-options {\nreplace:\n    prefix-list NN {\n        198.51.100.0/24;\n    }\n}\n"
+        );
+        let entries = aggregate_prefixes(&prefixes(&["10.0.0.0/8"]), true, Some(32));
+        assert_eq!(
+            render_prefix_list(&entries, PrefixListFormat::Cisco, "NN"),
+            "no ip prefix-list NN\nip prefix-list NN permit 10.0.0.0/8 le 32\n"
+        );
+    }
+}
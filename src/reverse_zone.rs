@@ -0,0 +1,201 @@
+use crate::error::Error;
+use crate::ip_utils::{ReverseZone, parse_ip_address};
+use serde::Serialize;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Name servers written when none are given
+pub const PLACEHOLDER_NAMESERVERS: [&str; 2] = ["ns1.example.com.", "ns2.example.com."];
+
+/// One row of a hosts mapping file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostEntry {
+    pub line: usize,
+    pub ip: IpAddr,
+    /// Fully qualified, with a trailing dot
+    pub hostname: String,
+}
+
+/// A PTR record inside a reverse zone
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PtrRecord {
+    pub ip: IpAddr,
+    /// Owner name relative to the zone, e.g. "10"
+    pub owner: String,
+    /// Target, fully qualified with a trailing dot
+    pub hostname: String,
+}
+
+/// Add a trailing dot to a host name
+pub fn fully_qualified(name: &str) -> String {
+    let name = name.trim();
+    if name.ends_with('.') {
+        name.to_string()
+    } else {
+        format!("{}.", name)
+    }
+}
+
+/// Whether `name` is usable as a PTR target: dot-separated labels of
+/// letters, digits, hyphens and underscores, at most 63 characters each
+fn is_hostname(name: &str) -> bool {
+    let name = name.strip_suffix('.').unwrap_or(name);
+    !name.is_empty()
+        && name.len() <= 253
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+/// Parse a hosts mapping: CSV rows of IP address and host name. Blank
+/// lines and `#` comments are skipped, and the first row may be a header.
+pub fn parse_hosts(content: &str, source: &str) -> Result<Vec<HostEntry>, Error> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut hosts = Vec::new();
+    let mut first_row = true;
+    for record in reader.records() {
+        let record = record.map_err(|e| {
+            let line = e.position().map(|position| position.line() as usize);
+            Error::data_parse(source, line, e)
+        })?;
+        let line = record
+            .position()
+            .map_or(0, |position| position.line() as usize);
+        let fields: Vec<&str> = record.iter().collect();
+        if fields.iter().all(|field| field.is_empty()) || fields[0].starts_with('#') {
+            continue;
+        }
+        let header_allowed = std::mem::replace(&mut first_row, false);
+        let parse_error = |message: String| Error::data_parse(source, Some(line), message);
+
+        let [address, hostname] = fields[..] else {
+            return Err(parse_error(format!(
+                "expected an IP address and a host name, found {} columns",
+                fields.len()
+            )));
+        };
+        let ip = match parse_ip_address(address) {
+            Ok(ip) => ip,
+            Err(_) if header_allowed => continue,
+            Err(_) => return Err(parse_error(format!("invalid IP address '{}'", address))),
+        };
+        if !is_hostname(hostname) {
+            return Err(parse_error(format!("invalid host name '{}'", hostname)));
+        }
+        hosts.push(HostEntry {
+            line,
+            ip,
+            hostname: fully_qualified(hostname),
+        });
+    }
+    Ok(hosts)
+}
+
+/// Load a hosts mapping file
+pub fn open_hosts(path: &Path) -> Result<Vec<HostEntry>, Error> {
+    let source = path.display().to_string();
+    let content = fs::read_to_string(path).map_err(|e| Error::data_parse(&source, None, e))?;
+    parse_hosts(&content, &source)
+}
+
+/// Render a BIND zone file with placeholder SOA values, the name servers
+/// and the PTR records. For RFC 2317 zones, the NS and CNAME records to add
+/// to the parent /24 zone follow as comments.
+pub fn render_zone(zone: &ReverseZone, records: &[PtrRecord], nameservers: &[String]) -> String {
+    let primary = nameservers
+        .first()
+        .map_or(PLACEHOLDER_NAMESERVERS[0], String::as_str);
+    let mut text = format!("$ORIGIN {}.\n$TTL 3600\n", zone.name);
+    text.push_str(&format!(
+        "@\tIN\tSOA\t{} hostmaster.example.com. (\n\
+         \t\t1\t; serial\n\
+         \t\t3600\t; refresh\n\
+         \t\t900\t; retry\n\
+         \t\t604800\t; expire\n\
+         \t\t3600 )\t; negative caching TTL\n",
+        primary
+    ));
+    for nameserver in nameservers {
+        text.push_str(&format!("\tIN\tNS\t{}\n", nameserver));
+    }
+    for record in records {
+        text.push_str(&format!("{}\tIN\tPTR\t{}\n", record.owner, record.hostname));
+    }
+
+    if let Some(parent) = &zone.parent {
+        let label = zone
+            .name
+            .strip_suffix(&format!(".{}", parent))
+            .unwrap_or(&zone.name);
+        text.push_str(&format!("; Delegation records for {}:\n", parent));
+        for nameserver in nameservers {
+            text.push_str(&format!(";{}\tIN\tNS\t{}\n", label, nameserver));
+        }
+        for record in records {
+            text.push_str(&format!(
+                ";{}\tIN\tCNAME\t{}.{}.\n",
+                record.owner, record.owner, zone.name
+            ));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ip_utils::reverse_zones;
+
+    #[test]
+    fn test_parse_hosts() {
+        let hosts = parse_hosts(
+            "ip,hostname\n# gateways\n192.0.2.1, gw.example.net\n\n2001:db8::1,web.example.net.\n",
+            "hosts.csv",
+        )
+        .unwrap();
+        assert_eq!(hosts.len(), 2);
+        assert_eq!(hosts[0].line, 3);
+        assert_eq!(hosts[0].hostname, "gw.example.net.");
+        assert_eq!(hosts[1].hostname, "web.example.net.");
+
+        let err = parse_hosts("192.0.2.1,gw\n192.0.2.x,web\n", "hosts.csv").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse hosts.csv at line 2: invalid IP address '192.0.2.x'"
+        );
+        let err = parse_hosts("192.0.2.1,bad name\n", "hosts.csv").unwrap_err();
+        assert!(err.to_string().ends_with("invalid host name 'bad name'"));
+        assert!(parse_hosts("192.0.2.1\n", "hosts.csv").is_err());
+    }
+
+    #[test]
+    fn test_render_classless_zone() {
+        let zone = &reverse_zones("192.0.2.128".parse().unwrap(), 26)[0];
+        let records = [PtrRecord {
+            ip: "192.0.2.130".parse().unwrap(),
+            owner: "130".to_string(),
+            hostname: "mail.example.net.".to_string(),
+        }];
+        let text = render_zone(zone, &records, &["ns.example.net.".to_string()]);
+        assert!(text.starts_with(
+            "$ORIGIN 128/26.2.0.192.in-addr.arpa.\n$TTL 3600\n@\tIN\tSOA\tns.example.net. hostmaster.example.com. (\n"
+        ));
+        assert!(text.ends_with(
+            "\tIN\tNS\tns.example.net.\n\
+             130\tIN\tPTR\tmail.example.net.\n\
+             ; Delegation records for 2.0.192.in-addr.arpa:\n\
+             ;128/26\tIN\tNS\tns.example.net.\n\
+             ;130\tIN\tCNAME\t130.128/26.2.0.192.in-addr.arpa.\n"
+        ));
+    }
+}
@@ -0,0 +1,352 @@
+use crate::asn::parse_asn;
+use crate::error::Error;
+use crate::ip_utils::{address_value, host_mask, parse_cidr, value_address};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+
+/// Validated ROA payload: `asn` may originate `prefix` and any more specific
+/// prefix up to `max_length` bits
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Vrp {
+    /// CIDR notation
+    pub prefix: String,
+    pub max_length: u8,
+    pub asn: u32,
+    /// Trust anchor, e.g. `apnic`
+    pub ta: Option<String>,
+}
+
+/// Route origin validation state (RFC 6811, section 2)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RovState {
+    /// A VRP covers the route and authorizes its origin and length
+    Valid,
+    /// VRPs cover the route but none of them matches it
+    Invalid,
+    /// No VRP covers the route
+    NotFound,
+}
+
+impl RovState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RovState::Valid => "valid",
+            RovState::Invalid => "invalid",
+            RovState::NotFound => "not_found",
+        }
+    }
+}
+
+/// Why a route is invalid
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InvalidReason {
+    /// No covering VRP names the origin AS
+    Origin,
+    /// A covering VRP names the origin AS, but the route is more specific
+    /// than its maxLength allows
+    MaxLength,
+}
+
+impl InvalidReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InvalidReason::Origin => "origin",
+            InvalidReason::MaxLength => "max_length",
+        }
+    }
+}
+
+/// Outcome of validating one route
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Validation {
+    pub state: RovState,
+    pub reason: Option<InvalidReason>,
+    /// VRPs that make the route valid
+    pub matched: Vec<Vrp>,
+    /// Every VRP covering the route, least specific first
+    pub covering: Vec<Vrp>,
+}
+
+/// One `roas` entry of an rpki-client or Routinator JSON export
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawVrp {
+    prefix: String,
+    max_length: Option<u8>,
+    asn: RawAsn,
+    ta: Option<String>,
+}
+
+/// rpki-client writes AS numbers as integers, Routinator as "AS13335"
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum RawAsn {
+    Number(u32),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+struct RawVrpFile {
+    roas: Vec<RawVrp>,
+}
+
+/// VRPs indexed by family, prefix length and network address
+#[derive(Debug, Clone, Default)]
+pub struct VrpTable {
+    source: String,
+    vrps: BTreeMap<(bool, u8, u128), Vec<Vrp>>,
+    len: usize,
+}
+
+impl VrpTable {
+    /// Parse a VRP export: the `json` output of rpki-client, or the `json`
+    /// or `jsonext` output of Routinator. Both hold a `roas` array of
+    /// `{asn, prefix, maxLength, ta}` objects.
+    pub fn parse(content: &str, source: &str) -> Result<Self, Error> {
+        let raw: RawVrpFile = serde_json::from_str(content)
+            .map_err(|e| Error::data_parse(source, Some(e.line()), e))?;
+
+        let mut table = VrpTable {
+            source: source.to_string(),
+            ..VrpTable::default()
+        };
+        for (index, entry) in raw.roas.into_iter().enumerate() {
+            let entry_error = |message: String| {
+                Error::data_parse(source, None, format!("roas[{}]: {}", index, message))
+            };
+
+            let asn = match entry.asn {
+                RawAsn::Number(asn) => asn,
+                RawAsn::Text(text) => parse_asn(&text)
+                    .ok_or_else(|| entry_error(format!("invalid AS number '{}'", text)))?,
+            };
+            let (ip, prefix_len) =
+                parse_cidr(&entry.prefix).map_err(|e| entry_error(e.to_string()))?;
+            let (ipv4, value) = address_value(ip);
+            if value & host_mask(prefix_len, ipv4) != 0 {
+                return Err(entry_error(format!(
+                    "prefix {} has host bits set",
+                    entry.prefix
+                )));
+            }
+            let max_length = entry.max_length.unwrap_or(prefix_len);
+            let max_bits = if ipv4 { 32 } else { 128 };
+            if max_length < prefix_len || max_length > max_bits {
+                return Err(entry_error(format!(
+                    "maxLength {} is out of range for {}",
+                    max_length, entry.prefix
+                )));
+            }
+
+            table
+                .vrps
+                .entry((ipv4, prefix_len, value))
+                .or_default()
+                .push(Vrp {
+                    prefix: format!("{}/{}", ip, prefix_len),
+                    max_length,
+                    asn,
+                    ta: entry.ta,
+                });
+            table.len += 1;
+        }
+        Ok(table)
+    }
+
+    /// Load a VRP export from disk
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let source = path.display().to_string();
+        let content = fs::read_to_string(path).map_err(|e| Error::config(path, e))?;
+        Self::parse(&content, &source)
+    }
+
+    /// File the VRPs were loaded from
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// VRPs whose prefix contains the route `ip/prefix_len`, least specific first
+    pub fn covering(&self, ip: IpAddr, prefix_len: u8) -> Vec<&Vrp> {
+        let (ipv4, value) = address_value(ip);
+        (0..=prefix_len)
+            .filter_map(|len| {
+                let network = value & !host_mask(len, ipv4);
+                self.vrps.get(&(ipv4, len, network))
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Validate the route `ip/prefix_len` originated by `origin` (RFC 6811).
+    /// A VRP for AS 0 covers routes but never matches them (RFC 6483).
+    pub fn validate(&self, ip: IpAddr, prefix_len: u8, origin: u32) -> Validation {
+        let covering = self.covering(ip, prefix_len);
+        let matched: Vec<Vrp> = covering
+            .iter()
+            .filter(|vrp| vrp.asn != 0 && vrp.asn == origin && prefix_len <= vrp.max_length)
+            .map(|vrp| (*vrp).clone())
+            .collect();
+
+        let (state, reason) = if covering.is_empty() {
+            (RovState::NotFound, None)
+        } else if !matched.is_empty() {
+            (RovState::Valid, None)
+        } else if covering.iter().any(|vrp| vrp.asn != 0 && vrp.asn == origin) {
+            (RovState::Invalid, Some(InvalidReason::MaxLength))
+        } else {
+            (RovState::Invalid, Some(InvalidReason::Origin))
+        };
+
+        Validation {
+            state,
+            reason,
+            matched,
+            covering: covering.into_iter().cloned().collect(),
+        }
+    }
+}
+
+/// Render a VRP as "192.0.2.0/24-24 AS64496 (ta)"
+pub fn describe_vrp(vrp: &Vrp) -> String {
+    let mut text = format!("{}-{} AS{}", vrp.prefix, vrp.max_length, vrp.asn);
+    if let Some(ta) = &vrp.ta {
+        text.push_str(&format!(" ({})", ta));
+    }
+    text
+}
+
+/// Parse "192.0.2.0/24" into its network address and prefix length,
+/// clearing any host bits
+pub fn parse_route(prefix: &str) -> Result<(IpAddr, u8), Error> {
+    let (ip, prefix_len) = parse_cidr(prefix)?;
+    let (ipv4, value) = address_value(ip);
+    Ok((
+        value_address(value & !host_mask(prefix_len, ipv4), ipv4),
+        prefix_len,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RPKI_CLIENT: &str = r#"{
+        "metadata": {"buildmachine": "rpki.example", "roas": 4},
+        "roas": [
+            {"asn": 64496, "prefix": "192.0.2.0/24", "maxLength": 24, "ta": "apnic", "expires": 1700000000},
+            {"asn": 64497, "prefix": "198.51.100.0/22", "maxLength": 24, "ta": "ripe", "expires": 1700000000},
+            {"asn": 0, "prefix": "203.0.113.0/24", "maxLength": 32, "ta": "arin", "expires": 1700000000},
+            {"asn": 64511, "prefix": "2001:db8::/32", "maxLength": 48, "ta": "ripe", "expires": 1700000000}
+        ]
+    }"#;
+
+    const ROUTINATOR: &str = r#"{
+        "metadata": {"generated": 1700000000, "generatedTime": "2023-11-14T22:13:20Z"},
+        "roas": [
+            {"asn": "AS64496", "prefix": "192.0.2.0/24", "maxLength": 24, "ta": "apnic"}
+        ]
+    }"#;
+
+    fn route(table: &VrpTable, prefix: &str, origin: u32) -> Validation {
+        let (ip, prefix_len) = parse_route(prefix).unwrap();
+        table.validate(ip, prefix_len, origin)
+    }
+
+    #[test]
+    fn test_validate() {
+        let table = VrpTable::parse(RPKI_CLIENT, "vrps.json").unwrap();
+        assert_eq!(table.len(), 4);
+
+        let validation = route(&table, "192.0.2.0/24", 64496);
+        assert_eq!(validation.state, RovState::Valid);
+        assert_eq!(validation.matched.len(), 1);
+
+        let validation = route(&table, "192.0.2.0/24", 64500);
+        assert_eq!(validation.state, RovState::Invalid);
+        assert_eq!(validation.reason, Some(InvalidReason::Origin));
+
+        // More specific than maxLength allows
+        let validation = route(&table, "192.0.2.128/25", 64496);
+        assert_eq!(validation.state, RovState::Invalid);
+        assert_eq!(validation.reason, Some(InvalidReason::MaxLength));
+
+        assert_eq!(
+            route(&table, "198.51.101.0/24", 64497).state,
+            RovState::Valid
+        );
+        assert_eq!(
+            route(&table, "198.51.101.0/25", 64497).state,
+            RovState::Invalid
+        );
+        // Less specific than the VRP: not covered
+        assert_eq!(
+            route(&table, "198.51.0.0/16", 64497).state,
+            RovState::NotFound
+        );
+
+        // AS 0 never authorizes a route
+        let validation = route(&table, "203.0.113.0/24", 0);
+        assert_eq!(validation.state, RovState::Invalid);
+        assert_eq!(validation.reason, Some(InvalidReason::Origin));
+
+        assert_eq!(
+            route(&table, "2001:db8:1::/48", 64511).state,
+            RovState::Valid
+        );
+        assert_eq!(
+            route(&table, "2001:db9::/32", 64511).state,
+            RovState::NotFound
+        );
+    }
+
+    #[test]
+    fn test_routinator_format() {
+        let table = VrpTable::parse(ROUTINATOR, "vrps.json").unwrap();
+        let validation = route(&table, "192.0.2.0/24", 64496);
+        assert_eq!(validation.state, RovState::Valid);
+        assert_eq!(
+            describe_vrp(&validation.matched[0]),
+            "192.0.2.0/24-24 AS64496 (apnic)"
+        );
+    }
+
+    #[test]
+    fn test_invalid_entries() {
+        let err = VrpTable::parse(
+            r#"{"roas": [{"asn": 1, "prefix": "192.0.2.0/24", "maxLength": 23}]}"#,
+            "vrps.json",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse vrps.json: roas[0]: maxLength 23 is out of range for 192.0.2.0/24"
+        );
+
+        let err = VrpTable::parse(
+            r#"{"roas": [{"asn": 1, "prefix": "192.0.2.1/24", "maxLength": 24}]}"#,
+            "vrps.json",
+        )
+        .unwrap_err();
+        assert!(err.to_string().ends_with("has host bits set"));
+
+        let err = VrpTable::parse("{\n\"roas\": [", "vrps.json").unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_DATA_PARSE);
+
+        let err = VrpTable::open("/nonexistent/vrps.json").unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_CONFIG);
+    }
+}
@@ -0,0 +1,329 @@
+use crate::asn::parse_asn;
+use crate::error::Error;
+use crate::ip_utils::{network_range, parse_cidr};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::Path;
+
+/// One RPSL object: its attributes in order, with continuation lines joined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RpslObject {
+    /// Line the object starts on
+    pub line: usize,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl RpslObject {
+    /// Object class, i.e. the name of the first attribute
+    pub fn class(&self) -> &str {
+        self.attributes
+            .first()
+            .map(|(name, _)| name.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Value of the first attribute, i.e. the object's key
+    pub fn key(&self) -> &str {
+        self.attributes
+            .first()
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_default()
+    }
+
+    /// Values of every attribute with the given name
+    pub fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.attributes
+            .iter()
+            .filter(move |(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Split an RPSL dump into objects. Objects are separated by blank lines;
+/// lines starting with `%` or `#` are comments, a `#` inside a value starts
+/// an end-of-line comment, and lines starting with whitespace or `+`
+/// continue the previous attribute.
+pub fn parse_rpsl(content: &str, source: &str) -> Result<Vec<RpslObject>, Error> {
+    let mut objects = Vec::new();
+    let mut current: Option<RpslObject> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let line_number = index + 1;
+        if line.trim().is_empty() {
+            objects.extend(current.take());
+            continue;
+        }
+        if line.starts_with('%') || line.starts_with('#') {
+            continue;
+        }
+
+        let value_of = |text: &str| {
+            let text = text.split_once('#').map_or(text, |(value, _)| value);
+            text.trim().to_string()
+        };
+
+        if line.starts_with([' ', '\t', '+']) {
+            let Some((_, value)) = current
+                .as_mut()
+                .and_then(|object| object.attributes.last_mut())
+            else {
+                return Err(Error::data_parse(
+                    source,
+                    Some(line_number),
+                    "continuation line outside of an object",
+                ));
+            };
+            let more = value_of(&line[1..]);
+            if !more.is_empty() {
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(&more);
+            }
+            continue;
+        }
+
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(Error::data_parse(
+                source,
+                Some(line_number),
+                format!("expected 'attribute: value', found '{}'", line),
+            ));
+        };
+        current
+            .get_or_insert_with(|| RpslObject {
+                line: line_number,
+                attributes: Vec::new(),
+            })
+            .attributes
+            .push((name.trim().to_ascii_lowercase(), value_of(value)));
+    }
+    objects.extend(current);
+
+    Ok(objects)
+}
+
+/// A route announced by an AS: family, network address and prefix length
+pub type RoutePrefix = (bool, u128, u8);
+
+/// Route and as-set objects loaded from IRR dumps
+#[derive(Debug, Clone, Default)]
+pub struct IrrDatabase {
+    routes: BTreeMap<u32, BTreeSet<RoutePrefix>>,
+    /// Members of each as-set, keyed by upper-case name
+    as_sets: BTreeMap<String, Vec<String>>,
+}
+
+/// ASNs an AS or as-set expands to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Expansion {
+    pub asns: BTreeSet<u32>,
+    /// Referenced as-sets that are not in the loaded data
+    pub missing: BTreeSet<String>,
+}
+
+impl IrrDatabase {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the `route`, `route6` and `as-set` objects of an RPSL dump and
+    /// return the number of objects used. Other object classes are skipped.
+    pub fn load(&mut self, content: &str, source: &str) -> Result<usize, Error> {
+        let mut count = 0;
+        for object in parse_rpsl(content, source)? {
+            let parse_error =
+                |message: String| Error::data_parse(source, Some(object.line), message);
+            match object.class() {
+                class @ ("route" | "route6") => {
+                    let (ip, prefix_len) = parse_cidr(object.key())
+                        .map_err(|e| parse_error(format!("{} object: {}", class, e)))?;
+                    if ip.is_ipv4() != (class == "route") {
+                        return Err(parse_error(format!(
+                            "{} object holds the wrong address family: {}",
+                            class,
+                            object.key()
+                        )));
+                    }
+                    let origin = object.values("origin").next().ok_or_else(|| {
+                        parse_error(format!("{} {} has no origin", class, object.key()))
+                    })?;
+                    let asn = parse_asn(origin)
+                        .ok_or_else(|| parse_error(format!("invalid origin '{}'", origin)))?;
+                    // Host bits are cleared, as some IRRs accept them
+                    let (start, _) = network_range(ip, prefix_len);
+                    self.routes
+                        .entry(asn)
+                        .or_default()
+                        .insert((ip.is_ipv4(), start, prefix_len));
+                }
+                "as-set" => {
+                    let members = object
+                        .values("members")
+                        .flat_map(|value| value.split(','))
+                        .map(|member| member.trim().to_ascii_uppercase())
+                        .filter(|member| !member.is_empty());
+                    self.as_sets
+                        .entry(object.key().to_ascii_uppercase())
+                        .or_default()
+                        .extend(members);
+                }
+                _ => continue,
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Load RPSL dumps from disk (e.g. `ripe.db.route`, `ripe.db.as-set`)
+    pub fn open<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Error> {
+        let mut database = IrrDatabase::new();
+        for path in paths {
+            let path = path.as_ref();
+            let source = path.display().to_string();
+            let data = fs::read(path).map_err(|e| Error::data_parse(&source, None, e))?;
+            if data.starts_with(&[0x1f, 0x8b]) {
+                return Err(Error::data_parse(
+                    source,
+                    None,
+                    "file is compressed; decompress it first (e.g. with gunzip)",
+                ));
+            }
+            let content = String::from_utf8_lossy(&data);
+            database.load(&content, &source)?;
+        }
+        Ok(database)
+    }
+
+    /// Whether an as-set with this name is loaded
+    pub fn has_as_set(&self, name: &str) -> bool {
+        self.as_sets.contains_key(&name.to_ascii_uppercase())
+    }
+
+    /// Expand an AS number ("AS64496") or as-set name ("AS-EXAMPLE") into
+    /// ASNs, following nested as-sets and ignoring cycles
+    pub fn expand(&self, name: &str) -> Expansion {
+        let mut expansion = Expansion::default();
+        let mut visited = BTreeSet::new();
+        let mut pending = vec![name.trim().to_ascii_uppercase()];
+
+        while let Some(name) = pending.pop() {
+            if let Some(asn) = parse_asn(&name) {
+                expansion.asns.insert(asn);
+                continue;
+            }
+            if !visited.insert(name.clone()) {
+                continue;
+            }
+            match self.as_sets.get(&name) {
+                Some(members) => pending.extend(members.iter().rev().cloned()),
+                None => {
+                    expansion.missing.insert(name);
+                }
+            }
+        }
+        expansion
+    }
+
+    /// Route prefixes registered for any of `asns`, deduplicated and sorted
+    pub fn prefixes(&self, asns: &BTreeSet<u32>) -> Vec<RoutePrefix> {
+        let prefixes: BTreeSet<RoutePrefix> = asns
+            .iter()
+            .filter_map(|asn| self.routes.get(asn))
+            .flatten()
+            .copied()
+            .collect();
+        prefixes.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DUMP: &str = "\
+% Sample IRR dump
+route:          192.0.2.0/24
+descr:          Example # trailing comment
+origin:         AS64496
+source:         TEST
+
+route6:         2001:db8::/32
+origin:         as64496
+source:         TEST
+
+route:          198.51.100.0/24
+origin:         AS64497
+
+as-set:         AS-EXAMPLE
+members:        AS64496,
+                AS-CUSTOMERS
++
+members:        AS64496
+source:         TEST
+
+as-set:         AS-CUSTOMERS
+members:        AS64497, AS-EXAMPLE, AS-UNKNOWN
+
+aut-num:        AS64496
+as-name:        EXAMPLE
+";
+
+    fn database() -> IrrDatabase {
+        let mut database = IrrDatabase::new();
+        assert_eq!(database.load(DUMP, "test.db").unwrap(), 5);
+        database
+    }
+
+    #[test]
+    fn test_parse_rpsl() {
+        let objects = parse_rpsl(DUMP, "test.db").unwrap();
+        assert_eq!(objects.len(), 6);
+        assert_eq!(objects[0].line, 2);
+        assert_eq!(objects[0].values("descr").next(), Some("Example"));
+        assert_eq!(
+            objects[3].values("members").collect::<Vec<_>>(),
+            ["AS64496, AS-CUSTOMERS", "AS64496"]
+        );
+
+        let err = parse_rpsl("route: 192.0.2.0/24\norigin AS1\n", "bad.db").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse bad.db at line 2: expected 'attribute: value', found 'origin AS1'"
+        );
+    }
+
+    #[test]
+    fn test_expand() {
+        let database = database();
+        let expansion = database.expand("as-example");
+        assert_eq!(expansion.asns, BTreeSet::from([64496, 64497]));
+        assert_eq!(
+            expansion.missing,
+            BTreeSet::from(["AS-UNKNOWN".to_string()])
+        );
+        assert_eq!(database.expand("AS64497").asns, BTreeSet::from([64497]));
+        assert!(database.has_as_set("AS-CUSTOMERS"));
+
+        let prefixes = database.prefixes(&expansion.asns);
+        assert_eq!(
+            prefixes,
+            [
+                (false, 0x2001_0db8 << 96, 32),
+                (true, 0xc0000200, 24),
+                (true, 0xc6336400, 24)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_route_without_origin() {
+        let err = IrrDatabase::new()
+            .load("\nroute: 192.0.2.0/24\nsource: TEST\n", "test.db")
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Failed to parse test.db at line 2: route 192.0.2.0/24 has no origin"
+        );
+    }
+}
@@ -0,0 +1,433 @@
+use crate::error::Error;
+use crate::ip_utils::{IpCategory, Notation, get_ip_info, parse_ip_lenient};
+use serde::Serialize;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, ToSocketAddrs};
+
+/// Schemes whose URLs browsers parse with the WHATWG "special" rules:
+/// backslashes count as slashes and the authority needs no `//`
+const SPECIAL_SCHEMES: [(&str, Option<u16>); 6] = [
+    ("http", Some(80)),
+    ("https", Some(443)),
+    ("ws", Some(80)),
+    ("wss", Some(443)),
+    ("ftp", Some(21)),
+    ("file", None),
+];
+
+/// Looks up the addresses of a host name
+pub trait Resolver: Send + Sync {
+    /// Every address `host` resolves to
+    fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, Error>;
+}
+
+/// Resolver backed by the operating system (`getaddrinfo`)
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, Error> {
+        let addresses = (host, 0).to_socket_addrs().map_err(|e| Error::Fetch {
+            source: format!("addresses of {}", host),
+            url: "system resolver".to_string(),
+            message: e.to_string(),
+        })?;
+        Ok(addresses.map(|address| address.ip()).collect())
+    }
+}
+
+/// Host and port a URL points to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UrlTarget {
+    /// Lower-case scheme; `None` for a bare host name
+    pub scheme: Option<String>,
+    /// Host with percent-encoding decoded, lower case, without IPv6 brackets
+    /// and zone ID
+    pub host: String,
+    /// Zone ID of an IPv6 literal (`[fe80::1%25eth0]`, RFC 6874)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zone: Option<String>,
+    /// Explicit port, else the default port of the scheme
+    pub port: Option<u16>,
+}
+
+/// An address a URL leads to, classified with the special-purpose registry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TargetAddress {
+    pub ip: IpAddr,
+    /// Address connections go to: the IPv4 address inside an IPv4-mapped,
+    /// NAT64 (64:ff9b::/96) or 6to4 (2002::/16) address, else `ip`
+    pub effective: IpAddr,
+    pub category: IpCategory,
+    /// Whether the effective address is anything but public
+    pub internal: bool,
+}
+
+impl TargetAddress {
+    pub fn new(ip: IpAddr) -> Self {
+        let effective = effective_address(ip);
+        let category = get_ip_info(&effective).category;
+        TargetAddress {
+            ip,
+            effective,
+            category,
+            internal: category != IpCategory::Public,
+        }
+    }
+}
+
+/// Outcome of checking a URL before fetching it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UrlCheck {
+    #[serde(flatten)]
+    pub target: UrlTarget,
+    /// Whether the host is an IP literal rather than a name to resolve
+    pub literal: bool,
+    /// Non-canonical notations of an IP literal host
+    pub notations: Vec<Notation>,
+    /// Literal or resolved addresses, sorted and deduplicated
+    pub addresses: Vec<TargetAddress>,
+    /// True unless some address is internal
+    pub allowed: bool,
+}
+
+impl UrlCheck {
+    /// First internal address, which is the reason for a denial
+    pub fn denied_by(&self) -> Option<&TargetAddress> {
+        self.addresses.iter().find(|address| address.internal)
+    }
+}
+
+/// IPv4 address traffic to `ip` really reaches, for IPv4-mapped,
+/// well-known NAT64 prefix and 6to4 (2002:V4ADDR::/48) addresses
+fn effective_address(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(ipv6) => {
+            if let Some(ipv4) = ipv6.to_ipv4_mapped() {
+                return IpAddr::V4(ipv4);
+            }
+            let value = u128::from(ipv6);
+            if value >> 32 == u128::from(Ipv6Addr::new(0x64, 0xff9b, 0, 0, 0, 0, 0, 0)) >> 32 {
+                return IpAddr::V4(Ipv4Addr::from(value as u32));
+            }
+            if ipv6.segments()[0] == 0x2002 {
+                return IpAddr::V4(Ipv4Addr::from((value >> 80) as u32));
+            }
+            ip
+        }
+        IpAddr::V4(_) => ip,
+    }
+}
+
+/// Decode `%XX` escapes, as browsers do for URL hosts
+fn percent_decode(text: &str) -> Option<String> {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = text.get(i + 1..i + 3)?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Whether the last label of a host is a number, which makes browsers
+/// parse the whole host as an IPv4 address
+fn ends_in_number(host: &str) -> bool {
+    let host = host.strip_suffix('.').unwrap_or(host);
+    let last = host.rsplit('.').next().unwrap_or(host);
+    match last.strip_prefix("0x").or_else(|| last.strip_prefix("0X")) {
+        Some(hex) => hex.bytes().all(|b| b.is_ascii_hexdigit()),
+        None => !last.is_empty() && last.bytes().all(|b| b.is_ascii_digit()),
+    }
+}
+
+/// Extract the host and port of a URL the way browsers read it: backslashes
+/// act as slashes for special schemes, the last `@` ends the user info and
+/// hosts are percent-decoded. A bare host name (with an optional port) is
+/// accepted too.
+pub fn parse_url_target(url: &str) -> Result<UrlTarget, Error> {
+    let invalid = |reason: &str| Error::invalid_input(url, reason);
+    let text: String = url
+        .trim()
+        .chars()
+        .filter(|c| !matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+
+    let (scheme, rest) = match text.split_once(':') {
+        Some((scheme, rest))
+            if scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+                && (rest.starts_with('/')
+                    || SPECIAL_SCHEMES
+                        .iter()
+                        .any(|(name, _)| name.eq_ignore_ascii_case(scheme))) =>
+        {
+            (Some(scheme.to_ascii_lowercase()), rest)
+        }
+        _ => (None, text.as_str()),
+    };
+    let special = scheme
+        .as_deref()
+        .map(|scheme| SPECIAL_SCHEMES.iter().find(|(name, _)| *name == scheme));
+    let default_port = special.flatten().and_then(|(_, port)| *port);
+
+    // Only special schemes (and bare hosts) treat a backslash as a slash
+    let slashes: &[char] = match special {
+        Some(None) => &['/'],
+        _ => &['/', '\\'],
+    };
+    let rest = rest.trim_start_matches(slashes);
+    let authority = rest
+        .split(|c: char| slashes.contains(&c) || matches!(c, '?' | '#'))
+        .next()
+        .unwrap_or_default();
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host_port)| host_port);
+
+    let (host, port) = if let Some(bracketed) = host_port.strip_prefix('[') {
+        let (host, after) = bracketed
+            .split_once(']')
+            .ok_or_else(|| invalid("Unterminated IPv6 address in URL"))?;
+        match after {
+            "" => (host.to_string(), None),
+            _ => match after.strip_prefix(':') {
+                Some(port) => (host.to_string(), Some(port)),
+                None => return Err(invalid("Invalid URL authority")),
+            },
+        }
+    } else if host_port.matches(':').count() > 1 && scheme.is_none() {
+        // An unbracketed IPv6 address given as a bare host
+        (host_port.to_string(), None)
+    } else {
+        match host_port.rsplit_once(':') {
+            Some((host, port)) => (host.to_string(), Some(port)),
+            None => (host_port.to_string(), None),
+        }
+    };
+
+    // An IPv6 zone ID follows "%25" in URLs (RFC 6874) and "%" elsewhere;
+    // in any other host "%" starts a percent-encoded byte
+    let (host, zone) = match host.split_once('%').filter(|_| host.contains(':')) {
+        Some((address, zone)) => {
+            let zone = zone
+                .strip_prefix("25")
+                .filter(|_| scheme.is_some())
+                .unwrap_or(zone);
+            if zone.is_empty() {
+                return Err(invalid("Invalid zone ID in URL host"));
+            }
+            (address.to_string(), Some(zone.to_string()))
+        }
+        None => (host, None),
+    };
+
+    let port = match port {
+        Some("") | None => default_port,
+        Some(port) => Some(port.parse().map_err(|_| invalid("Invalid port in URL"))?),
+    };
+    let host = percent_decode(&host)
+        .ok_or_else(|| invalid("Invalid percent-encoding in URL host"))?
+        .to_lowercase();
+    if host.is_empty() {
+        return Err(invalid("URL has no host"));
+    }
+
+    Ok(UrlTarget {
+        scheme,
+        host,
+        zone,
+        port,
+    })
+}
+
+/// Decide whether fetching `url` is safe: every address its host is
+/// (literally or through `resolver`) must be public. Obfuscated IPv4
+/// literals such as `http://0x7f.1/` are decoded first, like browsers do.
+pub fn check_url(url: &str, resolver: &dyn Resolver) -> Result<UrlCheck, Error> {
+    let target = parse_url_target(url)?;
+
+    let literal =
+        if target.host.contains(':') || ends_in_number(&target.host) {
+            Some(parse_ip_lenient(&target.host).map_err(|_| {
+                Error::invalid_input(&target.host, "Invalid IP address in URL host")
+            })?)
+        } else {
+            None
+        };
+
+    let (ips, notations) = match &literal {
+        Some(address) => (vec![address.ip], address.notations.clone()),
+        None => {
+            let ips = resolver.resolve(&target.host)?;
+            if ips.is_empty() {
+                return Err(Error::Fetch {
+                    source: format!("addresses of {}", target.host),
+                    url: "resolver".to_string(),
+                    message: "no addresses found".to_string(),
+                });
+            }
+            (ips, Vec::new())
+        }
+    };
+
+    let mut ips = ips;
+    ips.sort();
+    ips.dedup();
+    let addresses: Vec<TargetAddress> = ips.into_iter().map(TargetAddress::new).collect();
+    let allowed = !addresses.iter().any(|address| address.internal);
+
+    Ok(UrlCheck {
+        target,
+        literal: literal.is_some(),
+        notations,
+        addresses,
+        allowed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    /// Answers from a fixed table instead of DNS
+    struct StubResolver(HashMap<&'static str, Vec<IpAddr>>);
+
+    impl Resolver for StubResolver {
+        fn resolve(&self, host: &str) -> Result<Vec<IpAddr>, Error> {
+            self.0.get(host).cloned().ok_or_else(|| Error::Fetch {
+                source: format!("addresses of {}", host),
+                url: "stub".to_string(),
+                message: "NXDOMAIN".to_string(),
+            })
+        }
+    }
+
+    fn resolver() -> StubResolver {
+        let ip = |text: &str| text.parse::<IpAddr>().unwrap();
+        StubResolver(HashMap::from([
+            (
+                "example.com",
+                vec![
+                    ip("93.184.215.14"),
+                    ip("2606:2800:21f:cb07:6820:80da:af6b:8b2c"),
+                ],
+            ),
+            ("rebind.example", vec![ip("93.184.215.14"), ip("10.0.0.5")]),
+            ("mapped.example", vec![ip("::ffff:169.254.169.254")]),
+        ]))
+    }
+
+    fn target(url: &str) -> (Option<String>, String, Option<u16>) {
+        let target = parse_url_target(url).unwrap();
+        (target.scheme, target.host, target.port)
+    }
+
+    #[test]
+    fn test_parse_url_target() {
+        let some = |text: &str| Some(text.to_string());
+        assert_eq!(
+            target("https://user:pw@Example.COM:8443/path?q#f"),
+            (some("https"), "example.com".to_string(), Some(8443))
+        );
+        assert_eq!(
+            target("http://good.example@evil.example@127.0.0.1/"),
+            (some("http"), "127.0.0.1".to_string(), Some(80))
+        );
+        assert_eq!(
+            target("http:\\\\127.0.0.1\\admin"),
+            (some("http"), "127.0.0.1".to_string(), Some(80))
+        );
+        assert_eq!(
+            target("gopher://evil.example\\@127.0.0.1/"),
+            (some("gopher"), "127.0.0.1".to_string(), None)
+        );
+        assert_eq!(
+            target("http://evil.example\\@127.0.0.1/"),
+            (some("http"), "evil.example".to_string(), Some(80))
+        );
+        assert_eq!(
+            target("http://[fe80::1%25eth0]:8080/"),
+            (some("http"), "fe80::1".to_string(), Some(8080))
+        );
+        assert_eq!(
+            parse_url_target("http://[fe80::1%25eth0]/").unwrap().zone,
+            some("eth0")
+        );
+        assert_eq!(parse_url_target("fe80::1%2").unwrap().zone, some("2"));
+        assert_eq!(
+            target("http://%31%32%37.0.0.1/"),
+            (some("http"), "127.0.0.1".to_string(), Some(80))
+        );
+        assert_eq!(
+            target("localhost:8080"),
+            (None, "localhost".to_string(), Some(8080))
+        );
+        assert_eq!(target("::1"), (None, "::1".to_string(), None));
+
+        for invalid in [
+            "http://",
+            "http://[::1/",
+            "http://host:99999/",
+            "http://%zz/",
+        ] {
+            assert!(parse_url_target(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_check_url_literals() {
+        let check = check_url("http://0x7f.1/admin", &resolver()).unwrap();
+        assert!(check.literal);
+        assert!(!check.allowed);
+        assert_eq!(check.notations, [Notation::Hex, Notation::ShortForm]);
+        assert_eq!(check.denied_by().unwrap().category, IpCategory::Loopback);
+
+        let check = check_url("http://[::ffff:a9fe:a9fe]/latest/meta-data", &resolver()).unwrap();
+        let address = check.denied_by().unwrap();
+        assert_eq!(address.effective.to_string(), "169.254.169.254");
+        assert_eq!(address.category, IpCategory::LinkLocal);
+
+        let check = check_url("http://[64:ff9b::808:808]/", &resolver()).unwrap();
+        assert!(check.allowed);
+
+        let check = check_url("http://[2002:7f00:1::1]/", &resolver()).unwrap();
+        let address = check.denied_by().unwrap();
+        assert_eq!(address.effective.to_string(), "127.0.0.1");
+        assert_eq!(address.category, IpCategory::Loopback);
+        assert!(
+            check_url("http://[2002:808:808::1]/", &resolver())
+                .unwrap()
+                .allowed
+        );
+
+        assert!(check_url("https://8.8.8.8/", &resolver()).unwrap().allowed);
+        assert!(check_url("http://1.2.3.4.5/", &resolver()).is_err());
+    }
+
+    #[test]
+    fn test_check_url_resolved() {
+        let check = check_url("https://example.com/", &resolver()).unwrap();
+        assert!(!check.literal);
+        assert!(check.allowed);
+        assert_eq!(check.addresses.len(), 2);
+
+        let check = check_url("https://rebind.example/", &resolver()).unwrap();
+        assert!(!check.allowed);
+        assert_eq!(check.denied_by().unwrap().ip.to_string(), "10.0.0.5");
+
+        assert!(!check_url("mapped.example", &resolver()).unwrap().allowed);
+
+        let err = check_url("https://missing.example/", &resolver()).unwrap_err();
+        assert_eq!(err.exit_code(), crate::error::EXIT_NETWORK);
+    }
+}
@@ -0,0 +1,161 @@
+#!/usr/bin/env python3
+"""Generate the small MaxMind DB fixtures used by the unit tests.
+
+Usage: python3 testdata/generate_mmdb.py
+"""
+
+import ipaddress
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+
+def encode_control(type_id, size):
+    if size < 29:
+        head, extra = size, b""
+    elif size < 285:
+        head, extra = 29, bytes([size - 29])
+    elif size < 65821:
+        head, extra = 30, (size - 285).to_bytes(2, "big")
+    else:
+        head, extra = 31, (size - 65821).to_bytes(3, "big")
+    if type_id <= 7:
+        return bytes([(type_id << 5) | head]) + extra
+    return bytes([head, type_id - 7]) + extra
+
+
+def encode(value):
+    if isinstance(value, bool):
+        return encode_control(14, int(value))
+    if isinstance(value, str):
+        data = value.encode("utf-8")
+        return encode_control(2, len(data)) + data
+    if isinstance(value, float):
+        return encode_control(3, 8) + struct.pack(">d", value)
+    if isinstance(value, int):
+        data = value.to_bytes((value.bit_length() + 7) // 8, "big") if value else b""
+        type_id = 6 if value < 2**32 else 9
+        return encode_control(type_id, len(data)) + data
+    if isinstance(value, dict):
+        out = encode_control(7, len(value))
+        for key, item in value.items():
+            out += encode(key) + encode(item)
+        return out
+    if isinstance(value, list):
+        out = encode_control(11, len(value))
+        for item in value:
+            out += encode(item)
+        return out
+    raise TypeError(value)
+
+
+def write_mmdb(path, database_type, records):
+    """records: list of (cidr, dict); IPv4 networks are stored under ::/96"""
+    data = b""
+    tree = [[None, None]]
+    for cidr, record in records:
+        network = ipaddress.ip_network(cidr)
+        if network.version == 4:
+            bits = 96 + network.prefixlen
+            value = int(network.network_address)
+        else:
+            bits = network.prefixlen
+            value = int(network.network_address)
+        offset = len(data)
+        data += encode(record)
+
+        node = 0
+        for depth in range(bits):
+            bit = (value >> (127 - depth)) & 1
+            if depth == bits - 1:
+                tree[node][bit] = ("data", offset)
+            else:
+                child = tree[node][bit]
+                if child is None:
+                    tree.append([None, None])
+                    child = ("node", len(tree) - 1)
+                    tree[node][bit] = child
+                node = child[1]
+
+    node_count = len(tree)
+
+    def record_value(entry):
+        if entry is None:
+            return node_count
+        kind, number = entry
+        if kind == "node":
+            return number
+        return node_count + 16 + number
+
+    out = b""
+    for left, right in tree:
+        out += record_value(left).to_bytes(3, "big") + record_value(right).to_bytes(3, "big")
+    out += b"\x00" * 16
+    out += data
+    out += b"\xab\xcd\xefMaxMind.com"
+    out += encode(
+        {
+            "binary_format_major_version": 2,
+            "binary_format_minor_version": 0,
+            "build_epoch": 1700000000,
+            "database_type": database_type,
+            "description": {"en": "ipchecker test fixture"},
+            "ip_version": 6,
+            "languages": ["en"],
+            "node_count": node_count,
+            "record_size": 24,
+        }
+    )
+    with open(path, "wb") as f:
+        f.write(out)
+
+
+def names(en):
+    return {"en": en}
+
+
+JAPAN = {"geoname_id": 1861060, "iso_code": "JP", "names": names("Japan")}
+ASIA = {"code": "AS", "geoname_id": 6255147, "names": names("Asia")}
+EUROPE = {"code": "EU", "geoname_id": 6255148, "names": names("Europe")}
+
+write_mmdb(
+    os.path.join(HERE, "GeoLite2-City-Test.mmdb"),
+    "GeoLite2-City",
+    [
+        (
+            "1.0.16.0/24",
+            {
+                "city": {"geoname_id": 1850147, "names": names("Tokyo")},
+                "continent": ASIA,
+                "country": JAPAN,
+                "location": {"latitude": 35.6895, "longitude": 139.6917, "time_zone": "Asia/Tokyo"},
+                "registered_country": JAPAN,
+                "subdivisions": [{"geoname_id": 1850144, "iso_code": "13", "names": names("Tokyo")}],
+            },
+        ),
+        (
+            "2.125.160.0/19",
+            {
+                "continent": EUROPE,
+                "country": {"geoname_id": 2635167, "iso_code": "GB", "names": names("United Kingdom")},
+                "registered_country": {"geoname_id": 3017382, "iso_code": "FR", "names": names("France")},
+            },
+        ),
+        (
+            "2001:218::/32",
+            {"continent": ASIA, "country": JAPAN, "registered_country": JAPAN},
+        ),
+    ],
+)
+
+write_mmdb(
+    os.path.join(HERE, "GeoLite2-ASN-Test.mmdb"),
+    "GeoLite2-ASN",
+    [
+        ("1.0.16.0/24", {"autonomous_system_number": 2519, "autonomous_system_organization": "ARTERIA Networks Corporation"}),
+        ("1.0.17.0/24", {"autonomous_system_number": 2519, "autonomous_system_organization": "ARTERIA Networks Corporation"}),
+        ("1.1.1.0/24", {"autonomous_system_number": 13335, "autonomous_system_organization": "CLOUDFLARENET"}),
+        ("2001:218::/32", {"autonomous_system_number": 2914, "autonomous_system_organization": "NTT-LTD-2914"}),
+    ],
+)
@@ -0,0 +1,98 @@
+#!/usr/bin/env python3
+"""Generate the small MRT TABLE_DUMP_V2 fixture used by the unit tests.
+
+Usage: python3 testdata/generate_mrt.py
+"""
+
+import ipaddress
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+TIMESTAMP = 1700000000
+
+TABLE_DUMP_V2 = 13
+BGP4MP = 16
+PEER_INDEX_TABLE = 1
+RIB_IPV4_UNICAST = 2
+RIB_IPV6_UNICAST = 4
+RIB_IPV4_UNICAST_ADDPATH = 8
+
+AS_SET = 1
+AS_SEQUENCE = 2
+
+
+def record(mrt_type, subtype, body):
+    return struct.pack(">IHHI", TIMESTAMP, mrt_type, subtype, len(body)) + body
+
+
+def peer_index_table(peers):
+    body = ipaddress.ip_address("192.0.2.254").packed
+    body += struct.pack(">H", 4) + b"test"
+    body += struct.pack(">H", len(peers))
+    for peer_ip, peer_as in peers:
+        address = ipaddress.ip_address(peer_ip)
+        # Peer type: bit 0 = IPv6 address, bit 1 = 4-byte AS number
+        peer_type = 0x02 | (0x01 if address.version == 6 else 0x00)
+        body += bytes([peer_type]) + ipaddress.ip_address("192.0.2.1").packed
+        body += address.packed + struct.pack(">I", peer_as)
+    return record(TABLE_DUMP_V2, PEER_INDEX_TABLE, body)
+
+
+def as_path(*segments):
+    value = b""
+    for segment_type, asns in segments:
+        value += bytes([segment_type, len(asns)])
+        value += b"".join(struct.pack(">I", asn) for asn in asns)
+    # ORIGIN (IGP) followed by AS_PATH, both well-known transitive
+    attributes = bytes([0x40, 1, 1, 0])
+    if len(value) > 255:
+        attributes += bytes([0x50, 2]) + struct.pack(">H", len(value)) + value
+    else:
+        attributes += bytes([0x40, 2, len(value)]) + value
+    return attributes
+
+
+def rib(sequence, prefix, entries, addpath=False):
+    network = ipaddress.ip_network(prefix)
+    if network.version == 4:
+        subtype = RIB_IPV4_UNICAST_ADDPATH if addpath else RIB_IPV4_UNICAST
+    else:
+        subtype = RIB_IPV6_UNICAST
+    length = (network.prefixlen + 7) // 8
+    body = struct.pack(">IB", sequence, network.prefixlen)
+    body += network.network_address.packed[:length]
+    body += struct.pack(">H", len(entries))
+    for path_id, (peer, attributes) in enumerate(entries):
+        body += struct.pack(">HI", peer, TIMESTAMP)
+        if addpath:
+            body += struct.pack(">I", path_id + 1)
+        body += struct.pack(">H", len(attributes)) + attributes
+    return record(TABLE_DUMP_V2, subtype, body)
+
+
+def seq(*asns):
+    return as_path((AS_SEQUENCE, list(asns)))
+
+
+records = [
+    peer_index_table([("192.0.2.1", 3356), ("192.0.2.2", 174), ("2001:db8::3", 6939)]),
+    # Every peer sees the same origin
+    rib(0, "1.0.0.0/24", [(0, seq(3356, 13335)), (1, seq(174, 13335))]),
+    # A covering route and a more specific one with another origin
+    rib(1, "1.0.4.0/22", [(0, seq(3356, 38803))]),
+    rib(2, "1.0.4.0/24", [(1, seq(174, 4608, 4608))]),
+    # Path ending in an AS_SET: no single origin, so the prefix is skipped
+    rib(3, "10.0.0.0/8", [(0, as_path((AS_SEQUENCE, [3356]), (AS_SET, [64500, 64501])))]),
+    # Peers disagree (MOAS): the origin seen by most peers wins
+    rib(4, "192.0.2.0/24", [(0, seq(3356, 64496)), (1, seq(174, 64497)), (2, seq(6939, 64497))]),
+    # A single-member AS_SET names the origin
+    rib(5, "198.51.100.0/24", [(0, as_path((AS_SEQUENCE, [3356]), (AS_SET, [64498])))]),
+    rib(6, "203.0.113.0/24", [(0, seq(3356, 64499)), (1, seq(174, 64499))], addpath=True),
+    rib(7, "2001:db8::/32", [(2, seq(6939, 64511))]),
+    # Records of other MRT types are ignored
+    record(BGP4MP, 4, b"\x00" * 20),
+]
+
+with open(os.path.join(HERE, "rib-test.mrt"), "wb") as f:
+    f.write(b"".join(records))